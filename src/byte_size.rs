@@ -0,0 +1,124 @@
+use crate::core::{EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::ErrorReason;
+use std::borrow::Cow;
+
+/// Unit suffixes, matched case-insensitively; `K`/`KB` are SI (powers of 1000),
+/// `Ki`/`KiB` are binary (powers of 1024).
+const UNITS: &[(&str, u64)] = &[
+    ("b", 1),
+    ("k", 1000),
+    ("kb", 1000),
+    ("m", 1000_u64.pow(2)),
+    ("mb", 1000_u64.pow(2)),
+    ("g", 1000_u64.pow(3)),
+    ("gb", 1000_u64.pow(3)),
+    ("t", 1000_u64.pow(4)),
+    ("tb", 1000_u64.pow(4)),
+    ("p", 1000_u64.pow(5)),
+    ("pb", 1000_u64.pow(5)),
+    ("ki", 1 << 10),
+    ("kib", 1 << 10),
+    ("mi", 1 << 20),
+    ("mib", 1 << 20),
+    ("gi", 1 << 30),
+    ("gib", 1 << 30),
+    ("ti", 1 << 40),
+    ("tib", 1 << 40),
+    ("pi", 1 << 50),
+    ("pib", 1 << 50),
+];
+
+/// Units `render` picks from, largest first; binary units are preferred when both fit
+const RENDER_UNITS: &[(&str, u64)] = &[
+    ("PiB", 1 << 50),
+    ("PB", 1000_u64.pow(5)),
+    ("TiB", 1 << 40),
+    ("TB", 1000_u64.pow(4)),
+    ("GiB", 1 << 30),
+    ("GB", 1000_u64.pow(3)),
+    ("MiB", 1 << 20),
+    ("MB", 1000_u64.pow(2)),
+    ("KiB", 1 << 10),
+    ("KB", 1000),
+];
+
+/// A number of bytes, parsed from plain integers (`512`) or sizes with a unit
+/// (`64K`, `10MiB`, `2GB`).
+///
+/// `K`, `M`, `G`, `T`, `P` (optionally followed by `B`) are SI units, powers of
+/// 1000; `Ki`, `Mi`, `Gi`, `Ti`, `Pi` (optionally followed by `B`) are binary
+/// units, powers of 1024. Units are case-insensitive and may be separated from
+/// the number by spaces.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByteSize(pub u64);
+
+impl ByteSize {
+    pub fn bytes(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Renders the size with the largest unit that represents it exactly, e.g. `10MiB`
+impl std::fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (name, scale) in RENDER_UNITS {
+            if self.0 >= *scale && self.0.is_multiple_of(*scale) {
+                return write!(f, "{}{}", self.0 / scale, name);
+            }
+        }
+        write!(f, "{}", self.0)
+    }
+}
+
+fn parse_byte_size(value: &str) -> Result<u64, String> {
+    let digits = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    if digits == 0 {
+        return Err("expected a number of bytes".to_string());
+    }
+    let amount: u64 = value[..digits]
+        .parse()
+        .map_err(|_| "number too large".to_string())?;
+
+    let unit = value[digits..].trim_start();
+    if unit.is_empty() {
+        return Ok(amount);
+    }
+    let Some((_, scale)) = UNITS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(unit))
+    else {
+        return Err(format!(
+            "unknown unit {:?}, expected one of B, K, M, G, T, P (SI) or Ki, Mi, Gi, Ti, Pi (binary), optionally followed by B",
+            unit
+        ));
+    };
+    amount
+        .checked_mul(*scale)
+        .ok_or_else(|| "size overflows u64".to_string())
+}
+
+impl EnvarParse<ByteSize> for EnvarParser<ByteSize> {
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<ByteSize, EnvarError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(EnvarError::TryDefault(varname));
+        }
+
+        match parse_byte_size(value) {
+            Ok(bytes) => Ok(ByteSize(bytes)),
+            Err(reason) => Err(EnvarError::ParseError {
+                varname,
+                typename: "ByteSize",
+                value: value.to_string(),
+                reason: ErrorReason::new(move || reason),
+            }),
+        }
+    }
+
+    fn render(value: &ByteSize) -> Option<String> {
+        Some(value.to_string())
+    }
+}
@@ -0,0 +1,197 @@
+use crate::core::{EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::secret::SecretValue;
+use crate::ErrorReason;
+use std::borrow::Cow;
+
+/// Where `HexBytes` and `Base64Bytes` put the decoded bytes: a `Vec<u8>` of any
+/// length, or a `[u8; N]` that rejects any other length, e.g. for 32-byte keys.
+pub trait ByteBuf: AsRef<[u8]> + Sized {
+    fn from_decoded(bytes: Vec<u8>) -> Result<Self, String>;
+}
+
+impl ByteBuf for Vec<u8> {
+    fn from_decoded(bytes: Vec<u8>) -> Result<Self, String> {
+        Ok(bytes)
+    }
+}
+
+impl<const N: usize> ByteBuf for [u8; N] {
+    fn from_decoded(bytes: Vec<u8>) -> Result<Self, String> {
+        let len = bytes.len();
+        bytes
+            .try_into()
+            .map_err(|_| format!("expected {} bytes, found {}", N, len))
+    }
+}
+
+/// Bytes written as hex digits (either case, optionally prefixed with `0x`),
+/// held in a `Vec<u8>` or, for a checked length, a `[u8; N]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct HexBytes<B = Vec<u8>>(pub B);
+
+/// Bytes written in base64, standard (`+/`) or URL-safe (`-_`) alphabet, with or
+/// without `=` padding, held in a `Vec<u8>` or, for a checked length, a `[u8; N]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Base64Bytes<B = Vec<u8>>(pub B);
+
+impl<B> HexBytes<B> {
+    pub fn into_inner(self) -> B {
+        self.0
+    }
+}
+
+impl<B> Base64Bytes<B> {
+    pub fn into_inner(self) -> B {
+        self.0
+    }
+}
+
+impl<B> std::ops::Deref for HexBytes<B> {
+    type Target = B;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<B> std::ops::Deref for Base64Bytes<B> {
+    type Target = B;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<B: SecretValue> SecretValue for HexBytes<B> {
+    fn wipe(&mut self) {
+        self.0.wipe();
+    }
+}
+
+impl<B: SecretValue> SecretValue for Base64Bytes<B> {
+    fn wipe(&mut self) {
+        self.0.wipe();
+    }
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>, String> {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value)
+        .as_bytes();
+    if !digits.len().is_multiple_of(2) {
+        return Err(format!("odd number of hex digits ({})", digits.len()));
+    }
+    let nibble = |index: usize| {
+        (digits[index] as char)
+            .to_digit(16)
+            .map(|n| n as u8)
+            .ok_or_else(|| format!("invalid hex digit at position {}", index + 1))
+    };
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| Ok(nibble(i)? << 4 | nibble(i + 1)?))
+        .collect()
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_sextet(c: u8) -> Option<u32> {
+    match c {
+        b'A'..=b'Z' => Some((c - b'A') as u32),
+        b'a'..=b'z' => Some((c - b'a' + 26) as u32),
+        b'0'..=b'9' => Some((c - b'0' + 52) as u32),
+        b'+' | b'-' => Some(62),
+        b'/' | b'_' => Some(63),
+        _ => None,
+    }
+}
+
+fn decode_base64(value: &str) -> Result<Vec<u8>, String> {
+    let chars = value.trim_end_matches('=').as_bytes();
+    if chars.len() % 4 == 1 {
+        return Err("truncated base64 input".to_string());
+    }
+    let mut bytes = Vec::with_capacity(chars.len() * 3 / 4);
+    for (chunk_index, chunk) in chars.chunks(4).enumerate() {
+        let mut group = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            let sextet = base64_sextet(*c).ok_or_else(|| {
+                format!(
+                    "invalid base64 character at position {}",
+                    chunk_index * 4 + i + 1
+                )
+            })?;
+            group |= sextet << (18 - 6 * i);
+        }
+        let group = group.to_be_bytes();
+        bytes.extend_from_slice(&group[1..chunk.len()]);
+    }
+    Ok(bytes)
+}
+
+/// Standard alphabet, padded
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let mut group = [0u8; 4];
+        group[1..=chunk.len()].copy_from_slice(chunk);
+        let group = u32::from_be_bytes(group);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn parse_bytes<B: ByteBuf>(
+    varname: Cow<'static, str>,
+    typename: &'static str,
+    value: &str,
+    decode: fn(&str) -> Result<Vec<u8>, String>,
+) -> Result<B, EnvarError> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(EnvarError::TryDefault(varname));
+    }
+    match decode(value).and_then(B::from_decoded) {
+        Ok(bytes) => Ok(bytes),
+        Err(reason) => Err(EnvarError::ParseError {
+            varname,
+            typename,
+            value: value.to_string(),
+            reason: ErrorReason::new(move || reason),
+        }),
+    }
+}
+
+impl<B: ByteBuf> EnvarParse<HexBytes<B>> for EnvarParser<HexBytes<B>> {
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<HexBytes<B>, EnvarError> {
+        parse_bytes(varname, "HexBytes", value, decode_hex).map(HexBytes)
+    }
+
+    fn render(value: &HexBytes<B>) -> Option<String> {
+        Some(encode_hex(value.0.as_ref()))
+    }
+}
+
+impl<B: ByteBuf> EnvarParse<Base64Bytes<B>> for EnvarParser<Base64Bytes<B>> {
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<Base64Bytes<B>, EnvarError> {
+        parse_bytes(varname, "Base64Bytes", value, decode_base64).map(Base64Bytes)
+    }
+
+    fn render(value: &Base64Bytes<B>) -> Option<String> {
+        Some(encode_base64(value.0.as_ref()))
+    }
+}
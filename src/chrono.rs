@@ -0,0 +1,87 @@
+use crate::core::{EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::ErrorReason;
+use ::chrono::{DateTime, FixedOffset, NaiveDate, SecondsFormat, Utc};
+use std::borrow::Cow;
+
+fn parse_error(
+    varname: Cow<'static, str>,
+    typename: &'static str,
+    value: &str,
+    reason: String,
+) -> EnvarError {
+    EnvarError::ParseError {
+        varname,
+        typename,
+        value: value.to_string(),
+        reason: ErrorReason::new(move || reason),
+    }
+}
+
+/// An RFC 3339 timestamp (`2024-05-01T09:00:00+02:00`) or a number of seconds
+/// since the unix epoch, either way without the offset it was written with.
+fn parse_date_time(value: &str) -> Result<DateTime<FixedOffset>, String> {
+    if let Ok(seconds) = value.parse::<i64>() {
+        return DateTime::from_timestamp(seconds, 0)
+            .map(|utc| utc.fixed_offset())
+            .ok_or_else(|| "unix timestamp out of range".to_string());
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map_err(|e| format!("expected an RFC 3339 date-time or a unix timestamp: {}", e))
+}
+
+/// Accepts RFC 3339 (converted to UTC) or unix timestamps in seconds; renders RFC 3339.
+impl EnvarParse<DateTime<Utc>> for EnvarParser<DateTime<Utc>> {
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<DateTime<Utc>, EnvarError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(EnvarError::TryDefault(varname));
+        }
+        parse_date_time(value)
+            .map(|date_time| date_time.to_utc())
+            .map_err(|reason| parse_error(varname, "DateTime<Utc>", value, reason))
+    }
+
+    fn render(value: &DateTime<Utc>) -> Option<String> {
+        Some(value.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+    }
+}
+
+/// Like `DateTime<Utc>`, but keeps the offset the value was written with
+/// (unix timestamps get `+00:00`).
+impl EnvarParse<DateTime<FixedOffset>> for EnvarParser<DateTime<FixedOffset>> {
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<DateTime<FixedOffset>, EnvarError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(EnvarError::TryDefault(varname));
+        }
+        parse_date_time(value)
+            .map_err(|reason| parse_error(varname, "DateTime<FixedOffset>", value, reason))
+    }
+
+    fn render(value: &DateTime<FixedOffset>) -> Option<String> {
+        Some(value.to_rfc3339_opts(SecondsFormat::AutoSi, false))
+    }
+}
+
+/// Accepts RFC 3339 full dates, `2024-05-01`.
+impl EnvarParse<NaiveDate> for EnvarParser<NaiveDate> {
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<NaiveDate, EnvarError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(EnvarError::TryDefault(varname));
+        }
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|e| {
+            parse_error(
+                varname,
+                "NaiveDate",
+                value,
+                format!("expected a date such as 2024-05-01: {}", e),
+            )
+        })
+    }
+
+    fn render(value: &NaiveDate) -> Option<String> {
+        Some(value.format("%Y-%m-%d").to_string())
+    }
+}
@@ -1,12 +1,82 @@
 use crate::error::EnvarError;
-use crate::list_envar::ListEnvar;
-use crate::list_envar::ListEnvarConfig;
+use crate::provenance::Provenance;
+use crate::secret::SecretValue;
+use crate::source::EnvSource;
+use crate::stats::{EnvarStats, StatsCell};
 use crate::ErrorReason;
 use std::borrow::Cow;
+use std::ffi::{OsStr, OsString};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
+};
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{PoisonError, RwLock};
+use std::time::{Duration, Instant};
 
+static MAX_VALUE_LEN: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// Sets the default cap on the raw length (in bytes) of any environment variable value.
+///
+/// Unlimited by default; `Envar::with_max_len` overrides it per variable.
+pub fn set_max_value_len(max_len: usize) {
+    MAX_VALUE_LEN.store(max_len, Ordering::Relaxed);
+}
+
+pub fn max_value_len() -> usize {
+    MAX_VALUE_LEN.load(Ordering::Relaxed)
+}
+
+/// (layer generation, raw value, parsed value or parse failure, origin of the value)
+type OnDemandEntry<T> = (
+    u64,
+    Option<String>,
+    Option<Result<T, EnvarError>>,
+    Option<Resolved>,
+);
+
+/// (read at, layer generation, parsed value)
+type TtlEntry<T> = Option<(Instant, u64, T)>;
+
+/// Where `lookup_found` found a raw value
+struct Found<'a> {
+    /// the primary name or the alias that is set
+    name: &'a str,
+    found_in: FoundIn,
+}
+
+enum FoundIn {
+    TestScope,
+    /// an override layer, by index from the bottom
+    Layer(usize),
+    /// the original value, kept when it is not valid UTF-8
+    Source(Option<std::ffi::OsString>),
+    /// the file at this path, named by `<NAME>_FILE`
+    File(String),
+}
+
+/// Where a resolved value came from, and the raw value it was parsed from
+#[derive(Clone, PartialEq)]
+struct Resolved {
+    provenance: Provenance,
+    raw: Option<std::ffi::OsString>,
+}
+
+impl Resolved {
+    const DEFAULT: Resolved = Resolved {
+        provenance: Provenance::Default,
+        raw: None,
+    };
+}
+
+// variants are named after the constructors that select them
+#[allow(clippy::enum_variant_names)]
 enum EnvarStore<T> {
     OnStartup(std::sync::OnceLock<T>),
-    OnDemand(std::sync::Mutex<(Option<String>, Option<T>)>),
+    OnDemand(std::sync::Mutex<OnDemandEntry<T>>),
+    OnDemandTtl(Duration, std::sync::Mutex<TtlEntry<T>>),
 }
 
 pub enum EnvarDef<T> {
@@ -23,121 +93,1232 @@ impl<T> EnvarDef<T> {
     }
 }
 
+/// What `Envar::value` does when a set value fails to parse.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OnParseError {
+    /// Return the parse error (the default)
+    #[default]
+    Fail,
+    /// Silently fall back to the default, if there is one
+    UseDefault,
+    /// Fall back to the default, if there is one, and emit a warning
+    UseDefaultAndWarn,
+}
+
+/// What `Envar::value` does when the process environment holds a value
+/// that is not valid UTF-8.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NonUnicode {
+    /// Return `EnvarError::NotUnicode` (the default)
+    #[default]
+    Error,
+    /// Replace invalid sequences with `U+FFFD` and parse the result
+    Lossy,
+    /// Behave as if the variable were not set
+    TreatAsUnset,
+}
+
+/// Declarative information about an `Envar`, set through its builder methods.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EnvarMeta {
+    pub sensitive: bool,
+    pub description: Option<&'static str>,
+    /// The group the variable is documented under, e.g. "Database"
+    pub category: Option<&'static str>,
+    /// A sample raw value, shown when a required variable is missing
+    pub example: Option<&'static str>,
+    /// The unit the value is measured in, e.g. "seconds"
+    pub unit: Option<&'static str>,
+}
+
+impl EnvarMeta {
+    pub const fn new() -> Self {
+        Self {
+            sensitive: false,
+            description: None,
+            category: None,
+            example: None,
+            unit: None,
+        }
+    }
+}
+
+/// A check on a parsed value, returning the reason it is rejected
+pub type Validator<T> = fn(&T) -> Result<(), String>;
+
+/// How many validators one `Envar` can hold
+pub const MAX_VALIDATORS: usize = 8;
+
+/// Bounds set by `in_range`, with a check that knows `T: PartialOrd`
+struct RangeCheck<T> {
+    start: T,
+    end: T,
+    check: fn(&T, &T, &T) -> Result<(), String>,
+}
+
 pub struct Envar<T, F = fn() -> EnvarDef<T>> {
-    _name: &'static str,
+    _name: Cow<'static, str>,
     _default_factory: F,
+    _max_len: Option<usize>,
+    _on_parse_error: OnParseError,
+    _required_when: Option<fn() -> bool>,
+    _non_unicode: NonUnicode,
+    _serve_stale_on_error: bool,
+    /// fallback names, consulted in order when `_name` is not set
+    _aliases: &'static [&'static str],
+    /// fallback names consulted after `_aliases`, with a warning when used
+    _deprecated_aliases: &'static [&'static str],
+    _deprecation_warned: AtomicBool,
+    /// read instead of the global source when set
+    _source: Option<&'static dyn EnvSource>,
+    /// whether `<NAME>_FILE` is consulted when `<NAME>` is not set
+    _file_fallback: bool,
+    /// checks run on every parsed value, filled in order
+    _validators: [Option<Validator<T>>; MAX_VALIDATORS],
+    /// bounds set by `in_range`
+    _range: Option<RangeCheck<T>>,
+    /// the raw values accepted by `one_of`, unrestricted when empty
+    _allowed: &'static [&'static str],
+    /// set by `set_override`, shadowing every other source
+    _override: RwLock<Option<T>>,
+    /// where the last resolved value came from
+    _resolved: RwLock<Option<Resolved>>,
+    _meta: EnvarMeta,
+    _stats: StatsCell,
     /// used when loaded on startup
     store: EnvarStore<T>,
 }
 
+impl<T> Envar<T>
+where
+    T: Clone + 'static,
+    EnvarParser<T>: EnvarParse<T>,
+{
+    /// Takes a plain `fn` (or non-capturing closure) so that builder chains such as
+    /// `Envar::on_demand(..).with_max_len(n)` still fit a `static Envar<T>`.
+    /// Use `on_demand_with` for any other default factory.
+    pub const fn on_demand(name: &'static str, default_factory: fn() -> EnvarDef<T>) -> Self {
+        Self::on_demand_with(name, default_factory)
+    }
+
+    /// Takes a plain `fn` like `on_demand`; use `on_startup_with` for any other default factory.
+    pub const fn on_startup(name: &'static str, default_factory: fn() -> EnvarDef<T>) -> Self {
+        Self::on_startup_with(name, default_factory)
+    }
+
+    pub const fn on_demand_ttl(
+        name: &'static str,
+        ttl: Duration,
+        default_factory: fn() -> EnvarDef<T>,
+    ) -> Self {
+        Self::on_demand_ttl_with(name, ttl, default_factory)
+    }
+
+    /// Like `on_demand`, but falls back to `aliases` in order when `name` is not set,
+    /// e.g. to keep renamed variables working during a migration.
+    pub const fn on_demand_with_aliases(
+        name: &'static str,
+        aliases: &'static [&'static str],
+        default_factory: fn() -> EnvarDef<T>,
+    ) -> Self {
+        Self::on_demand(name, default_factory).aliases(aliases)
+    }
+
+    /// An on-demand variable without a default, for use with `expect_value`.
+    pub const fn required(name: &'static str) -> Self {
+        Self::on_demand(name, || EnvarDef::Unset)
+    }
+}
+
 impl<T, F> Envar<T, F>
 where
     T: Clone + 'static,
     EnvarParser<T>: EnvarParse<T>,
     F: Fn() -> EnvarDef<T>,
 {
-    pub const fn on_demand(name: &'static str, default_factory: F) -> Self {
+    /// Every constructor goes through here, leaving the builder settings at their defaults
+    const fn base(name: Cow<'static, str>, store: EnvarStore<T>, default_factory: F) -> Self {
         Self {
             _name: name,
             _default_factory: default_factory,
-            store: EnvarStore::OnDemand(std::sync::Mutex::new((None, None))),
+            _max_len: None,
+            _on_parse_error: OnParseError::Fail,
+            _required_when: None,
+            _non_unicode: NonUnicode::Error,
+            _serve_stale_on_error: false,
+            _aliases: &[],
+            _deprecated_aliases: &[],
+            _deprecation_warned: AtomicBool::new(false),
+            _source: None,
+            _file_fallback: false,
+            _validators: [None; MAX_VALIDATORS],
+            _range: None,
+            _allowed: &[],
+            _override: RwLock::new(None),
+            _resolved: RwLock::new(None),
+            _meta: EnvarMeta::new(),
+            _stats: StatsCell::new(),
+            store,
         }
     }
 
-    pub const fn on_startup(name: &'static str, default_factory: F) -> Self {
-        Self {
-            _name: name,
-            _default_factory: default_factory,
-            store: EnvarStore::OnStartup(std::sync::OnceLock::new()),
+    /// Like `on_demand`, but accepts any default factory, e.g. a capturing closure.
+    pub const fn on_demand_with(name: &'static str, default_factory: F) -> Self {
+        Self::base(
+            Cow::Borrowed(name),
+            EnvarStore::OnDemand(std::sync::Mutex::new((0, None, None, None))),
+            default_factory,
+        )
+    }
+
+    /// Like `on_startup`, but accepts any default factory, e.g. a capturing closure.
+    pub const fn on_startup_with(name: &'static str, default_factory: F) -> Self {
+        Self::base(
+            Cow::Borrowed(name),
+            EnvarStore::OnStartup(std::sync::OnceLock::new()),
+            default_factory,
+        )
+    }
+
+    /// Like `on_demand_with`, but a parsed value is reused for `ttl` before the
+    /// environment is read again. Pushing or popping an override layer
+    /// expires it early; failures are not cached.
+    pub const fn on_demand_ttl_with(name: &'static str, ttl: Duration, default_factory: F) -> Self {
+        Self::base(
+            Cow::Borrowed(name),
+            EnvarStore::OnDemandTtl(ttl, std::sync::Mutex::new(None)),
+            default_factory,
+        )
+    }
+
+    /// Like `on_demand_with`, but the name may be computed at runtime,
+    /// e.g. from a per-tenant or per-plugin prefix.
+    pub fn on_demand_named(name: impl Into<Cow<'static, str>>, default_factory: F) -> Self {
+        Self::base(
+            name.into(),
+            EnvarStore::OnDemand(std::sync::Mutex::new((0, None, None, None))),
+            default_factory,
+        )
+    }
+
+    /// Like `on_startup_with`, but the name may be computed at runtime.
+    pub fn on_startup_named(name: impl Into<Cow<'static, str>>, default_factory: F) -> Self {
+        Self::base(
+            name.into(),
+            EnvarStore::OnStartup(std::sync::OnceLock::new()),
+            default_factory,
+        )
+    }
+
+    /// Caps the raw length (in bytes) of this variable's value, overriding `set_max_value_len`.
+    pub const fn with_max_len(mut self, max_len: usize) -> Self {
+        self._max_len = Some(max_len);
+        self
+    }
+
+    /// Sets what happens when a set value fails to parse; `OnParseError::Fail` by default.
+    pub const fn on_parse_error(mut self, policy: OnParseError) -> Self {
+        self._on_parse_error = policy;
+        self
+    }
+
+    /// Sets what happens when the value is not valid UTF-8; `NonUnicode::Error` by default.
+    pub const fn on_non_unicode(mut self, policy: NonUnicode) -> Self {
+        self._non_unicode = policy;
+        self
+    }
+
+    /// When a changed value fails to parse, or reading the value fails (e.g. the
+    /// source reports `EnvarError::SourceError` once its `RetryPolicy` gives up),
+    /// keeps serving the last good value (with a warning) instead of returning
+    /// the error. Only affects `on_demand` variables, and applies after the
+    /// `OnParseError` policy.
+    pub const fn serve_stale_on_error(mut self) -> Self {
+        self._serve_stale_on_error = true;
+        self
+    }
+
+    /// Makes the variable mandatory whenever `condition` holds, even if it has a default,
+    /// e.g. `.required_when(|| matches!(DEPLOY_ENV.value(), Ok(DeploymentEnv::Production)))`.
+    ///
+    /// The condition is checked by `registry::validate_all`; `value()` still uses the default.
+    pub const fn required_when(mut self, condition: fn() -> bool) -> Self {
+        self._required_when = Some(condition);
+        self
+    }
+
+    /// Whether a `required_when` condition currently makes the variable mandatory
+    #[cfg(feature = "registry")]
+    pub(crate) fn conditionally_required(&self) -> bool {
+        self._required_when.is_some_and(|condition| condition())
+    }
+
+    /// Marks the variable as holding a secret, so generated manifests route it
+    /// through secret stores rather than plain configuration.
+    pub const fn sensitive(mut self) -> Self {
+        self._meta.sensitive = true;
+        self
+    }
+
+    /// A human-readable description, surfaced in generated documentation.
+    pub const fn description(mut self, description: &'static str) -> Self {
+        self._meta.description = Some(description);
+        self
+    }
+
+    /// Fallback names consulted in order when the primary name is not set.
+    pub const fn aliases(mut self, aliases: &'static [&'static str]) -> Self {
+        self._aliases = aliases;
+        self
+    }
+
+    /// Fallback names consulted after the aliases. Reading a value through one
+    /// emits a warning (once per variable) naming the primary name to use instead.
+    pub const fn deprecated_aliases(mut self, aliases: &'static [&'static str]) -> Self {
+        self._deprecated_aliases = aliases;
+        self
+    }
+
+    /// Reads this variable from `source` instead of the global source
+    /// (the process environment unless replaced with `set_source`).
+    pub const fn source(mut self, source: &'static dyn EnvSource) -> Self {
+        self._source = Some(source);
+        self
+    }
+
+    /// When the variable is not set, reads its value from the file named by
+    /// `<NAME>_FILE` instead (e.g. `DB_PASSWORD_FILE=/run/secrets/db`), as with
+    /// Docker and Kubernetes secrets. Sensitive variables do this without asking.
+    pub const fn with_file_fallback(mut self) -> Self {
+        self._file_fallback = true;
+        self
+    }
+
+    /// Runs `validator` on every parsed value, after the validators added before it
+    /// (at most `MAX_VALIDATORS`). A rejected value is reported as
+    /// `EnvarError::ParseError` with the returned reason, and goes through the
+    /// `on_parse_error` policy like any other parse failure.
+    pub const fn with_validator(mut self, validator: Validator<T>) -> Self {
+        let mut i = 0;
+        while i < MAX_VALIDATORS {
+            if self._validators[i].is_none() {
+                self._validators[i] = Some(validator);
+                return self;
+            }
+            i += 1;
         }
+        panic!("too many validators on one Envar");
+    }
+
+    /// Groups the variable under `category` in generated documentation and manifests.
+    pub const fn category(mut self, category: &'static str) -> Self {
+        self._meta.category = Some(category);
+        self
     }
 
-    pub fn name(&self) -> &'static str {
-        self._name
+    /// A sample raw value, shown by `expect_value` and `value_or_exit`.
+    pub const fn example(mut self, example: &'static str) -> Self {
+        self._meta.example = Some(example);
+        self
+    }
+
+    /// The unit the value is measured in, e.g. "seconds" or "MiB".
+    pub const fn unit(mut self, unit: &'static str) -> Self {
+        self._meta.unit = Some(unit);
+        self
+    }
+
+    pub fn describe(&self) -> &EnvarMeta {
+        &self._meta
+    }
+
+    /// Whether the variable was marked `sensitive`, or its type always is (e.g. `Secret<T>`)
+    pub fn is_sensitive(&self) -> bool {
+        self._meta.sensitive || <EnvarParser<T> as EnvarParse<T>>::SENSITIVE
+    }
+
+    pub fn name(&self) -> &str {
+        &self._name
+    }
+
+    /// The raw value from the topmost override layer defining it, else the process environment
+    /// (or, failing that, from the first alias that is set)
+    pub(crate) fn lookup_raw(&self) -> Result<Option<String>, EnvarError> {
+        Ok(self.lookup_found()?.map(|(raw, _)| raw))
+    }
+
+    /// `lookup_raw`, along with where the raw value was found
+    fn lookup_found(&self) -> Result<Option<(String, Found<'_>)>, EnvarError> {
+        for name in self.lookup_names() {
+            let mut found = self.lookup_name(name)?;
+            if found.is_none() && self.reads_file_fallback() {
+                if let Some((path, _)) = self.lookup_name(&file_varname(name))? {
+                    let raw = read_value_file(name, &path)?;
+                    found = Some((raw, FoundIn::File(path)));
+                }
+            }
+            if let Some((raw, found_in)) = found {
+                if self._deprecated_aliases.contains(&name)
+                    && !self._deprecation_warned.swap(true, Ordering::Relaxed)
+                {
+                    crate::warnings::emit(&format!(
+                        "environment variable {} is deprecated, use {} instead",
+                        name, self._name
+                    ));
+                }
+                return Ok(Some((raw, Found { name, found_in })));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The primary name followed by the aliases and deprecated aliases, in lookup order
+    pub(crate) fn lookup_names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self._name.as_ref())
+            .chain(self._aliases.iter().copied())
+            .chain(self._deprecated_aliases.iter().copied())
+    }
+
+    fn lookup_name(&self, name: &str) -> Result<Option<(String, FoundIn)>, EnvarError> {
+        if let Some(scoped) = crate::test::lookup(name) {
+            return Ok(scoped.map(|raw| (raw, FoundIn::TestScope)));
+        }
+        match crate::layers::lookup(name) {
+            Some((index, raw)) => Ok(Some((raw, FoundIn::Layer(index)))),
+            None => match self.read_source(name)?.map(std::ffi::OsString::into_string) {
+                None => Ok(None),
+                Some(Ok(raw)) => Ok(Some((raw, FoundIn::Source(None)))),
+                Some(Err(original)) if <EnvarParser<T> as EnvarParse<T>>::ACCEPTS_NON_UNICODE => {
+                    let raw = original.to_string_lossy().into_owned();
+                    Ok(Some((raw, FoundIn::Source(Some(original)))))
+                }
+                Some(Err(original)) => Ok(self
+                    .decode_raw(Some(original.clone()))?
+                    .map(|raw| (raw, FoundIn::Source(Some(original))))),
+            },
+        }
+    }
+
+    fn resolved_from(&self, found: Found<'_>, raw: &str) -> Resolved {
+        let name = found.name.to_string();
+        let (provenance, original) = match found.found_in {
+            FoundIn::TestScope => (Provenance::TestScope { name }, None),
+            FoundIn::Layer(index) => (Provenance::Layer { name, index }, None),
+            FoundIn::Source(original) => (
+                Provenance::Environment {
+                    origin: self.source_origin(&name),
+                    name,
+                },
+                original,
+            ),
+            FoundIn::File(path) => (Provenance::File { name, path }, None),
+        };
+        Resolved {
+            provenance,
+            raw: Some(original.unwrap_or_else(|| raw.into())),
+        }
+    }
+
+    fn record_resolved(&self, resolved: Resolved) {
+        // reads inside a test scope must not leak into other threads or outlive it
+        if crate::test::in_scope() {
+            crate::test::record_resolved(self as *const Self as usize, resolved);
+            return;
+        }
+        // cache hits mostly re-record the same source, which only needs the read lock
+        if self
+            ._resolved
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+            == Some(&resolved)
+        {
+            return;
+        }
+        *self
+            ._resolved
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(resolved);
+    }
+
+    /// Where the value returned by `value()` comes from: the override, or else
+    /// the source of the last value resolved (and possibly cached).
+    ///
+    /// `None` before the first successful read.
+    pub fn provenance(&self) -> Option<Provenance> {
+        if self.override_value().is_some() {
+            return Some(Provenance::Override);
+        }
+        self.last_resolved().map(|resolved| resolved.provenance)
+    }
+
+    /// What the last read resolved, on this thread if it is inside a test scope
+    fn last_resolved(&self) -> Option<Resolved> {
+        if crate::test::in_scope() {
+            if let Some(resolved) = crate::test::resolved(self as *const Self as usize) {
+                return Some(resolved);
+            }
+        }
+        self._resolved
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Resolves the variable like `value()`, returning the raw value the result
+    /// was parsed from, e.g. to forward it to a child process. The raw value is
+    /// the one recorded with the cached value rather than read again, so it still
+    /// matches after the environment changes under an `on_startup` variable.
+    ///
+    /// The raw value of a sensitive variable is returned in plain text too;
+    /// use `raw_redacted` to log it.
+    ///
+    /// Fails with `EnvarError::NotSet` when the value is the default or an override.
+    /// Values that are not valid UTF-8 are returned as decoded by the `NonUnicode`
+    /// policy; see `raw_os` for the original.
+    pub fn raw(&self) -> Result<String, EnvarError> {
+        let raw = self.raw_os()?;
+        Ok(self.decode_raw(Some(raw))?.unwrap_or_default())
+    }
+
+    /// Like `raw`, but `***` for sensitive variables, for logging next to the typed value.
+    pub fn raw_redacted(&self) -> Result<String, EnvarError> {
+        let raw = self.raw()?;
+        Ok(self.display_raw(&raw))
+    }
+
+    /// Like `raw`, but returns the raw value exactly as it was read.
+    pub fn raw_os(&self) -> Result<std::ffi::OsString, EnvarError> {
+        self.value()?;
+        if self.override_value().is_some() {
+            return Err(self.not_set());
+        }
+        self.last_resolved()
+            .and_then(|resolved| resolved.raw)
+            .ok_or_else(|| self.not_set())
+    }
+
+    /// Whether `<NAME>_FILE` is consulted when `<NAME>` is not set
+    pub(crate) fn reads_file_fallback(&self) -> bool {
+        self._file_fallback || self.is_sensitive()
+    }
+
+    /// Reads `name` from this variable's source, or the global one
+    pub(crate) fn read_source(&self, name: &str) -> Result<Option<std::ffi::OsString>, EnvarError> {
+        let read = match self._source {
+            Some(source) => source.try_get(name),
+            None => crate::source::with_global(|source| source.try_get(name)),
+        };
+        read.map_err(|reason| EnvarError::SourceError {
+            varname: Cow::Owned(name.to_string()),
+            reason,
+        })
+    }
+
+    /// Where `read_source` finds `name`, for `explain`
+    pub(crate) fn source_origin(&self, name: &str) -> String {
+        match self._source {
+            Some(source) => source.origin(name),
+            None => crate::source::with_global(|source| source.origin(name)),
+        }
+    }
+
+    /// The error for a variable with no usable value and no default
+    pub(crate) fn not_set(&self) -> EnvarError {
+        if self._aliases.is_empty() && self._deprecated_aliases.is_empty() {
+            return EnvarError::NotSet(self._name.clone());
+        }
+        EnvarError::NotSetAliases {
+            varname: self._name.clone(),
+            aliases: self
+                ._aliases
+                .iter()
+                .chain(self._deprecated_aliases)
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// Applies the `NonUnicode` policy to a value from the process environment
+    pub(crate) fn decode_raw(
+        &self,
+        raw: Option<std::ffi::OsString>,
+    ) -> Result<Option<String>, EnvarError> {
+        let Some(raw) = raw else {
+            return Ok(None);
+        };
+        match raw.into_string() {
+            Ok(raw) => Ok(Some(raw)),
+            Err(raw) => match self._non_unicode {
+                NonUnicode::Error => Err(EnvarError::NotUnicode(self._name.clone())),
+                NonUnicode::Lossy => Ok(Some(raw.to_string_lossy().into_owned())),
+                NonUnicode::TreatAsUnset => Ok(None),
+            },
+        }
+    }
+
+    pub(crate) fn default_value(&self) -> EnvarDef<T> {
+        (self._default_factory)()
+    }
+
+    /// The default to use in place of a value that failed to parse, if the policy allows one
+    pub(crate) fn parse_error_fallback(&self) -> Option<T> {
+        match self._on_parse_error {
+            OnParseError::Fail => None,
+            OnParseError::UseDefault | OnParseError::UseDefaultAndWarn => {
+                (self._default_factory)().to_option()
+            }
+        }
+    }
+
+    fn recover(&self, error: EnvarError) -> Result<T, EnvarError> {
+        match self.parse_error_fallback() {
+            Some(default) => {
+                if self._on_parse_error == OnParseError::UseDefaultAndWarn {
+                    crate::warnings::emit(&format!("{}; falling back to the default", error));
+                }
+                Ok(default)
+            }
+            None => Err(error),
+        }
+    }
+
+    /// Makes `value()` return `value` until `clear_override` is called,
+    /// without touching the process environment.
+    ///
+    /// Takes precedence over override layers, the environment and values
+    /// frozen by `on_startup` reads.
+    pub fn set_override(&self, value: T) {
+        *self
+            ._override
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(value);
+    }
+
+    /// Removes the value set by `set_override`, returning it.
+    pub fn clear_override(&self) -> Option<T> {
+        self._override
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+    }
+
+    /// Like `set_override`, but only for the current thread and until the guard
+    /// is dropped, so parallel tests can each override the same static.
+    pub fn override_scope(&self, value: T) -> crate::test::OverrideGuard {
+        crate::test::scope_override(self as *const Self as usize, value)
+    }
+
+    pub(crate) fn override_value(&self) -> Option<T> {
+        if let Some(value) = crate::test::override_value(self as *const Self as usize) {
+            return Some(value);
+        }
+        self._override
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Drops the cached value of an `on_demand` or `on_demand_ttl` variable, so the
+    /// next read resolves it again. Cached `Secret`s are wiped as they are dropped,
+    /// and with the `zeroize` feature, so are the cached raw values.
+    ///
+    /// Values frozen by `on_startup` reads are kept.
+    pub fn clear_cache(&self) {
+        match &self.store {
+            EnvarStore::OnStartup(_) => return,
+            EnvarStore::OnDemand(mutex) => {
+                let mut entry = mutex.lock().unwrap_or_else(PoisonError::into_inner);
+                entry.1.wipe();
+                if let Some(resolved) = &mut entry.3 {
+                    resolved.raw.wipe();
+                }
+                *entry = (0, None, None, None);
+            }
+            EnvarStore::OnDemandTtl(_, mutex) => {
+                *mutex.lock().unwrap_or_else(PoisonError::into_inner) = None;
+            }
+        }
+        let mut resolved = self
+            ._resolved
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(resolved) = &mut *resolved {
+            resolved.raw.wipe();
+        }
+        *resolved = None;
+    }
+
+    /// The value frozen by an earlier read of an `on_startup` variable
+    pub(crate) fn startup_value(&self) -> Option<&T> {
+        match &self.store {
+            EnvarStore::OnStartup(once_loaded) => once_loaded.get(),
+            EnvarStore::OnDemand(_) | EnvarStore::OnDemandTtl(..) => None,
+        }
+    }
+
+    /// Runtime counters for this variable
+    pub fn stats(&self) -> EnvarStats {
+        self._stats.snapshot()
+    }
+
+    /// Parses a raw value exactly like `value()` does, without recording statistics
+    pub(crate) fn parse_unrecorded(&self, raw: &str) -> Result<T, EnvarError> {
+        let max_len = self._max_len.unwrap_or_else(max_value_len);
+        if raw.len() > max_len {
+            return Err(EnvarError::TooLong {
+                varname: self._name.clone(),
+                len: raw.len(),
+                max_len,
+            });
+        }
+        let value = EnvarParser::<T>::parse(self._name.clone(), raw)
+            .map_err(|e| self.redact_if_sensitive(e))?;
+        self.validate_parsed(&value, raw)?;
+        Ok(value)
+    }
+
+    fn redact_if_sensitive(&self, error: EnvarError) -> EnvarError {
+        if self.is_sensitive() {
+            redact_parse_error(error)
+        } else {
+            error
+        }
+    }
+
+    /// Checks the allowed values and the range, then runs the validators,
+    /// reporting the first failure
+    fn validate_parsed(&self, value: &T, raw: &str) -> Result<(), EnvarError> {
+        if !self._allowed.is_empty() && !self._allowed.contains(&raw) {
+            return Err(EnvarError::NotAllowed {
+                varname: self._name.clone(),
+                value: self.display_raw(raw),
+                allowed: self._allowed,
+                suggestion: crate::suggest::closest(raw, self._allowed),
+            });
+        }
+        if let Some(range) = &self._range {
+            if let Err(range) = (range.check)(value, &range.start, &range.end) {
+                return Err(EnvarError::OutOfRange {
+                    varname: self._name.clone(),
+                    value: self.display_raw(raw),
+                    range,
+                });
+            }
+        }
+        for validator in self._validators.iter().flatten() {
+            if let Err(reason) = validator(value) {
+                return Err(EnvarError::ParseError {
+                    varname: self._name.clone(),
+                    typename: std::any::type_name::<T>(),
+                    value: self.display_raw(raw),
+                    reason: ErrorReason::new(move || reason),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a parse failure depends on the raw value alone: validators such as
+    /// `must_exist` may look at the outside world, as may some parsers
+    fn failures_are_deterministic(&self) -> bool {
+        EnvarParser::<T>::DETERMINISTIC && self._validators.iter().all(Option::is_none)
+    }
+
+    /// `raw` as shown in error messages, masked for sensitive variables
+    fn display_raw(&self, raw: &str) -> String {
+        if self.is_sensitive() {
+            "***".to_string()
+        } else {
+            raw.to_string()
+        }
+    }
+
+    /// Like `parse_unrecorded`, but for values that may not be valid UTF-8
+    fn parse_unrecorded_os(&self, raw: &std::ffi::OsStr) -> Result<T, EnvarError> {
+        if let Some(raw) = raw.to_str() {
+            return self.parse_unrecorded(raw);
+        }
+        let max_len = self._max_len.unwrap_or_else(max_value_len);
+        if raw.len() > max_len {
+            return Err(EnvarError::TooLong {
+                varname: self._name.clone(),
+                len: raw.len(),
+                max_len,
+            });
+        }
+        let value = EnvarParser::<T>::parse_os(self._name.clone(), raw)
+            .map_err(|e| self.redact_if_sensitive(e))?;
+        self.validate_parsed(&value, &raw.to_string_lossy())?;
+        Ok(value)
+    }
+
+    /// Parses a found raw value, from the original if it is not valid UTF-8
+    /// and the type accepts that
+    fn parse_raw(&self, raw: &str, found: &Found<'_>) -> Result<T, EnvarError> {
+        let result = match &found.found_in {
+            FoundIn::Source(Some(original))
+                if <EnvarParser<T> as EnvarParse<T>>::ACCEPTS_NON_UNICODE =>
+            {
+                self.parse_unrecorded_os(original)
+            }
+            _ => self.parse_unrecorded(raw),
+        };
+        if let Err(e) = &result {
+            if !matches!(e, EnvarError::TryDefault(_)) {
+                self._stats.record_parse_failure();
+            }
+        }
+        result
     }
 
     pub fn value(&self) -> Result<T, EnvarError> {
+        self._stats.record_read();
+        if let Some(value) = self.override_value() {
+            return Ok(value);
+        }
+        if crate::test::in_scope() {
+            // scoped values are neither frozen nor cached, as they would outlive the scope
+            return match self.startup_value() {
+                Some(value) => Ok(value.clone()),
+                None => self.resolve_uncached(),
+            };
+        }
         match &self.store {
             EnvarStore::OnStartup(once_loaded) => {
                 // check if once lock is initialized
                 if let Some(value) = once_loaded.get() {
+                    self._stats.record_cache_hit();
                     return Ok(value.clone());
                 }
+                self._stats.record_cache_miss();
 
-                if let Ok(value) = std::env::var(self._name) {
-                    match EnvarParser::<T>::parse(Cow::Borrowed(self._name), value.as_str()) {
-                        Ok(value) => {
-                            // preemption is possible, we make sure to maintain consistency
-                            Ok(once_loaded.get_or_init(move || value).clone())
-                        }
-                        Err(EnvarError::TryDefault(varname)) => {
+                if let Some((value, found)) = self.lookup_found()? {
+                    let (value, resolved) = match self.parse_raw(value.as_str(), &found) {
+                        Ok(parsed) => (parsed, self.resolved_from(found, value.as_str())),
+                        Err(EnvarError::TryDefault(_)) => {
                             if let EnvarDef::Default(default) = (self._default_factory)() {
-                                Ok(once_loaded.get_or_init(move || default).clone())
+                                (default, Resolved::DEFAULT)
                             } else {
-                                Err(EnvarError::NotSet(varname))
+                                return Err(self.not_set());
                             }
                         }
-                        Err(e) => Err(e),
+                        Err(e) => (self.recover(e)?, Resolved::DEFAULT),
+                    };
+                    self._stats.record_change();
+                    // preemption is possible, we make sure to maintain consistency
+                    let mut initialized = false;
+                    let value = once_loaded
+                        .get_or_init(|| {
+                            initialized = true;
+                            value
+                        })
+                        .clone();
+                    if initialized {
+                        self.record_resolved(resolved);
                     }
+                    Ok(value)
                 } else {
                     if let Some(value) = once_loaded.get() {
+                        self._stats.record_cache_hit();
                         return Ok(value.clone());
                     }
                     if let EnvarDef::Default(default) = (self._default_factory)() {
-                        let _ = once_loaded.set(default.clone());
-                        return Ok(default);
+                        self._stats.record_change();
+                        if once_loaded.set(default.clone()).is_ok() {
+                            self.record_resolved(Resolved::DEFAULT);
+                        }
+                        Ok(default)
                     } else {
-                        Err(EnvarError::NotSet(Cow::Borrowed(self._name)))
+                        Err(self.not_set())
                     }
                 }
             }
             EnvarStore::OnDemand(mutex) => {
-                let mut entry = mutex.lock().unwrap();
-                let env_value = std::env::var(self._name).ok();
-
-                let reset_value =
-                    |env_value: Option<String>, entry: &mut (Option<String>, Option<T>)| {
-                        let value = match env_value.as_ref() {
-                            None => (self._default_factory)().to_option(),
-                            Some(value) => match EnvarParser::<T>::parse(
-                                Cow::Borrowed(self._name),
-                                value.as_str(),
-                            ) {
-                                Ok(value) => Some(value),
-                                Err(EnvarError::TryDefault(varname)) => {
-                                    if let EnvarDef::Default(default) = (self._default_factory)() {
-                                        return Ok(default);
-                                    } else {
-                                        return Err(EnvarError::NotSet(varname));
+                // the cached entry is always either consistent or about to be rebuilt,
+                // so a poisoned lock (a panic elsewhere while holding it) is recovered from
+                let generation = crate::layers::generation();
+                let (env_value, found) = match self.lookup_found() {
+                    Ok(Some((raw, found))) => (Some(raw), Some(found)),
+                    Ok(None) => (None, None),
+                    Err(e) => {
+                        let entry = mutex.lock().unwrap_or_else(PoisonError::into_inner);
+                        let last_good = match entry.2.clone() {
+                            Some(Ok(last_good)) => Some(last_good),
+                            _ => None,
+                        };
+                        return self.serve_stale(e, last_good);
+                    }
+                };
+
+                {
+                    let entry = mutex.lock().unwrap_or_else(PoisonError::into_inner);
+                    if entry.0 == generation && entry.1.as_ref() == env_value.as_ref() {
+                        if let Some(result) = entry.2.clone() {
+                            self._stats.record_cache_hit();
+                            if let (Ok(_), Some(resolved)) = (&result, entry.3.clone()) {
+                                self.record_resolved(resolved);
+                            }
+                            return result;
+                        }
+                    }
+                }
+                self._stats.record_cache_miss();
+
+                // the lock is not held while running default factories and parsers,
+                // so they can safely read other `Envar`s, or even this one
+                let value = match (env_value.as_ref(), found) {
+                    (Some(value), Some(found)) => match self.parse_raw(value.as_str(), &found) {
+                        Ok(parsed) => Some((parsed, self.resolved_from(found, value.as_str()))),
+                        Err(EnvarError::TryDefault(_)) => {
+                            if let EnvarDef::Default(default) = (self._default_factory)() {
+                                self.record_resolved(Resolved::DEFAULT);
+                                return Ok(default);
+                            } else {
+                                return Err(self.not_set());
+                            }
+                        }
+                        Err(e) => match self.recover(e) {
+                            Ok(default) => Some((default, Resolved::DEFAULT)),
+                            Err(e) => {
+                                let mut entry =
+                                    mutex.lock().unwrap_or_else(PoisonError::into_inner);
+                                // length errors depend on the global cap, so only parse
+                                // failures are remembered against the raw value, and only
+                                // when they cannot change while the raw value stays the same
+                                let cacheable = matches!(e, EnvarError::ParseError { .. })
+                                    && self.failures_are_deterministic();
+                                if let Some(Ok(last_good)) = entry.2.clone() {
+                                    if self._serve_stale_on_error {
+                                        if cacheable {
+                                            entry.0 = generation;
+                                            entry.1 = env_value;
+                                        }
+                                        if let Some(resolved) = entry.3.clone() {
+                                            self.record_resolved(resolved);
+                                        }
+                                        return self.serve_stale(e, Some(last_good));
                                     }
                                 }
-                                Err(e) => {
-                                    return Err(e);
+                                // cached too, so a misconfigured variable on a hot path
+                                // is not re-parsed until its raw value changes
+                                if cacheable {
+                                    entry.0 = generation;
+                                    entry.1 = env_value;
+                                    entry.2 = Some(Err(e.clone()));
                                 }
-                            },
-                        };
+                                return Err(e);
+                            }
+                        },
+                    },
+                    _ => (self._default_factory)()
+                        .to_option()
+                        .map(|default| (default, Resolved::DEFAULT)),
+                };
 
-                        let value = match value {
-                            None => return Err(EnvarError::NotSet(Cow::Borrowed(self._name))),
-                            Some(value) => value,
-                        };
+                let (value, resolved) = match value {
+                    None => return Err(self.not_set()),
+                    Some(value) => value,
+                };
 
-                        entry.0 = env_value;
-                        entry.1 = Some(value.clone());
+                let mut entry = mutex.lock().unwrap_or_else(PoisonError::into_inner);
+                entry.0 = generation;
+                entry.1 = env_value;
+                entry.2 = Some(Ok(value.clone()));
+                entry.3 = Some(resolved.clone());
+                self.record_resolved(resolved);
+                self._stats.record_change();
 
-                        return Ok(value);
-                    };
+                Ok(value)
+            }
+            EnvarStore::OnDemandTtl(ttl, mutex) => {
+                let generation = crate::layers::generation();
+                {
+                    let entry = mutex.lock().unwrap_or_else(PoisonError::into_inner);
+                    if let Some((read_at, entry_generation, value)) = entry.as_ref() {
+                        if *entry_generation == generation && read_at.elapsed() < *ttl {
+                            self._stats.record_cache_hit();
+                            return Ok(value.clone());
+                        }
+                    }
+                }
+                self._stats.record_cache_miss();
 
-                if entry.0.as_ref() == env_value.as_ref() {
-                    if let Some(value) = entry.1.clone() {
-                        return Ok(value);
+                // as for `OnDemand`, the lock is not held while resolving
+                let value = match self.resolve_uncached() {
+                    Ok(value) => value,
+                    Err(e) => {
+                        let entry = mutex.lock().unwrap_or_else(PoisonError::into_inner);
+                        return self
+                            .serve_stale(e, entry.as_ref().map(|(_, _, value)| value.clone()));
                     }
+                };
+                let mut entry = mutex.lock().unwrap_or_else(PoisonError::into_inner);
+                *entry = Some((Instant::now(), generation, value.clone()));
+                self._stats.record_change();
+                Ok(value)
+            }
+        }
+    }
+
+    /// With `serve_stale_on_error`, the last good value (if any) with a warning
+    /// about `error`, else `error`
+    fn serve_stale(&self, error: EnvarError, last_good: Option<T>) -> Result<T, EnvarError> {
+        match last_good {
+            Some(stale) if self._serve_stale_on_error => {
+                crate::warnings::emit(&format!("{}; serving the last good value", error));
+                Ok(stale)
+            }
+            _ => Err(error),
+        }
+    }
+
+    /// Resolves the value from the environment and defaults, bypassing the store
+    fn resolve_uncached(&self) -> Result<T, EnvarError> {
+        let (value, resolved) = match self.lookup_found()? {
+            None => match (self._default_factory)() {
+                EnvarDef::Default(default) => (default, Resolved::DEFAULT),
+                EnvarDef::Unset => return Err(self.not_set()),
+            },
+            Some((raw, found)) => match self.parse_raw(raw.as_str(), &found) {
+                Ok(value) => (value, self.resolved_from(found, raw.as_str())),
+                Err(EnvarError::TryDefault(_)) => match (self._default_factory)() {
+                    EnvarDef::Default(default) => (default, Resolved::DEFAULT),
+                    EnvarDef::Unset => return Err(self.not_set()),
+                },
+                Err(e) => (self.recover(e)?, Resolved::DEFAULT),
+            },
+        };
+        self.record_resolved(resolved);
+        Ok(value)
+    }
+
+    /// The value, or `default` if it is missing or invalid.
+    pub fn value_or(&self, default: T) -> T {
+        self.value().unwrap_or(default)
+    }
+
+    /// The value, or the result of `default` if it is missing or invalid.
+    pub fn value_or_else(&self, default: impl FnOnce() -> T) -> T {
+        self.value().unwrap_or_else(|_| default())
+    }
+
+    /// The value, or `None` if it is missing or invalid.
+    pub fn get(&self) -> Option<T> {
+        self.value().ok()
+    }
+
+    /// The value, or prints a report (name, expected type and example)
+    /// and exits with the error's `sysexits.h` code.
+    pub fn expect_value(&self) -> T {
+        self.value().unwrap_or_else(|e| {
+            let code = e.exit_code();
+            self.exit_with(e, code)
+        })
+    }
+
+    /// Like `expect_value`, but exits with `code`.
+    pub fn value_or_exit(&self, code: i32) -> T {
+        self.value().unwrap_or_else(|e| self.exit_with(e, code))
+    }
+
+    fn exit_with(&self, error: EnvarError, code: i32) -> ! {
+        eprint!("{}", self.failure_report(&error));
+        std::process::exit(code)
+    }
+
+    /// The multi-line message printed by `expect_value` and `value_or_exit`,
+    /// documenting the variable from its metadata
+    pub(crate) fn failure_report(&self, error: &EnvarError) -> String {
+        crate::messages::FailureReport {
+            error,
+            varname: &self._name,
+            expected: &short_type_name(std::any::type_name::<T>()),
+            description: self._meta.description,
+            unit: self._meta.unit,
+            example: self._meta.example,
+        }
+        .to_string()
+    }
+}
+
+impl<T, F> Envar<T, F>
+where
+    T: Clone + 'static,
+    EnvarParser<T>: EnvarParse<T>,
+    F: Fn() -> EnvarDef<T>,
+{
+    /// Whether the variable is explicitly set to a value equal to its declared default,
+    /// i.e. the setting is redundant.
+    ///
+    /// The set value and the default are compared in raw form, as written by
+    /// `EnvarParse::render`, so `T` needs no `PartialEq`; types without a raw
+    /// form (such as `Secret`) are never reported.
+    pub fn is_explicit_default(&self) -> bool {
+        let EnvarDef::Default(default) = (self._default_factory)() else {
+            return false;
+        };
+        let Some(default) = EnvarParser::<T>::render(&default) else {
+            return false;
+        };
+        let Ok(Some(raw)) = self.lookup_raw() else {
+            return false;
+        };
+        // rendering the parsed value normalizes spellings such as `04` or `yes`
+        match self.parse_unrecorded(raw.as_str()) {
+            Ok(value) => EnvarParser::<T>::render(&value).is_some_and(|value| value == default),
+            Err(_) => false,
+        }
+    }
+}
+
+impl<F> Envar<String, F>
+where
+    F: Fn() -> EnvarDef<String>,
+{
+    /// Accepts only the listed values (compared exactly), rejecting others with
+    /// `EnvarError::NotAllowed`, which lists them and suggests the closest one.
+    pub const fn one_of(mut self, allowed: &'static [&'static str]) -> Self {
+        self._allowed = allowed;
+        self
+    }
+}
+
+fn check_range<T: PartialOrd + std::fmt::Display>(
+    value: &T,
+    start: &T,
+    end: &T,
+) -> Result<(), String> {
+    if start <= value && value <= end {
+        Ok(())
+    } else {
+        Err(format!("{}..={}", start, end))
+    }
+}
+
+impl<T, F> Envar<T, F>
+where
+    T: Copy + PartialOrd + std::fmt::Display + 'static,
+    EnvarParser<T>: EnvarParse<T>,
+    F: Fn() -> EnvarDef<T>,
+{
+    /// Rejects parsed values outside `range` with `EnvarError::OutOfRange`,
+    /// e.g. `in_range(1..=65535)` for a port. Use `T::MIN` or `T::MAX` for an open end.
+    pub const fn in_range(mut self, range: RangeInclusive<T>) -> Self {
+        self._range = Some(RangeCheck {
+            start: *range.start(),
+            end: *range.end(),
+            check: check_range::<T>,
+        });
+        self
+    }
+}
+
+/// Hides the value of a `ParseError` and its reason, which parsers may build
+/// from the value, e.g. for secrets
+pub(crate) fn redact_parse_error(error: EnvarError) -> EnvarError {
+    match error {
+        EnvarError::ParseError {
+            varname, typename, ..
+        } => EnvarError::ParseError {
+            varname,
+            typename,
+            value: "***".to_string(),
+            reason: ErrorReason::new(|| "details hidden for a sensitive value".to_string()),
+        },
+        e => e,
+    }
+}
+
+/// The variable naming the file `name`'s value is read from
+pub(crate) fn file_varname(name: &str) -> String {
+    format!("{}_FILE", name)
+}
+
+/// Reads a value from the file at `path`, without the trailing newline most editors add
+pub(crate) fn read_value_file(name: &str, path: &str) -> Result<String, EnvarError> {
+    match std::fs::read_to_string(path) {
+        Ok(mut contents) => {
+            if contents.ends_with('\n') {
+                contents.pop();
+                if contents.ends_with('\r') {
+                    contents.pop();
                 }
+            }
+            Ok(contents)
+        }
+        Err(e) => Err(EnvarError::FileError {
+            varname: Cow::Owned(file_varname(name)),
+            path: path.to_string(),
+            reason: e.to_string(),
+        }),
+    }
+}
+
+/// Parses one item of a list-like value, prefixing a parse error's reason with
+/// the item's position, e.g. `item 3 of PORTS: "http": invalid digit found in string`
+pub(crate) fn parse_item<T>(
+    varname: Cow<'static, str>,
+    index: usize,
+    item: &str,
+) -> Result<T, EnvarError>
+where
+    EnvarParser<T>: EnvarParse<T>,
+{
+    parse_part(varname, &format!("item {}", index + 1), item)
+}
+
+/// Parses `part` of a composite value, prefixing a parse error's reason with
+/// `{label} of {varname}` and the part's text
+pub(crate) fn parse_part<T>(
+    varname: Cow<'static, str>,
+    label: &str,
+    part: &str,
+) -> Result<T, EnvarError>
+where
+    EnvarParser<T>: EnvarParse<T>,
+{
+    EnvarParser::<T>::parse(varname, part).map_err(|e| locate_error(e, label))
+}
 
-                return reset_value(env_value, &mut entry);
+/// Prefixes the reason of a parse error with where in the value it happened
+pub(crate) fn locate_error(error: EnvarError, label: &str) -> EnvarError {
+    match error {
+        EnvarError::ParseError {
+            varname,
+            typename,
+            value,
+            reason,
+        } => {
+            // `value`, unlike the raw part, is masked by sensitive types
+            let position = format!("{} of {}: {:?}", label, varname, value);
+            EnvarError::ParseError {
+                varname,
+                typename,
+                value,
+                reason: ErrorReason::new(move || format!("{}: {}", position, reason)),
             }
         }
+        e => e,
+    }
+}
+
+/// `type_name` without module paths, e.g. `Option<String>` for
+/// `core::option::Option<alloc::string::String>`
+pub(crate) fn short_type_name(name: &str) -> String {
+    let mut short = String::with_capacity(name.len());
+    let mut segment = String::new();
+    for c in name.chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            segment.push(c);
+        } else {
+            short.push_str(segment.rsplit("::").next().unwrap_or_default());
+            segment.clear();
+            short.push(c);
+        }
     }
+    short.push_str(segment.rsplit("::").next().unwrap_or_default());
+    short
 }
 
 macro_rules! impl_via_parse {
@@ -145,23 +1326,100 @@ macro_rules! impl_via_parse {
         $(
         impl EnvarParse<$t> for $crate::EnvarParser<$t> {
             fn parse(varname: Cow<'static, str>, s: &str) -> Result<$t, EnvarError> {
-                Ok(s.parse::<$t>().map_err(|e| EnvarError::ParseError {
+                s.parse::<$t>().map_err(|e| EnvarError::ParseError {
                     varname,
                     typename: stringify!($t),
                     value: s.to_string(),
                     reason: ErrorReason::new(move || format!("{}", e)),
-                })?)
+                })
+            }
+
+            fn render(value: &$t) -> Option<String> {
+                Some(value.to_string())
             }
         }
         )*
     };
 }
 
-impl_via_parse!(usize, u64, u32, u16, u8, isize, i64, i32, i16, i8, f64, f32);
+impl_via_parse!(usize, u128, u64, u32, u16, u8, isize, i128, i64, i32, i16, i8, f64, f32);
+impl_via_parse!(
+    NonZeroUsize,
+    NonZeroU128,
+    NonZeroU64,
+    NonZeroU32,
+    NonZeroU16,
+    NonZeroU8,
+    NonZeroIsize,
+    NonZeroI128,
+    NonZeroI64,
+    NonZeroI32,
+    NonZeroI16,
+    NonZeroI8
+);
+impl_via_parse!(IpAddr, Ipv4Addr, Ipv6Addr);
 
 impl EnvarParse<String> for EnvarParser<String> {
     fn parse(_varname: Cow<'static, str>, value: &str) -> Result<String, EnvarError> {
-        return Ok(value.to_string());
+        Ok(value.to_string())
+    }
+
+    fn render(value: &String) -> Option<String> {
+        Some(value.clone())
+    }
+}
+
+impl EnvarParse<OsString> for EnvarParser<OsString> {
+    const ACCEPTS_NON_UNICODE: bool = true;
+
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<OsString, EnvarError> {
+        Self::parse_os(varname, value.as_ref())
+    }
+
+    fn parse_os(varname: Cow<'static, str>, value: &OsStr) -> Result<OsString, EnvarError> {
+        if value.is_empty() {
+            return Err(EnvarError::TryDefault(varname));
+        }
+        Ok(value.to_os_string())
+    }
+
+    fn render(value: &OsString) -> Option<String> {
+        value.to_str().map(str::to_string)
+    }
+}
+
+/// A single character; surrounding whitespace is trimmed unless the value is
+/// exactly one (whitespace) character, so `" "` reads as a space.
+impl EnvarParse<char> for EnvarParser<char> {
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<char, EnvarError> {
+        let mut chars = value.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Ok(c);
+        }
+
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            return Err(EnvarError::TryDefault(varname));
+        }
+        let mut chars = trimmed.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => {
+                let count = trimmed.chars().count();
+                Err(EnvarError::ParseError {
+                    varname,
+                    typename: "char",
+                    value: value.to_string(),
+                    reason: ErrorReason::new(move || {
+                        format!("expected exactly one character, found {}", count)
+                    }),
+                })
+            }
+        }
+    }
+
+    fn render(value: &char) -> Option<String> {
+        Some(value.to_string())
     }
 }
 
@@ -184,7 +1442,7 @@ impl EnvarParse<bool> for EnvarParser<bool> {
             }
         }
 
-        return Err(EnvarError::ParseError {
+        Err(EnvarError::ParseError {
             varname,
             typename: "bool",
             value: value.to_string(),
@@ -192,41 +1450,21 @@ impl EnvarParse<bool> for EnvarParser<bool> {
                 let owned_value = value.to_string();
                 move || owned_value
             }),
-        });
+        })
     }
-}
 
-impl<T, C> EnvarParse<ListEnvar<T, C>> for EnvarParser<ListEnvar<T, C>>
-where
-    C: ListEnvarConfig,
-    EnvarParser<T>: EnvarParse<T>,
-{
-    fn parse(varname: Cow<'static, str>, value: &str) -> Result<ListEnvar<T, C>, EnvarError> {
-        let mut list: Vec<T> = vec![];
-
-        for item in value.split(C::SEP) {
-            if C::FILTER_EMPTY_STR && item.is_empty() {
-                continue;
-            }
-            let trimmed = item.trim();
-            if C::FILTER_WHITESPACE && trimmed.is_empty() {
-                continue;
-            }
-            let parsed = EnvarParser::<T>::parse(varname.clone(), trimmed);
-            match parsed {
-                Ok(value) => list.push(value),
-                Err(e) => return Err(e),
-            }
-        }
-
-        Ok(ListEnvar::new(list))
+    fn render(value: &bool) -> Option<String> {
+        Some(value.to_string())
     }
 }
 
+#[cfg(feature = "option")]
 impl<T> EnvarParse<Option<T>> for EnvarParser<Option<T>>
 where
     EnvarParser<T>: EnvarParse<T>,
 {
+    const DETERMINISTIC: bool = EnvarParser::<T>::DETERMINISTIC;
+
     fn parse(varname: Cow<'static, str>, value: &str) -> Result<Option<T>, EnvarError> {
         let value = value.trim();
         if value.is_empty() {
@@ -235,10 +1473,48 @@ where
         let parsed = EnvarParser::<T>::parse(varname, value);
         Ok(Some(parsed?))
     }
+
+    fn render(value: &Option<T>) -> Option<String> {
+        match value {
+            None => Some(String::new()),
+            Some(value) => EnvarParser::<T>::render(value),
+        }
+    }
 }
 
 pub struct EnvarParser<T: ?Sized>(std::marker::PhantomData<T>);
 
 pub trait EnvarParse<T> {
+    /// Whether values of the type are always treated like `Envar::sensitive` ones
+    const SENSITIVE: bool = false;
+
+    /// For list types, the separator items are split on, so that nested lists
+    /// can be checked for clashing separators
+    const LIST_SEP: Option<&'static str> = None;
+
+    /// Whether `parse_os` reads values that are not valid UTF-8, e.g. for
+    /// `OsString` and `PathBuf`; such values then bypass the `NonUnicode` policy
+    const ACCEPTS_NON_UNICODE: bool = false;
+
+    /// Whether parsing depends on the raw value alone. Failures of parsers that
+    /// also consult the outside world, like `ResolvedAddrs` resolving DNS, are
+    /// not cached against the raw value, so the next read tries again
+    const DETERMINISTIC: bool = true;
+
     fn parse(varname: Cow<'static, str>, value: &str) -> Result<T, EnvarError>;
+
+    /// Parses a value as read from the environment, which may not be valid UTF-8.
+    /// By default, such values are rejected with `EnvarError::NotUnicode`.
+    fn parse_os(varname: Cow<'static, str>, value: &std::ffi::OsStr) -> Result<T, EnvarError> {
+        match value.to_str() {
+            Some(value) => Self::parse(varname, value),
+            None => Err(EnvarError::NotUnicode(varname)),
+        }
+    }
+
+    /// Renders a value back into raw environment variable form, used by generated
+    /// documentation and manifests. `None` if the type has no textual form.
+    fn render(_value: &T) -> Option<String> {
+        None
+    }
 }
@@ -0,0 +1,439 @@
+//! Deserializing serde types straight from the environment.
+//!
+//! `from_env::<T>("APP")` reads each field of `T` from `APP_<FIELD>`, with the
+//! field name uppercased. Nested structs read from `APP_<FIELD>__<SUBFIELD>`,
+//! and maps collect every `APP_<FIELD>__<KEY>` under the lowercased key.
+//! Scalars are parsed like the corresponding `Envar<T>` (so `on`/`off`
+//! booleans and the like work), sequences split on `,`, and enums match unit
+//! variants by name.
+//!
+//! Unset variables count as missing fields: `Option` fields become `None`,
+//! `#[serde(default)]` applies, and anything else fails with
+//! `EnvarError::NotSet`. Parse failures are `EnvarError::ParseError`s naming
+//! the variable.
+
+use crate::core::{locate_error, EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::ErrorReason;
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+};
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
+
+/// Deserializes `T` from the variables named `<prefix>_<FIELD>`, or just
+/// `<FIELD>` with an empty prefix.
+///
+/// Reads the same environment as `Envar`: the installed source, override
+/// layers on top, and values scoped to the current thread by `typed_env::test`.
+pub fn from_env<T: DeserializeOwned>(prefix: &str) -> Result<T, EnvarError> {
+    let env = environment();
+    let root = Node {
+        env: &env,
+        name: prefix.trim_end_matches('_').to_string(),
+        raw: None,
+        root: true,
+    };
+    T::deserialize(root).map_err(|e| match e {
+        DeError::Envar(e) => e,
+        DeError::Custom(reason) | DeError::MissingField(reason) => EnvarError::ParseError {
+            varname: Cow::Owned(prefix.to_string()),
+            typename: std::any::type_name::<T>(),
+            value: String::new(),
+            reason: ErrorReason::new(move || reason),
+        },
+    })
+}
+
+/// Every visible variable with a UTF-8 name and value
+fn environment() -> HashMap<String, String> {
+    let mut env = HashMap::new();
+    for (name, value) in crate::source::with_global(|source| source.vars()) {
+        if let (Ok(name), Ok(value)) = (name.into_string(), value.into_string()) {
+            env.insert(name, value);
+        }
+    }
+    env.extend(crate::layers::vars());
+    for (name, value) in crate::test::scoped_vars() {
+        match value {
+            Some(value) => env.insert(name, value),
+            None => env.remove(&name),
+        };
+    }
+    env
+}
+
+#[derive(Debug)]
+enum DeError {
+    Envar(EnvarError),
+    /// A message from serde or a `Deserialize` impl, not yet tied to a variable
+    Custom(String),
+    /// A field with no variable (the field's name)
+    MissingField(String),
+}
+
+impl std::fmt::Display for DeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeError::Envar(e) => write!(f, "{}", e),
+            DeError::Custom(message) => f.write_str(message),
+            DeError::MissingField(field) => write!(f, "missing field `{}`", field),
+        }
+    }
+}
+
+impl std::error::Error for DeError {}
+
+impl de::Error for DeError {
+    fn custom<M: std::fmt::Display>(message: M) -> Self {
+        DeError::Custom(message.to_string())
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        DeError::MissingField(field.to_string())
+    }
+}
+
+impl From<EnvarError> for DeError {
+    fn from(e: EnvarError) -> Self {
+        DeError::Envar(e)
+    }
+}
+
+/// A variable, or a group of them sharing its name as a prefix
+struct Node<'a> {
+    env: &'a HashMap<String, String>,
+    name: String,
+    /// The value to deserialize; looked up by `name` when `None`
+    raw: Option<&'a str>,
+    /// Whether this is the prefix passed to `from_env`, which is joined to
+    /// field names by `_` rather than `__`
+    root: bool,
+}
+
+impl<'a> Node<'a> {
+    fn child(&self, field: &str) -> Node<'a> {
+        let name = match (self.root, self.name.is_empty()) {
+            (true, true) => field.to_uppercase(),
+            (true, false) => format!("{}_{}", self.name, field.to_uppercase()),
+            (false, _) => format!("{}__{}", self.name, field.to_uppercase()),
+        };
+        Node {
+            env: self.env,
+            name,
+            raw: None,
+            root: false,
+        }
+    }
+
+    /// The prefix of nested variables, e.g. `APP_DB__`
+    fn nested_prefix(&self) -> String {
+        match (self.root, self.name.is_empty()) {
+            (true, true) => String::new(),
+            (true, false) => format!("{}_", self.name),
+            (false, _) => format!("{}__", self.name),
+        }
+    }
+
+    fn raw(&self) -> Option<&'a str> {
+        self.raw
+            .or_else(|| self.env.get(&self.name).map(String::as_str))
+    }
+
+    fn has_nested(&self) -> bool {
+        let prefix = self.nested_prefix();
+        self.env.keys().any(|name| name.starts_with(&prefix))
+    }
+
+    fn is_present(&self) -> bool {
+        self.raw().is_some() || self.has_nested()
+    }
+
+    fn varname(&self) -> Cow<'static, str> {
+        Cow::Owned(self.name.clone())
+    }
+
+    fn require_raw(&self) -> Result<&'a str, DeError> {
+        self.raw()
+            .ok_or_else(|| EnvarError::NotSet(self.varname()).into())
+    }
+
+    /// Parses the value with the crate's own parser for `T`
+    fn parse<T>(&self) -> Result<T, DeError>
+    where
+        EnvarParser<T>: EnvarParse<T>,
+    {
+        let raw = self.require_raw()?;
+        match EnvarParser::<T>::parse(self.varname(), raw) {
+            Ok(value) => Ok(value),
+            Err(EnvarError::TryDefault(varname)) => Err(EnvarError::NotSet(varname).into()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Ties messages from `visit` to this variable, as a `ParseError` of `typename`
+    fn visit_raw<V>(
+        &self,
+        typename: &'static str,
+        visit: impl FnOnce(&'a str) -> Result<V, DeError>,
+    ) -> Result<V, DeError> {
+        let raw = self.require_raw()?;
+        visit(raw).map_err(|e| match e {
+            DeError::Custom(reason) => EnvarError::ParseError {
+                varname: self.varname(),
+                typename,
+                value: raw.to_string(),
+                reason: ErrorReason::new(move || reason),
+            }
+            .into(),
+            e => e,
+        })
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident($t:ty) => $visit:ident),* $(,)?) => {
+        $(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+            let value = self.parse::<$t>()?;
+            visitor.$visit(value)
+        }
+        )*
+    };
+}
+
+impl<'de, 'a> de::Deserializer<'de> for Node<'a> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        if self.raw().is_none() && self.has_nested() {
+            return self.deserialize_map(visitor);
+        }
+        self.visit_raw("String", |raw| visitor.visit_str(raw))
+    }
+
+    deserialize_parsed!(
+        deserialize_bool(bool) => visit_bool,
+        deserialize_i8(i8) => visit_i8,
+        deserialize_i16(i16) => visit_i16,
+        deserialize_i32(i32) => visit_i32,
+        deserialize_i64(i64) => visit_i64,
+        deserialize_i128(i128) => visit_i128,
+        deserialize_u8(u8) => visit_u8,
+        deserialize_u16(u16) => visit_u16,
+        deserialize_u32(u32) => visit_u32,
+        deserialize_u64(u64) => visit_u64,
+        deserialize_u128(u128) => visit_u128,
+        deserialize_f32(f32) => visit_f32,
+        deserialize_f64(f64) => visit_f64,
+        deserialize_char(char) => visit_char,
+    );
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.visit_raw("String", |raw| visitor.visit_str(raw))
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.visit_raw("bytes", |raw| visitor.visit_bytes(raw.as_bytes()))
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_bytes(visitor)
+    }
+
+    /// Unset, blank and (for structs and maps) absent values are `None`
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self.raw() {
+            Some(raw) if raw.trim().is_empty() => visitor.visit_none(),
+            None if !self.has_nested() => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Splits the value on `,`, skipping blank items
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        let raw = self.require_raw()?;
+        let items = raw
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .enumerate();
+        visitor.visit_seq(Items { node: &self, items })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    /// Collects the nested variables, keyed by the lowercased name segment
+    /// after the prefix
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        let prefix = self.nested_prefix();
+        let keys: BTreeSet<&str> = self
+            .env
+            .keys()
+            .filter_map(|name| name.strip_prefix(&prefix))
+            .map(|rest| rest.split("__").next().unwrap_or(rest))
+            .filter(|key| !key.is_empty())
+            .collect();
+        let entries = keys
+            .into_iter()
+            .map(|key| {
+                let node = Node {
+                    env: self.env,
+                    name: format!("{}{}", prefix, key),
+                    raw: None,
+                    root: false,
+                };
+                (key.to_lowercase(), node)
+            })
+            .collect::<Vec<_>>();
+        visitor.visit_map(Entries {
+            entries: entries.into_iter(),
+            value: None,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        let entries = fields
+            .iter()
+            .map(|field| (field.to_string(), self.child(field)))
+            .filter(|(_, child)| child.is_present())
+            .collect::<Vec<_>>();
+        let result = visitor.visit_map(Entries {
+            entries: entries.into_iter(),
+            value: None,
+        });
+        result.map_err(|e| match e {
+            DeError::MissingField(field) => EnvarError::NotSet(self.child(&field).varname()).into(),
+            e => e,
+        })
+    }
+
+    /// Unit variants, matched by name
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        self.visit_raw(name, |raw| {
+            visitor.visit_enum(IntoDeserializer::<DeError>::into_deserializer(raw.trim()))
+        })
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_unit()
+    }
+}
+
+/// The items of a sequence value
+struct Items<'n, 'a, I> {
+    node: &'n Node<'a>,
+    items: I,
+}
+
+impl<'de, 'a, I> SeqAccess<'de> for Items<'_, 'a, I>
+where
+    I: Iterator<Item = (usize, &'a str)>,
+{
+    type Error = DeError;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, DeError> {
+        let Some((index, item)) = self.items.next() else {
+            return Ok(None);
+        };
+        let node = Node {
+            env: self.node.env,
+            name: self.node.name.clone(),
+            raw: Some(item),
+            root: false,
+        };
+        seed.deserialize(node).map(Some).map_err(|e| match e {
+            DeError::Envar(e) => locate_error(e, &format!("item {}", index + 1)).into(),
+            e => e,
+        })
+    }
+}
+
+/// The fields of a struct, or the entries of a map
+struct Entries<'a, I> {
+    entries: I,
+    value: Option<Node<'a>>,
+}
+
+impl<'de, 'a, I> MapAccess<'de> for Entries<'a, I>
+where
+    I: Iterator<Item = (String, Node<'a>)>,
+{
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, DeError> {
+        let Some((key, node)) = self.entries.next() else {
+            return Ok(None);
+        };
+        self.value = Some(node);
+        seed.deserialize(IntoDeserializer::<DeError>::into_deserializer(key))
+            .map(Some)
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<S::Value, DeError> {
+        let node = self
+            .value
+            .take()
+            .expect("next_value_seed is called after next_key_seed");
+        seed.deserialize(node)
+    }
+}
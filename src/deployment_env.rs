@@ -0,0 +1,78 @@
+use crate::core::{EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::ErrorReason;
+use std::borrow::Cow;
+
+/// The kind of deployment a service runs in, parsed case-insensitively from
+/// the usual spellings (`dev`/`development`/`local`, `test`/`ci`,
+/// `stage`/`staging`, `prod`/`production`/`live`, ...).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DeploymentEnv {
+    Development,
+    Test,
+    Staging,
+    Production,
+}
+
+impl DeploymentEnv {
+    /// The canonical name, as rendered back into the environment.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeploymentEnv::Development => "development",
+            DeploymentEnv::Test => "test",
+            DeploymentEnv::Staging => "staging",
+            DeploymentEnv::Production => "production",
+        }
+    }
+
+    pub fn is_development(&self) -> bool {
+        *self == DeploymentEnv::Development
+    }
+
+    pub fn is_test(&self) -> bool {
+        *self == DeploymentEnv::Test
+    }
+
+    pub fn is_staging(&self) -> bool {
+        *self == DeploymentEnv::Staging
+    }
+
+    pub fn is_production(&self) -> bool {
+        *self == DeploymentEnv::Production
+    }
+}
+
+impl std::fmt::Display for DeploymentEnv {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl EnvarParse<DeploymentEnv> for EnvarParser<DeploymentEnv> {
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<DeploymentEnv, EnvarError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(EnvarError::TryDefault(varname));
+        }
+
+        for (name, env) in crate::special_constants::DEPLOYMENT_ENV_NAMES {
+            if name.eq_ignore_ascii_case(value) {
+                return Ok(*env);
+            }
+        }
+
+        Err(EnvarError::ParseError {
+            varname,
+            typename: "DeploymentEnv",
+            value: value.to_string(),
+            reason: ErrorReason::new(|| {
+                "expected one of development, test, staging, production (or an alias such as dev, prod)"
+                    .to_string()
+            }),
+        })
+    }
+
+    fn render(value: &DeploymentEnv) -> Option<String> {
+        Some(value.to_string())
+    }
+}
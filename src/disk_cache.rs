@@ -0,0 +1,187 @@
+use crate::multiline::{escape_line, unescape_line};
+use std::collections::BTreeMap;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const HEADER: &str = "# typed-env cache v1";
+
+/// The last known-good configuration fetched from a remote source, kept on
+/// disk so a restart while the backend is unreachable can still start.
+///
+/// Entries carry a checksum, so truncated or edited files are rejected
+/// rather than trusted, and an optional TTL after which they are ignored.
+///
+/// The file may hold secrets, so on unix it is only readable by its owner.
+pub struct DiskCache {
+    _path: PathBuf,
+    _ttl: Option<Duration>,
+}
+
+impl DiskCache {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            _path: path.into(),
+            _ttl: None,
+        }
+    }
+
+    /// Ignores cached values older than `ttl`.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self._ttl = Some(ttl);
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self._path
+    }
+
+    fn encode_body(values: &BTreeMap<String, String>) -> String {
+        let mut body = String::new();
+        for (name, value) in values {
+            body.push_str(&format!("{}={}\n", name, escape_line(value)));
+        }
+        body
+    }
+
+    /// Writes `values` to the cache file, replacing it atomically.
+    ///
+    /// The values go to a uniquely named temporary file next to it (created
+    /// with mode `0600` on unix), which is synced and then renamed over it, so
+    /// concurrent writers never interleave and readers never see a partial file.
+    pub fn store(&self, values: &BTreeMap<String, String>) -> io::Result<()> {
+        let body = Self::encode_body(values);
+        let written = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let checksum = crate::feature_flag::fnv1a(format!("{}\n{}", written, body).as_bytes());
+        let contents = format!(
+            "{}\nwritten {}\nchecksum {:016x}\n{}",
+            HEADER, written, checksum, body
+        );
+
+        let tmp = self.temp_path();
+        let written = Self::write_private(&tmp, contents.as_bytes())
+            .and_then(|()| std::fs::rename(&tmp, &self._path));
+        if written.is_err() {
+            let _ = std::fs::remove_file(&tmp);
+        }
+        written
+    }
+
+    /// `<path>.<pid>.<random>.tmp`, unique to this write
+    fn temp_path(&self) -> PathBuf {
+        // `RandomState` is seeded from the operating system's randomness
+        let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+        hasher.write_u128(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_nanos()),
+        );
+        let mut tmp = self._path.clone().into_os_string();
+        tmp.push(format!(
+            ".{}.{:016x}.tmp",
+            std::process::id(),
+            hasher.finish()
+        ));
+        tmp.into()
+    }
+
+    /// Creates `path`, failing if it exists, readable only by its owner on unix
+    fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(path)?;
+        file.write_all(contents)?;
+        file.sync_all()
+    }
+
+    /// Reads the cached values; `Ok(None)` if there is no cache file or it has expired.
+    ///
+    /// A file that fails its integrity check is an `InvalidData` error.
+    pub fn load(&self) -> io::Result<Option<BTreeMap<String, String>>> {
+        let contents = match std::fs::read_to_string(&self._path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let invalid = |reason: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("corrupt cache file {}: {}", self._path.display(), reason),
+            )
+        };
+
+        let mut parts = contents.splitn(4, '\n');
+        if parts.next() != Some(HEADER) {
+            return Err(invalid("unknown header"));
+        }
+        let written: u64 = parts
+            .next()
+            .and_then(|line| line.strip_prefix("written "))
+            .and_then(|secs| secs.parse().ok())
+            .ok_or_else(|| invalid("missing timestamp"))?;
+        let checksum = parts
+            .next()
+            .and_then(|line| line.strip_prefix("checksum "))
+            .and_then(|hex| u64::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| invalid("missing checksum"))?;
+        let body = parts.next().unwrap_or("");
+        if crate::feature_flag::fnv1a(format!("{}\n{}", written, body).as_bytes()) != checksum {
+            return Err(invalid("checksum mismatch"));
+        }
+
+        if let Some(ttl) = self._ttl {
+            let age = SystemTime::now()
+                .duration_since(UNIX_EPOCH + Duration::from_secs(written))
+                .unwrap_or_default();
+            if age > ttl {
+                return Ok(None);
+            }
+        }
+
+        let mut values = BTreeMap::new();
+        for line in body.lines() {
+            let (name, value) = line
+                .split_once('=')
+                .ok_or_else(|| invalid("malformed entry"))?;
+            values.insert(name.to_string(), unescape_line(value));
+        }
+        Ok(Some(values))
+    }
+
+    /// Runs `fetch`, caching its result on success; on failure falls back to
+    /// the cached values, if any are usable, and otherwise returns the fetch error.
+    ///
+    /// Failing to write the cache is reported as a warning, not an error.
+    pub fn fetch_or_load<E>(
+        &self,
+        fetch: impl FnOnce() -> Result<BTreeMap<String, String>, E>,
+    ) -> Result<BTreeMap<String, String>, E> {
+        match fetch() {
+            Ok(values) => {
+                if let Err(e) = self.store(&values) {
+                    crate::warnings::emit(&format!(
+                        "cannot write cache file {}: {}",
+                        self._path.display(),
+                        e
+                    ));
+                }
+                Ok(values)
+            }
+            Err(fetch_error) => match self.load() {
+                Ok(Some(values)) => {
+                    crate::warnings::emit(&format!(
+                        "fetch failed, using cached configuration from {}",
+                        self._path.display()
+                    ));
+                    Ok(values)
+                }
+                _ => Err(fetch_error),
+            },
+        }
+    }
+}
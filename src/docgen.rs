@@ -0,0 +1,451 @@
+//! Generators rendering the registered variables (see `registry`) into
+//! deployment artifacts.
+
+use crate::registry::{self, AnyEnvar};
+use serde_json::{json, Map, Number, Value};
+
+fn yaml_quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Renders a Kubernetes ConfigMap named `{app}-config` holding the non-sensitive
+/// variables, followed by the `envFrom:`/`env:` section of a Deployment container.
+///
+/// Sensitive variables are referenced from a Secret named `{app}-secrets`.
+/// Required variables, and those whose default cannot be rendered, are left
+/// commented out.
+pub fn kubernetes(app: &str) -> String {
+    let entries = registry::entries();
+    let (secrets, plain): (Vec<&'static dyn AnyEnvar>, Vec<&'static dyn AnyEnvar>) =
+        entries.iter().partition(|envar| envar.is_sensitive());
+
+    let mut out = String::new();
+    out.push_str("apiVersion: v1\nkind: ConfigMap\nmetadata:\n");
+    out.push_str(&format!("  name: {}-config\n", app));
+    // entries without a rendered default are commented out, so they neither
+    // look set nor replace the default with an empty value
+    if plain.iter().any(|envar| envar.default_raw().is_some()) {
+        out.push_str("data:\n");
+    } else {
+        out.push_str("data: {}\n");
+    }
+    for (category, group) in registry::group_by_category(&plain) {
+        if let Some(category) = category {
+            out.push_str(&format!("  # {}\n", category));
+        }
+        for envar in group {
+            match envar.default_raw() {
+                Some(value) => {
+                    out.push_str(&format!("  {}: {}\n", envar.name(), yaml_quote(&value)))
+                }
+                None if envar.is_required() => out.push_str(&format!(
+                    "  # {} is required, provide it at deploy time\n",
+                    envar.name()
+                )),
+                None => out.push_str(&format!(
+                    "  # {} has a default that cannot be rendered\n",
+                    envar.name()
+                )),
+            }
+        }
+    }
+
+    out.push_str("---\n# Deployment container environment\n");
+    out.push_str("envFrom:\n  - configMapRef:\n");
+    out.push_str(&format!("      name: {}-config\n", app));
+    if !secrets.is_empty() {
+        out.push_str("env:\n");
+        for envar in &secrets {
+            out.push_str(&format!("  - name: {}\n", envar.name()));
+            out.push_str("    valueFrom:\n      secretKeyRef:\n");
+            out.push_str(&format!("        name: {}-secrets\n", app));
+            out.push_str(&format!("        key: {}\n", envar.name()));
+        }
+    }
+    out
+}
+
+/// The registered variables grouped by category, each paired with the
+/// category heading to write before it, if it starts a categorized group.
+fn categorized() -> Vec<(Option<&'static str>, &'static dyn AnyEnvar)> {
+    let mut out = vec![];
+    for (category, group) in registry::group_by_category(&registry::entries()) {
+        for (i, envar) in group.into_iter().enumerate() {
+            out.push((if i == 0 { category } else { None }, envar));
+        }
+    }
+    out
+}
+
+fn dockerfile_quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' | '\\' | '$' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Renders `ENV` lines for a Dockerfile, with defaults as values.
+///
+/// Required variables are listed as comments, since baking a placeholder into the
+/// image would make them look set; sensitive variables are never baked in.
+pub fn dockerfile() -> String {
+    let mut out = String::new();
+    for (heading, envar) in categorized() {
+        if let Some(heading) = heading {
+            out.push_str(&format!("# {}\n", heading));
+        }
+        if envar.is_sensitive() {
+            out.push_str(&format!(
+                "# {} is sensitive, provide it at runtime\n",
+                envar.name()
+            ));
+            continue;
+        }
+        match envar.default_raw() {
+            Some(value) => out.push_str(&format!(
+                "ENV {}={}\n",
+                envar.name(),
+                dockerfile_quote(&value)
+            )),
+            None if envar.is_required() => out.push_str(&format!(
+                "# {} is required, provide it at runtime\n",
+                envar.name()
+            )),
+            None => out.push_str(&format!(
+                "# {} has a default that cannot be rendered\n",
+                envar.name()
+            )),
+        }
+    }
+    out
+}
+
+/// Renders a `.env`-style template of the given variables, grouped by category.
+///
+/// Each variable is preceded by comments with its type, unit, default and description.
+/// Required variables are left to fill in (with their example, if any); variables
+/// with a default are commented out, so copying the template changes nothing.
+pub fn env_template_of(entries: &[&'static dyn AnyEnvar]) -> String {
+    let mut sections = vec![];
+    for (category, group) in registry::group_by_category(entries) {
+        let mut out = String::new();
+        if let Some(category) = category {
+            out.push_str(&format!("# --- {} ---\n", category));
+        }
+        for envar in group {
+            let meta = envar.meta();
+            let mut summary = crate::core::short_type_name(envar.typename());
+            if let Some(unit) = meta.unit {
+                summary.push_str(&format!(", in {}", unit));
+            }
+            if envar.is_required() {
+                summary.push_str(", required");
+            }
+            if envar.is_sensitive() {
+                summary.push_str(", sensitive");
+            }
+            out.push_str(&format!("# {} ({})\n", envar.name(), summary));
+            if let Some(description) = meta.description {
+                for line in description.lines() {
+                    out.push_str(&format!("# {}\n", line));
+                }
+            }
+            let value = |raw: &str| {
+                if raw
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "_-.,:/@+".contains(c))
+                {
+                    raw.to_string()
+                } else {
+                    dockerfile_quote(raw)
+                }
+            };
+            match envar.default_raw().filter(|_| !envar.is_sensitive()) {
+                Some(default) => out.push_str(&format!("# {}={}\n", envar.name(), value(&default))),
+                None => out.push_str(&format!(
+                    "{}={}\n",
+                    envar.name(),
+                    meta.example.map(value).unwrap_or_default()
+                )),
+            }
+        }
+        sections.push(out);
+    }
+    sections.join("\n")
+}
+
+/// `env_template_of` every registered variable, e.g. to write `.env.example`.
+pub fn env_template() -> String {
+    env_template_of(&registry::entries())
+}
+
+/// Renders the `environment:` block of a docker-compose service.
+///
+/// Every variable is interpolated from the host environment: required ones with
+/// `${NAME:?}` so compose refuses to start without them, the others with
+/// `${NAME:-default}`. Sensitive variables with a default, and variables whose
+/// default cannot be rendered, are left commented out, as an empty value would
+/// replace the default.
+pub fn docker_compose() -> String {
+    let mut out = String::from("environment:\n");
+    for (heading, envar) in categorized() {
+        if let Some(heading) = heading {
+            out.push_str(&format!("  # {}\n", heading));
+        }
+        let name = envar.name();
+        let value = if envar.is_required() {
+            format!("${{{name}:?{name} is required}}")
+        } else if envar.is_sensitive() {
+            // the default of a secret is not written out
+            out.push_str(&format!(
+                "  # {} is sensitive, provide it at runtime to replace its default\n",
+                name
+            ));
+            continue;
+        } else if let Some(value) = envar.default_raw() {
+            // `$` starts an interpolation in compose files
+            let value = value.replace('$', "$$");
+            // a `}` would end the interpolation early, so such defaults are literal
+            if value.contains('}') {
+                value
+            } else {
+                format!("${{{name}:-{value}}}")
+            }
+        } else {
+            out.push_str(&format!(
+                "  # {} has a default that cannot be rendered\n",
+                name
+            ));
+            continue;
+        };
+        out.push_str(&format!("  {}: {}\n", name, yaml_quote(&value)));
+    }
+    out
+}
+
+/// Options for `systemd`.
+#[derive(Clone, Debug, Default)]
+pub struct SystemdOptions {
+    /// Emits `EnvironmentFile=` pointing here, where required values can be provided
+    pub environment_file: Option<String>,
+    /// Emits `LoadCredential=NAME:{dir}/NAME` for sensitive variables, along with
+    /// `Environment=NAME_FILE=%d/NAME` pointing them at the loaded credential
+    pub credentials_dir: Option<String>,
+}
+
+fn systemd_quote(assignment: &str) -> String {
+    let mut quoted = String::with_capacity(assignment.len() + 2);
+    quoted.push('"');
+    for c in assignment.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            // `%` starts a unit file specifier
+            '%' => quoted.push_str("%%"),
+            '\n' => quoted.push_str("\\n"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Renders `Environment=` directives for the `[Service]` section of a systemd unit,
+/// with defaults as values.
+///
+/// Required variables are listed as comments, to be provided through the environment
+/// file; sensitive variables are never written in plain text.
+pub fn systemd(options: &SystemdOptions) -> String {
+    let mut out = String::from("[Service]\n");
+    if let Some(path) = &options.environment_file {
+        out.push_str(&format!("EnvironmentFile=-{}\n", path));
+    }
+    for (heading, envar) in categorized() {
+        if let Some(heading) = heading {
+            out.push_str(&format!("# {}\n", heading));
+        }
+        let name = envar.name();
+        if envar.is_sensitive() {
+            match &options.credentials_dir {
+                Some(dir) => {
+                    out.push_str(&format!("LoadCredential={}:{}/{}\n", name, dir, name));
+                    // `%d` is the unit's `$CREDENTIALS_DIRECTORY`; sensitive variables
+                    // always read `<NAME>_FILE`
+                    out.push_str(&format!(
+                        "Environment=\"{}=%d/{}\"\n",
+                        crate::core::file_varname(name),
+                        name
+                    ));
+                }
+                None => out.push_str(&format!("# {} is sensitive, provide it at runtime\n", name)),
+            }
+            continue;
+        }
+        match envar.default_raw() {
+            Some(value) => out.push_str(&format!(
+                "Environment={}\n",
+                systemd_quote(&format!("{}={}", name, value))
+            )),
+            None if envar.is_required() => {
+                out.push_str(&format!("# {} is required, provide it at runtime\n", name))
+            }
+            None => out.push_str(&format!(
+                "# {} has a default that cannot be rendered\n",
+                name
+            )),
+        }
+    }
+    out
+}
+
+/// The last path segment of each generic argument, e.g.
+/// `typed_env::list_envar::ListEnvar<i32, C>` => `("ListEnvar", ["i32", "C"])`.
+fn split_typename(typename: &str) -> (&str, Vec<&str>) {
+    let (head, args) = match typename.find('<') {
+        Some(start) if typename.ends_with('>') => {
+            (&typename[..start], &typename[start + 1..typename.len() - 1])
+        }
+        _ => (typename, ""),
+    };
+    let mut parts = vec![];
+    let (mut depth, mut begin) = (0, 0);
+    for (i, c) in args.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(args[begin..i].trim());
+                begin = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if !args.is_empty() {
+        parts.push(args[begin..].trim());
+    }
+    (head.rsplit("::").next().unwrap_or(head), parts)
+}
+
+/// JSON Schema keywords describing values of the given Rust type.
+fn json_type(typename: &str) -> Map<String, Value> {
+    let (head, args) = split_typename(typename);
+    let keywords = match head {
+        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" => json!({"type": "integer", "minimum": 0}),
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => json!({"type": "integer"}),
+        "NonZero" if args.len() == 1 => {
+            let mut keywords = json_type(args[0]);
+            match keywords.get_mut("minimum") {
+                Some(minimum) => *minimum = json!(1),
+                None => {
+                    keywords.insert("not".to_string(), json!({"const": 0}));
+                }
+            }
+            return keywords;
+        }
+        "f32" | "f64" => json!({"type": "number"}),
+        "bool" => json!({"type": "boolean"}),
+        "Ipv4Addr" => json!({"type": "string", "format": "ipv4"}),
+        "Ipv6Addr" => json!({"type": "string", "format": "ipv6"}),
+        "Uuid" => json!({"type": "string", "format": "uuid"}),
+        "DateTime" | "OffsetDateTime" => json!({"type": "string", "format": "date-time"}),
+        "NaiveDate" | "Date" => json!({"type": "string", "format": "date"}),
+        "Option" if args.len() == 1 => return json_type(args[0]),
+        "ListEnvar" | "LazyList" if !args.is_empty() => {
+            json!({"type": "array", "items": json_type(args[0])})
+        }
+        _ => json!({"type": "string"}),
+    };
+    match keywords {
+        Value::Object(keywords) => keywords,
+        _ => unreachable!(),
+    }
+}
+
+/// The default as a JSON value matching the schema's type, if it can be expressed as one.
+fn json_default(schema: &Map<String, Value>, raw: &str) -> Option<Value> {
+    match schema.get("type").and_then(Value::as_str) {
+        Some("integer") => raw
+            .parse::<i64>()
+            .map(Value::from)
+            .or_else(|_| raw.parse::<u64>().map(Value::from))
+            .ok(),
+        Some("number") => raw
+            .parse::<f64>()
+            .ok()
+            .and_then(Number::from_f64)
+            .map(Value::Number),
+        Some("boolean") => raw.parse::<bool>().ok().map(Value::Bool),
+        Some("string") => Some(Value::String(raw.to_string())),
+        _ => None,
+    }
+}
+
+/// Renders a JSON Schema of the given variables: their types, defaults, and
+/// which of them are required. Sensitive variables are marked `writeOnly`
+/// and their defaults are left out.
+///
+/// The `EnvConfig` derive generates a `json_schema()` calling this with the
+/// fields of the struct.
+pub fn json_schema_of(entries: &[&dyn AnyEnvar]) -> String {
+    let mut properties = Map::new();
+    let mut required = vec![];
+    for envar in entries {
+        let mut schema = json_type(envar.typename());
+        if let Some(description) = envar.meta().description {
+            schema.insert("description".to_string(), json!(description));
+        }
+        if let Some(example) = envar.meta().example {
+            schema.insert("examples".to_string(), json!([example]));
+        }
+        if envar.is_sensitive() {
+            // like `env_template`, the default of a secret is not written out
+            schema.insert("writeOnly".to_string(), json!(true));
+        } else if let Some(default) = envar.default_raw() {
+            if let Some(default) = json_default(&schema, &default) {
+                schema.insert("default".to_string(), default);
+            }
+        }
+        if envar.is_required() {
+            required.push(json!(envar.name()));
+        }
+        properties.insert(envar.name().to_string(), Value::Object(schema));
+    }
+
+    let schema = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    });
+    serde_json::to_string_pretty(&schema).expect("a JSON value always serializes")
+}
+
+/// `json_schema_of` every registered variable.
+pub fn json_schema() -> String {
+    json_schema_of(&registry::entries())
+}
@@ -0,0 +1,103 @@
+use crate::core::{EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::ErrorReason;
+use std::borrow::Cow;
+use std::time::Duration;
+
+const UNITS: &[(&str, Duration)] = &[
+    ("ns", Duration::from_nanos(1)),
+    ("us", Duration::from_micros(1)),
+    ("ms", Duration::from_millis(1)),
+    ("s", Duration::from_secs(1)),
+    ("m", Duration::from_secs(60)),
+    ("h", Duration::from_secs(60 * 60)),
+    ("d", Duration::from_secs(24 * 60 * 60)),
+];
+
+/// `scale * amount`, or `None` if it does not fit in a `Duration`
+fn scaled(scale: Duration, amount: u64) -> Option<Duration> {
+    let nanos = scale.as_nanos().checked_mul(u128::from(amount))?;
+    let secs = u64::try_from(nanos / 1_000_000_000).ok()?;
+    Some(Duration::new(secs, (nanos % 1_000_000_000) as u32))
+}
+
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    // a plain integer is a number of milliseconds
+    if let Ok(millis) = value.parse::<u64>() {
+        return Ok(Duration::from_millis(millis));
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits == 0 {
+            return Err(format!("expected a number at {:?}", rest));
+        }
+        let amount: u64 = rest[..digits]
+            .parse()
+            .map_err(|_| format!("number too large at {:?}", rest))?;
+        rest = &rest[digits..];
+
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let unit = rest[..unit_len].trim();
+        let Some((_, scale)) = UNITS.iter().find(|(name, _)| *name == unit) else {
+            return Err(format!(
+                "unknown unit {:?}, expected one of ns, us, ms, s, m, h, d",
+                unit
+            ));
+        };
+        rest = rest[unit_len..].trim_start();
+
+        total = scaled(*scale, amount)
+            .and_then(|part| total.checked_add(part))
+            .ok_or_else(|| "duration overflows".to_string())?;
+    }
+    Ok(total)
+}
+
+/// Renders a duration with the largest units that represent it exactly, e.g. `2h30m`
+fn render_duration(value: &Duration) -> String {
+    if value.is_zero() {
+        return "0s".to_string();
+    }
+    let mut rest = value.as_nanos();
+    let mut out = String::new();
+    for (name, scale) in UNITS.iter().rev() {
+        let amount = rest / scale.as_nanos();
+        if amount > 0 {
+            out.push_str(&format!("{}{}", amount, name));
+            rest %= scale.as_nanos();
+        }
+    }
+    out
+}
+
+/// Accepts human-friendly durations (`30s`, `5m`, `2h30m`, `1s500ms`; units
+/// `ns`, `us`, `ms`, `s`, `m`, `h`, `d`) and plain integers as milliseconds.
+impl EnvarParse<Duration> for EnvarParser<Duration> {
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<Duration, EnvarError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(EnvarError::TryDefault(varname));
+        }
+
+        match parse_duration(value) {
+            Ok(duration) => Ok(duration),
+            Err(reason) => Err(EnvarError::ParseError {
+                varname,
+                typename: "Duration",
+                value: value.to_string(),
+                reason: ErrorReason::new(move || reason),
+            }),
+        }
+    }
+
+    fn render(value: &Duration) -> Option<String> {
+        Some(render_duration(value))
+    }
+}
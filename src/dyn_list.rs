@@ -0,0 +1,109 @@
+use crate::core::{parse_item, Envar, EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::list_envar::filter_items;
+use std::borrow::Cow;
+use std::marker::PhantomData;
+
+/// The separator and filtering of a `DynListEnvar`, chosen at runtime.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DynListConfig {
+    _sep: Cow<'static, str>,
+    _filter_empty_str: bool,
+    _filter_whitespace: bool,
+}
+
+impl DynListConfig {
+    /// Splits on `sep`, dropping empty and whitespace-only items.
+    ///
+    /// Panics if `sep` is empty.
+    pub const fn new(sep: &'static str) -> Self {
+        assert!(
+            !sep.is_empty(),
+            "DynListConfig: the separator must not be empty"
+        );
+        Self {
+            _sep: Cow::Borrowed(sep),
+            _filter_empty_str: true,
+            _filter_whitespace: true,
+        }
+    }
+
+    /// Like `new`, for a separator computed at runtime, e.g. read from another variable.
+    ///
+    /// Panics if `sep` is empty.
+    pub fn with_sep(sep: impl Into<Cow<'static, str>>) -> Self {
+        let sep = sep.into();
+        assert!(
+            !sep.is_empty(),
+            "DynListConfig: the separator must not be empty"
+        );
+        Self {
+            _sep: sep,
+            _filter_empty_str: true,
+            _filter_whitespace: true,
+        }
+    }
+
+    /// Keeps empty items, like `ListEnvarConfig::FILTER_EMPTY_STR = false`.
+    pub const fn keep_empty(mut self) -> Self {
+        self._filter_empty_str = false;
+        self
+    }
+
+    /// Keeps whitespace-only items (as empty strings), like
+    /// `ListEnvarConfig::FILTER_WHITESPACE = false`.
+    pub const fn keep_whitespace(mut self) -> Self {
+        self._filter_whitespace = false;
+        self
+    }
+
+    pub fn sep(&self) -> &str {
+        &self._sep
+    }
+
+    fn items<'a>(&'a self, value: &'a str) -> impl Iterator<Item = Cow<'a, str>> {
+        let items = value.split(self._sep.as_ref()).map(Cow::Borrowed);
+        filter_items(items, self._filter_empty_str, self._filter_whitespace)
+    }
+}
+
+/// A list variable whose separator is given as a `DynListConfig` value rather
+/// than a `ListEnvarConfig` type, read through an `Envar<String>`.
+pub struct DynListEnvar<T> {
+    _envar: Envar<String>,
+    _config: DynListConfig,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> DynListEnvar<T>
+where
+    EnvarParser<T>: EnvarParse<T>,
+{
+    pub const fn new(envar: Envar<String>, config: DynListConfig) -> Self {
+        Self {
+            _envar: envar,
+            _config: config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The variable holding the raw list
+    pub fn envar(&self) -> &Envar<String> {
+        &self._envar
+    }
+
+    pub fn config(&self) -> &DynListConfig {
+        &self._config
+    }
+
+    /// Reads the raw list and parses its items, failing on the first bad item.
+    pub fn value(&self) -> Result<Vec<T>, EnvarError> {
+        let raw = self._envar.value()?;
+        let varname: Cow<'static, str> = Cow::Owned(self._envar.name().to_string());
+        self._config
+            .items(&raw)
+            .enumerate()
+            .map(|(index, item)| parse_item::<T>(varname.clone(), index, &item))
+            .collect()
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::path::Path;
+use thiserror::Error;
+
+/// An error loading a shell-export (`.envrc`) or dotenv file.
+#[derive(Error, Debug)]
+pub enum EnvFileError {
+    Io(#[from] std::io::Error),
+    Syntax { line: usize, message: String },
+}
+
+impl std::fmt::Display for EnvFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnvFileError::Io(e) => write!(f, "cannot read env file: {}", e),
+            EnvFileError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Resolves `$NAME` against earlier assignments of the file, then override
+/// layers, then the process environment; unset names expand to nothing.
+fn resolve(name: &str, earlier: &HashMap<String, String>) -> String {
+    if let Some(value) = earlier.get(name) {
+        return value.clone();
+    }
+    match crate::layers::lookup(name) {
+        Some((_, value)) => value,
+        None => std::env::var(name).unwrap_or_default(),
+    }
+}
+
+/// Expands a `$NAME` or `${NAME}` reference whose `$` was just consumed
+fn expand(
+    chars: &mut std::iter::Peekable<std::str::Chars<'_>>,
+    earlier: &HashMap<String, String>,
+) -> Result<String, String> {
+    let mut name = String::new();
+    if chars.peek() == Some(&'{') {
+        chars.next();
+        loop {
+            match chars.next() {
+                Some('}') => break,
+                Some(c) if is_name_char(c) => name.push(c),
+                _ => return Err("malformed ${...} reference".to_string()),
+            }
+        }
+    } else {
+        while let Some(&c) = chars.peek() {
+            if !(is_name_char(c) && (!name.is_empty() || is_name_start(c))) {
+                break;
+            }
+            name.push(c);
+            chars.next();
+        }
+        if name.is_empty() {
+            return Ok("$".to_string());
+        }
+    }
+    Ok(resolve(&name, earlier))
+}
+
+fn parse_value(raw: &str, earlier: &HashMap<String, String>) -> Result<String, String> {
+    let mut value = String::new();
+    let mut chars = raw.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => loop {
+                match chars.next() {
+                    Some('\'') => break,
+                    Some(c) => value.push(c),
+                    None => return Err("unterminated single quote".to_string()),
+                }
+            },
+            '"' => loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => match chars.next() {
+                        Some(c @ ('"' | '\\' | '$' | '`')) => value.push(c),
+                        Some('n') => value.push('\n'),
+                        Some(c) => {
+                            value.push('\\');
+                            value.push(c);
+                        }
+                        None => return Err("unterminated double quote".to_string()),
+                    },
+                    Some('$') => value.push_str(&expand(&mut chars, earlier)?),
+                    Some(c) => value.push(c),
+                    None => return Err("unterminated double quote".to_string()),
+                }
+            },
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    value.push(c);
+                }
+            }
+            '$' => value.push_str(&expand(&mut chars, earlier)?),
+            c if c.is_whitespace() => {
+                let rest: String = chars.collect();
+                let rest = rest.trim_start();
+                if !rest.is_empty() && !rest.starts_with('#') {
+                    return Err(format!(
+                        "unexpected {:?} after value; quote values containing spaces",
+                        rest
+                    ));
+                }
+                break;
+            }
+            c => value.push(c),
+        }
+    }
+    Ok(value)
+}
+
+/// Parses a shell-export file (as used by direnv's `.envrc`) or a dotenv file.
+///
+/// Understands `export KEY=value` and `KEY=value` lines, `#` comments,
+/// single quotes (literal), double quotes (with `\` escapes), and `$VAR` /
+/// `${VAR}` references to earlier keys, override layers or the process
+/// environment. Other shell commands are skipped with a warning.
+pub fn parse_env_file(contents: &str) -> Result<Vec<(String, String)>, EnvFileError> {
+    let mut entries: Vec<(String, String)> = vec![];
+    let mut earlier: HashMap<String, String> = HashMap::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line_no = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let assignment = line
+            .strip_prefix("export")
+            .filter(|rest| rest.starts_with(char::is_whitespace))
+            .map_or(line, str::trim_start);
+
+        let Some((name, raw)) = assignment.split_once('=') else {
+            crate::warnings::emit(&format!("env file line {}: skipping {:?}", line_no, line));
+            continue;
+        };
+        let valid_name = name.starts_with(is_name_start) && name.chars().all(is_name_char);
+        if !valid_name {
+            crate::warnings::emit(&format!("env file line {}: skipping {:?}", line_no, line));
+            continue;
+        }
+
+        let value = parse_value(raw, &earlier).map_err(|message| EnvFileError::Syntax {
+            line: line_no,
+            message,
+        })?;
+        earlier.insert(name.to_string(), value.clone());
+        entries.push((name.to_string(), value));
+    }
+    Ok(entries)
+}
+
+/// Reads and parses an env file (see `parse_env_file`) and pushes it as an
+/// override layer, removed when the returned guard is dropped.
+pub fn load_env_file(path: impl AsRef<Path>) -> Result<crate::layers::LayerGuard, EnvFileError> {
+    let contents = std::fs::read_to_string(path)?;
+    let entries = parse_env_file(&contents)?;
+    Ok(crate::layers::push_layer(entries))
+}
+
+/// Reads and parses an env file into a map, e.g. to use as an `EnvSource`
+/// or a layer of a `LayeredSource`. Later assignments win.
+pub fn read_env_file(path: impl AsRef<Path>) -> Result<HashMap<String, String>, EnvFileError> {
+    let contents = std::fs::read_to_string(path)?;
+    Ok(parse_env_file(&contents)?.into_iter().collect())
+}
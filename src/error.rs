@@ -2,9 +2,10 @@ use crate::ErrorReason;
 use std::borrow::Cow;
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+/// Messages are rendered by the installed `MessageFormatter`
+/// (`DefaultMessageFormatter` unless replaced via `set_message_formatter`).
+#[derive(Error, Debug, Clone)]
 pub enum EnvarError {
-    #[error("Cannot parse environment variable {varname} (value = {value:?}) as {typename}")]
     ParseError {
         varname: Cow<'static, str>,
         typename: &'static str,
@@ -12,14 +13,228 @@ pub enum EnvarError {
         reason: ErrorReason,
     },
 
-    #[error("Environment variable {0} is not set")]
+    TooLong {
+        varname: Cow<'static, str>,
+        len: usize,
+        max_len: usize,
+    },
+
+    /// A list has fewer items than `ListEnvarConfig::MIN_LEN` or more than `MAX_LEN`
+    ListLength {
+        varname: Cow<'static, str>,
+        len: usize,
+        min_len: usize,
+        max_len: usize,
+    },
+
     NotSet(Cow<'static, str>),
 
+    /// Neither the variable nor any of its fallback names is set
+    NotSetAliases {
+        varname: Cow<'static, str>,
+        aliases: Vec<&'static str>,
+    },
+
+    /// The parsed value is outside the bounds set by `Envar::in_range`
+    OutOfRange {
+        varname: Cow<'static, str>,
+        value: String,
+        /// The bounds, e.g. "1..=65535"
+        range: String,
+    },
+
+    /// The value is not one of those accepted by `Envar::one_of`
+    NotAllowed {
+        varname: Cow<'static, str>,
+        value: String,
+        allowed: &'static [&'static str],
+        /// The allowed value closest to `value`, if it looks like a typo
+        suggestion: Option<&'static str>,
+    },
+
+    /// The value in the process environment is not valid UTF-8
+    NotUnicode(Cow<'static, str>),
+
+    /// The file named by a `<NAME>_FILE` variable (`varname`) could not be read
+    FileError {
+        varname: Cow<'static, str>,
+        path: String,
+        reason: String,
+    },
+
+    /// The source could not be read for `varname`, e.g. a remote store was still
+    /// unreachable after every attempt of a `RetryingSource`
+    SourceError {
+        varname: Cow<'static, str>,
+        reason: String,
+    },
+
     // This is a special case:
     // Sometimes even if the environment variable is set, we
     // might prefer to use the default value (if any).
     // For example, if the environment variable is set to an empty string,
     // we might prefer to use the default value.
-    #[error("Environment variable {0} is not set and default factory returned None")]
     TryDefault(Cow<'static, str>),
 }
+
+impl std::fmt::Display for EnvarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::messages::format_error(self, f)
+    }
+}
+
+/// `sysexits.h`: the command was used incorrectly, e.g. a required variable is missing
+pub const EX_USAGE: i32 = 64;
+/// `sysexits.h`: something was found in an unconfigured or misconfigured state
+pub const EX_CONFIG: i32 = 78;
+/// `sysexits.h`: a temporary failure, retrying later may succeed
+pub const EX_TEMPFAIL: i32 = 75;
+
+impl EnvarError {
+    /// The conventional `sysexits.h` code for exiting on this error:
+    /// `EX_USAGE` for missing variables, `EX_TEMPFAIL` for sources that could
+    /// not be read, `EX_CONFIG` for bad values.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            EnvarError::NotSet(_)
+            | EnvarError::NotSetAliases { .. }
+            | EnvarError::TryDefault(_) => EX_USAGE,
+            EnvarError::SourceError { .. } => EX_TEMPFAIL,
+            _ => EX_CONFIG,
+        }
+    }
+}
+
+/// Prints the error to stderr and exits with its `exit_code`.
+pub fn report_and_exit(error: &EnvarError) -> ! {
+    eprintln!("error: {}", error);
+    std::process::exit(error.exit_code())
+}
+
+/// Every error found while validating a set of variables, in declaration order.
+#[derive(Error, Debug)]
+pub struct EnvarErrors {
+    pub errors: Vec<EnvarError>,
+}
+
+impl std::fmt::Display for EnvarErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::messages::format_errors(&self.errors, f)
+    }
+}
+
+impl EnvarErrors {
+    /// `EX_USAGE` if every error is a missing variable, `EX_CONFIG` otherwise.
+    pub fn exit_code(&self) -> i32 {
+        if self
+            .errors
+            .iter()
+            .all(|error| error.exit_code() == EX_USAGE)
+        {
+            EX_USAGE
+        } else {
+            EX_CONFIG
+        }
+    }
+}
+
+impl From<EnvarError> for EnvarErrors {
+    fn from(error: EnvarError) -> Self {
+        EnvarErrors {
+            errors: vec![error],
+        }
+    }
+}
+
+impl FromIterator<EnvarError> for EnvarErrors {
+    fn from_iter<I: IntoIterator<Item = EnvarError>>(iter: I) -> Self {
+        EnvarErrors {
+            errors: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Results of reading several variables that can be combined by `collect`.
+///
+/// Implemented for arrays and `Vec`s of `Result<T, EnvarError>` (collected into a `Vec<T>`)
+/// and for tuples of up to eight results of different types.
+pub trait EnvarResults {
+    type Output;
+
+    fn collect_all(self) -> Result<Self::Output, EnvarErrors>;
+}
+
+/// Combines several results, keeping every error instead of stopping at the first.
+///
+/// `let (port, host) = typed_env::collect((PORT.value(), HOST.value()))?;`
+pub fn collect<R: EnvarResults>(results: R) -> Result<R::Output, EnvarErrors> {
+    results.collect_all()
+}
+
+impl<T> EnvarResults for Vec<Result<T, EnvarError>> {
+    type Output = Vec<T>;
+
+    fn collect_all(self) -> Result<Vec<T>, EnvarErrors> {
+        let mut values = Vec::with_capacity(self.len());
+        let mut errors = Vec::new();
+        for result in self {
+            match result {
+                Ok(value) => values.push(value),
+                Err(e) => errors.push(e),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(EnvarErrors { errors });
+        }
+        Ok(values)
+    }
+}
+
+impl<T, const N: usize> EnvarResults for [Result<T, EnvarError>; N] {
+    type Output = Vec<T>;
+
+    fn collect_all(self) -> Result<Vec<T>, EnvarErrors> {
+        Vec::from(self).collect_all()
+    }
+}
+
+macro_rules! impl_envar_results_tuple {
+    ($($t:ident $v:ident),+) => {
+        impl<$($t),+> EnvarResults for ($(Result<$t, EnvarError>,)+) {
+            type Output = ($($t,)+);
+
+            fn collect_all(self) -> Result<Self::Output, EnvarErrors> {
+                let ($($v,)+) = self;
+                let mut errors = Vec::new();
+                $(
+                let $v = match $v {
+                    Ok(value) => Some(value),
+                    Err(e) => {
+                        errors.push(e);
+                        None
+                    }
+                };
+                )+
+                if !errors.is_empty() {
+                    return Err(EnvarErrors { errors });
+                }
+                Ok(($($v.expect("every failed result is reported above"),)+))
+            }
+        }
+    };
+}
+
+impl_envar_results_tuple!(A a);
+impl_envar_results_tuple!(A a, B b);
+impl_envar_results_tuple!(A a, B b, C c);
+impl_envar_results_tuple!(A a, B b, C c, D d);
+impl_envar_results_tuple!(A a, B b, C c, D d, E e);
+impl_envar_results_tuple!(A a, B b, C c, D d, E e, F f);
+impl_envar_results_tuple!(A a, B b, C c, D d, E e, F f, G g);
+impl_envar_results_tuple!(A a, B b, C c, D d, E e, F f, G g, H h);
+
+/// Prints the aggregated report to stderr and exits with its `exit_code`.
+pub fn report_all_and_exit(errors: &EnvarErrors) -> ! {
+    eprint!("{}", errors);
+    std::process::exit(errors.exit_code())
+}
@@ -1,6 +1,15 @@
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex, PoisonError};
 
+/// A lazily rendered error reason.
+///
+/// Clones share the provider and the rendered string, so cloning is cheap
+/// and the reason is rendered at most once.
+#[derive(Clone)]
 pub struct ErrorReason {
+    inner: Arc<ErrorReasonInner>,
+}
+
+struct ErrorReasonInner {
     error_provider: Mutex<Option<Box<dyn 'static + Sync + Send + FnOnce() -> String>>>,
     reason_str: std::sync::OnceLock<String>,
 }
@@ -20,29 +29,28 @@ impl std::fmt::Display for ErrorReason {
 impl ErrorReason {
     pub fn new(producer: impl 'static + Sync + Send + FnOnce() -> String) -> Self {
         Self {
-            error_provider: Mutex::new(Some(Box::new(producer))),
-            reason_str: std::sync::OnceLock::new(),
+            inner: Arc::new(ErrorReasonInner {
+                error_provider: Mutex::new(Some(Box::new(producer))),
+                reason_str: std::sync::OnceLock::new(),
+            }),
         }
     }
 
     pub fn as_str(&self) -> &str {
-        let result = self
-            .reason_str
-            .get_or_init(|| match self.error_provider.lock() {
-                Err(e) => {
-                    panic!(
-                        "typed-error internal error: cannot lock to get error provider: {}",
-                        e
-                    );
-                }
-                Ok(mut error_producer) => {
-                    let error_producer = error_producer.take();
-                    match error_producer {
-                        None => panic!("typed-error internal error: provider has been consumed"),
-                        Some(error_producer) => (error_producer)(),
-                    }
-                }
-            });
+        let result = self.inner.reason_str.get_or_init(|| {
+            // the provider is taken out before running it, so a panicking provider
+            // never poisons the lock; later calls see it consumed and fall back
+            let error_producer = self
+                .inner
+                .error_provider
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take();
+            match error_producer {
+                None => "<error reason unavailable>".to_string(),
+                Some(error_producer) => (error_producer)(),
+            }
+        });
 
         result.as_str()
     }
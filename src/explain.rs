@@ -0,0 +1,215 @@
+use crate::core::{Envar, EnvarDef, EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use std::borrow::Cow;
+
+/// One place a raw value was looked up.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lookup {
+    pub name: String,
+    /// "test scope", "override layer", or the label of the `EnvSource`
+    pub source: String,
+    pub raw: Option<String>,
+}
+
+/// A trace of how an `Envar` resolves, returned by `Envar::explain`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Explanation {
+    pub name: String,
+    /// Lookups in the order they were tried
+    pub lookups: Vec<Lookup>,
+    /// The raw value that was found, if any
+    pub raw: Option<String>,
+    /// The type the raw value is parsed as
+    pub parser: &'static str,
+    /// Whether the value comes from the default factory
+    pub default_applied: bool,
+    /// Whether the value was frozen by an earlier `on_startup` read
+    pub cached: bool,
+    /// Whether the value was set by `Envar::set_override`
+    pub overridden: bool,
+    /// The `Debug` rendering of the value, or the error message
+    pub outcome: Result<String, String>,
+}
+
+impl std::fmt::Display for Explanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} ({})", self.name, self.parser)?;
+        for lookup in &self.lookups {
+            match &lookup.raw {
+                Some(raw) => writeln!(f, "  {} in {}: {:?}", lookup.name, lookup.source, raw)?,
+                None => writeln!(f, "  {} in {}: not set", lookup.name, lookup.source)?,
+            }
+        }
+        if self.overridden {
+            writeln!(f, "  value set by set_override")?;
+        }
+        if self.cached {
+            writeln!(f, "  value frozen by an earlier on_startup read")?;
+        }
+        if self.default_applied {
+            writeln!(f, "  default applied")?;
+        }
+        match &self.outcome {
+            Ok(value) => write!(f, "  => {}", value),
+            Err(error) => write!(f, "  => error: {}", error),
+        }
+    }
+}
+
+impl<T, F> Envar<T, F>
+where
+    T: Clone + std::fmt::Debug + 'static,
+    EnvarParser<T>: EnvarParse<T>,
+    F: Fn() -> EnvarDef<T>,
+{
+    /// Looks up one name, recording where it was looked up in `lookups`
+    fn trace_lookup(
+        &self,
+        name: &str,
+        lookups: &mut Vec<Lookup>,
+        error: &mut Option<EnvarError>,
+    ) -> Option<String> {
+        if let Some(raw) = crate::test::lookup(name) {
+            lookups.push(Lookup {
+                name: name.to_string(),
+                source: "test scope".to_string(),
+                raw: raw.clone(),
+            });
+            return raw;
+        }
+        if let Some((_, raw)) = crate::layers::lookup(name) {
+            lookups.push(Lookup {
+                name: name.to_string(),
+                source: "override layer".to_string(),
+                raw: Some(raw.clone()),
+            });
+            return Some(raw);
+        }
+        let raw = match self.read_source(name) {
+            // shown lossily, as the type reads the original
+            Ok(Some(raw)) if <EnvarParser<T> as EnvarParse<T>>::ACCEPTS_NON_UNICODE => {
+                Ok(Some(raw.to_string_lossy().into_owned()))
+            }
+            Ok(raw) => self.decode_raw(raw),
+            Err(e) => Err(e),
+        };
+        let raw = match raw {
+            Ok(raw) => raw,
+            Err(e) => {
+                *error = Some(e);
+                None
+            }
+        };
+        lookups.push(Lookup {
+            name: name.to_string(),
+            source: self.source_origin(name),
+            raw: raw.clone(),
+        });
+        raw
+    }
+
+    /// Traces how this variable resolves, without touching its cache or statistics.
+    ///
+    /// For sensitive variables, raw values and the outcome are shown as `***`;
+    /// the paths in `<NAME>_FILE` variables are kept.
+    pub fn explain(&self) -> Explanation {
+        let mut explanation = self.trace();
+        if self.is_sensitive() {
+            let mask = |raw: &mut Option<String>| {
+                if raw.is_some() {
+                    *raw = Some("***".to_string());
+                }
+            };
+            for lookup in &mut explanation.lookups {
+                if !self
+                    .lookup_names()
+                    .any(|name| lookup.name == crate::core::file_varname(name))
+                {
+                    mask(&mut lookup.raw);
+                }
+            }
+            mask(&mut explanation.raw);
+            if explanation.outcome.is_ok() {
+                explanation.outcome = Ok("***".to_string());
+            }
+        }
+        explanation
+    }
+
+    fn trace(&self) -> Explanation {
+        let name = self.name();
+        let mut lookups = vec![];
+        let mut lookup_error = None;
+        let mut raw = None;
+        // the primary name, then each alias, until one is set
+        for name in self.lookup_names() {
+            let mut found = self.trace_lookup(name, &mut lookups, &mut lookup_error);
+            if found.is_none() && lookup_error.is_none() && self.reads_file_fallback() {
+                let file_varname = crate::core::file_varname(name);
+                if let Some(path) =
+                    self.trace_lookup(&file_varname, &mut lookups, &mut lookup_error)
+                {
+                    match crate::core::read_value_file(name, &path) {
+                        Ok(contents) => found = Some(contents),
+                        Err(e) => lookup_error = Some(e),
+                    }
+                }
+            }
+            if found.is_some() || lookup_error.is_some() {
+                raw = found;
+                break;
+            }
+        }
+        let mut explanation = Explanation {
+            name: name.to_string(),
+            lookups,
+            raw: raw.clone(),
+            parser: std::any::type_name::<T>(),
+            default_applied: false,
+            cached: false,
+            overridden: false,
+            outcome: Err(String::new()),
+        };
+
+        if let Some(value) = self.override_value() {
+            explanation.overridden = true;
+            explanation.outcome = Ok(format!("{:?}", value));
+            return explanation;
+        }
+
+        if let Some(value) = self.startup_value() {
+            explanation.cached = true;
+            explanation.outcome = Ok(format!("{:?}", value));
+            return explanation;
+        }
+
+        if let Some(e) = lookup_error {
+            explanation.outcome = Err(e.to_string());
+            return explanation;
+        }
+
+        let parsed = match raw.as_ref() {
+            None => Err(EnvarError::TryDefault(Cow::Owned(name.to_string()))),
+            Some(raw) => self.parse_unrecorded(raw.as_str()),
+        };
+
+        explanation.outcome = match parsed {
+            Ok(value) => Ok(format!("{:?}", value)),
+            Err(EnvarError::TryDefault(_)) => match self.default_value() {
+                EnvarDef::Default(default) => {
+                    explanation.default_applied = true;
+                    Ok(format!("{:?}", default))
+                }
+                EnvarDef::Unset => Err(self.not_set().to_string()),
+            },
+            Err(e) => match self.parse_error_fallback() {
+                Some(default) => {
+                    explanation.default_applied = true;
+                    Ok(format!("{:?}", default))
+                }
+                None => Err(e.to_string()),
+            },
+        };
+        explanation
+    }
+}
@@ -0,0 +1,131 @@
+use crate::core::{EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::ErrorReason;
+use std::borrow::Cow;
+
+/// A feature flag, parsed from a boolean word (`on`, `off`, `true`, ...),
+/// a rollout percentage (`25%`) or an allow-list (`allow:user1,user2`).
+///
+/// Empty values are treated as `off`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum FeatureFlag {
+    On,
+    #[default]
+    Off,
+    /// Enabled for this percentage (0 to 100) of keys, bucketed per `flag`
+    /// (the variable name) so that separate flags roll out to separate keys
+    Percent {
+        percent: u8,
+        flag: Cow<'static, str>,
+    },
+    /// Enabled for exactly these keys
+    Allow(Vec<String>),
+}
+
+impl FeatureFlag {
+    /// Whether the flag is enabled for everyone.
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            FeatureFlag::On => true,
+            FeatureFlag::Percent { percent, .. } => *percent >= 100,
+            FeatureFlag::Off | FeatureFlag::Allow(_) => false,
+        }
+    }
+
+    /// Whether the flag is enabled for `key`, e.g. a user or tenant id.
+    ///
+    /// Percentage rollouts hash the flag's name and the key with FNV-1a, so a key
+    /// stays in or out of the rollout across restarts and hosts, raising the
+    /// percentage only ever adds keys, and two flags at 10% reach different keys.
+    pub fn is_enabled_for(&self, key: &str) -> bool {
+        match self {
+            FeatureFlag::On => true,
+            FeatureFlag::Off => false,
+            FeatureFlag::Percent { percent, flag } => {
+                stable_bucket(flag, key) < u64::from(*percent)
+            }
+            FeatureFlag::Allow(keys) => keys.iter().any(|allowed| allowed == key),
+        }
+    }
+}
+
+/// The 64-bit FNV-1a hash of `bytes`, stable across platforms and releases
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// The FNV-1a hash of `flag` and `key`, reduced to a bucket in `0..100`
+fn stable_bucket(flag: &str, key: &str) -> u64 {
+    fnv1a(format!("{}\0{}", flag, key).as_bytes()) % 100
+}
+
+impl std::fmt::Display for FeatureFlag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FeatureFlag::On => write!(f, "on"),
+            FeatureFlag::Off => write!(f, "off"),
+            FeatureFlag::Percent { percent, .. } => write!(f, "{}%", percent),
+            FeatureFlag::Allow(keys) => write!(f, "allow:{}", keys.join(",")),
+        }
+    }
+}
+
+impl EnvarParse<FeatureFlag> for EnvarParser<FeatureFlag> {
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<FeatureFlag, EnvarError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Ok(FeatureFlag::Off);
+        }
+
+        if let Some(keys) = value.strip_prefix("allow:") {
+            let keys = keys
+                .split(',')
+                .map(str::trim)
+                .filter(|key| !key.is_empty())
+                .map(str::to_string)
+                .collect();
+            return Ok(FeatureFlag::Allow(keys));
+        }
+
+        if let Some(percent) = value.strip_suffix('%') {
+            if let Ok(percent @ 0..=100) = percent.trim().parse::<u8>() {
+                return Ok(FeatureFlag::Percent {
+                    percent,
+                    flag: varname,
+                });
+            }
+        }
+
+        if crate::special_constants::TRUE_ALTERNATIVES
+            .iter()
+            .any(|alternative| alternative.eq_ignore_ascii_case(value))
+        {
+            return Ok(FeatureFlag::On);
+        }
+
+        if crate::special_constants::FALSE_ALTERNATIVES
+            .iter()
+            .any(|alternative| alternative.eq_ignore_ascii_case(value))
+        {
+            return Ok(FeatureFlag::Off);
+        }
+
+        Err(EnvarError::ParseError {
+            varname,
+            typename: "FeatureFlag",
+            value: value.to_string(),
+            reason: ErrorReason::new(|| {
+                "expected on/off, a percentage from 0% to 100%, or allow:key1,key2".to_string()
+            }),
+        })
+    }
+
+    fn render(value: &FeatureFlag) -> Option<String> {
+        Some(value.to_string())
+    }
+}
@@ -0,0 +1,48 @@
+use crate::core::{EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::ErrorReason;
+use std::borrow::Cow;
+use std::str::FromStr;
+
+/// Parses any `T: FromStr` whose error implements `Display`, for types without
+/// their own `EnvarParse` impl (e.g. from third-party crates).
+///
+/// The value is passed to `from_str` as-is. There is no raw form to render back
+/// (`render` is `None`), since `T` need not implement `Display`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FromStrEnvar<T>(pub T);
+
+impl<T> FromStrEnvar<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> std::ops::Deref for FromStrEnvar<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> EnvarParse<FromStrEnvar<T>> for EnvarParser<FromStrEnvar<T>>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<FromStrEnvar<T>, EnvarError> {
+        match value.parse::<T>() {
+            Ok(parsed) => Ok(FromStrEnvar(parsed)),
+            Err(e) => {
+                let reason = e.to_string();
+                Err(EnvarError::ParseError {
+                    varname,
+                    typename: std::any::type_name::<T>(),
+                    value: value.to_string(),
+                    reason: ErrorReason::new(move || reason),
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use crate::core::{EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::ErrorReason;
+use ::ipnet::{IpNet, Ipv4Net, Ipv6Net};
+use std::borrow::Cow;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Parses `addr/prefix`, or a bare address as a single-host network (`/32` or
+/// `/128`), so allow-lists can mix networks and hosts.
+macro_rules! impl_ip_net {
+    ($($net:ident($addr:ty, $max_prefix:expr)),*) => {
+        $(
+        impl EnvarParse<$net> for EnvarParser<$net> {
+            fn parse(varname: Cow<'static, str>, value: &str) -> Result<$net, EnvarError> {
+                let value = value.trim();
+                if value.is_empty() {
+                    return Err(EnvarError::TryDefault(varname));
+                }
+                if let Ok(addr) = value.parse::<$addr>() {
+                    return Ok($net::new(addr, $max_prefix(&addr))
+                        .expect("the full prefix length is valid"));
+                }
+                value.parse::<$net>().map_err(|e| EnvarError::ParseError {
+                    varname,
+                    typename: stringify!($net),
+                    value: value.to_string(),
+                    reason: ErrorReason::new(move || {
+                        format!("{}, expected a network such as 10.0.0.0/8", e)
+                    }),
+                })
+            }
+
+            fn render(value: &$net) -> Option<String> {
+                Some(value.to_string())
+            }
+        }
+        )*
+    };
+}
+
+impl_ip_net!(
+    IpNet(IpAddr, |addr: &IpAddr| if addr.is_ipv4() {
+        32
+    } else {
+        128
+    }),
+    Ipv4Net(Ipv4Addr, |_: &Ipv4Addr| 32),
+    Ipv6Net(Ipv6Addr, |_: &Ipv6Addr| 128)
+);
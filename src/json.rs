@@ -0,0 +1,47 @@
+use crate::core::{EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::ErrorReason;
+use serde::de::DeserializeOwned;
+use std::borrow::Cow;
+
+/// A JSON value deserialized into `T`, e.g. `FEATURES='{"a":true,"b":3}'`.
+///
+/// Deserialization errors are reported as `EnvarError::ParseError`, with serde's
+/// message (including the line and column) as the reason. There is no raw form
+/// to render back (`render` is `None`), since `T` need not implement `Serialize`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Json<T>(pub T);
+
+impl<T> Json<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> std::ops::Deref for Json<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: DeserializeOwned> EnvarParse<Json<T>> for EnvarParser<Json<T>> {
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<Json<T>, EnvarError> {
+        if value.trim().is_empty() {
+            return Err(EnvarError::TryDefault(varname));
+        }
+        match serde_json::from_str::<T>(value) {
+            Ok(parsed) => Ok(Json(parsed)),
+            Err(e) => {
+                let reason = e.to_string();
+                Err(EnvarError::ParseError {
+                    varname,
+                    typename: std::any::type_name::<T>(),
+                    value: value.to_string(),
+                    reason: ErrorReason::new(move || reason),
+                })
+            }
+        }
+    }
+}
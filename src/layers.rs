@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{PoisonError, RwLock};
+
+// each layer is tagged with an id, so a guard removes its own layer wherever it sits
+static LAYERS: RwLock<Vec<(u64, HashMap<String, String>)>> = RwLock::new(Vec::new());
+static NEXT_LAYER_ID: AtomicU64 = AtomicU64::new(0);
+
+// bumped on every push/pop so `on_demand` caches know to re-resolve
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Removes its layer when dropped, even if other layers were pushed on top of it since.
+///
+/// Call `keep` to leave the layer in place until `pop_layer` instead.
+#[must_use = "the layer is removed as soon as the guard is dropped; call `keep()` to leave it in place"]
+#[derive(Debug)]
+pub struct LayerGuard {
+    id: u64,
+}
+
+impl LayerGuard {
+    /// Removes the layer now, returning it (`None` if it was already popped).
+    pub fn pop(self) -> Option<HashMap<String, String>> {
+        let layer = remove_layer(self.id);
+        std::mem::forget(self);
+        layer
+    }
+
+    /// Leaves the layer in place for the rest of the process, or until `pop_layer`.
+    pub fn keep(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for LayerGuard {
+    fn drop(&mut self) {
+        remove_layer(self.id);
+    }
+}
+
+fn remove_layer(id: u64) -> Option<HashMap<String, String>> {
+    let mut layers = LAYERS.write().unwrap_or_else(PoisonError::into_inner);
+    let position = layers.iter().position(|(layer_id, _)| *layer_id == id)?;
+    let (_, layer) = layers.remove(position);
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    Some(layer)
+}
+
+/// Pushes a layer of raw values that shadows the process environment (and lower layers)
+/// until the returned guard is dropped.
+///
+/// Useful for request- or phase-scoped configuration, e.g. a migration mode.
+pub fn push_layer<K, V>(layer: impl IntoIterator<Item = (K, V)>) -> LayerGuard
+where
+    K: Into<String>,
+    V: Into<String>,
+{
+    let layer = layer
+        .into_iter()
+        .map(|(k, v)| (k.into(), v.into()))
+        .collect();
+    let id = NEXT_LAYER_ID.fetch_add(1, Ordering::Relaxed);
+    let mut layers = LAYERS.write().unwrap_or_else(PoisonError::into_inner);
+    layers.push((id, layer));
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    LayerGuard { id }
+}
+
+/// Removes the topmost layer, returning it; its guard, if any, then does nothing.
+pub fn pop_layer() -> Option<HashMap<String, String>> {
+    let mut layers = LAYERS.write().unwrap_or_else(PoisonError::into_inner);
+    let layer = layers.pop().map(|(_, layer)| layer);
+    if layer.is_some() {
+        GENERATION.fetch_add(1, Ordering::SeqCst);
+    }
+    layer
+}
+
+/// The number of layers currently pushed.
+pub fn layer_depth() -> usize {
+    LAYERS.read().unwrap_or_else(PoisonError::into_inner).len()
+}
+
+/// Expires `on_demand_ttl` caches, e.g. after the source changed
+pub(crate) fn bump_generation() {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+pub(crate) fn generation() -> u64 {
+    GENERATION.load(Ordering::SeqCst)
+}
+
+/// Finds `name` in the layers, topmost first, returning the layer index and raw value.
+pub(crate) fn lookup(name: &str) -> Option<(usize, String)> {
+    let layers = LAYERS.read().unwrap_or_else(PoisonError::into_inner);
+    layers
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(index, (_, layer))| layer.get(name).map(|raw| (index, raw.clone())))
+}
+
+/// Every layered name and raw value, upper layers taking precedence.
+pub(crate) fn vars() -> HashMap<String, String> {
+    let layers = LAYERS.read().unwrap_or_else(PoisonError::into_inner);
+    let mut vars = HashMap::new();
+    for (_, layer) in layers.iter() {
+        vars.extend(layer.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+    vars
+}
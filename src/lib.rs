@@ -1,13 +1,122 @@
+extern crate self as typed_env;
+
+mod byte_size;
+mod bytes;
+#[cfg(feature = "chrono")]
+mod chrono;
 mod core;
+#[cfg(feature = "serde")]
+pub mod de;
+mod deployment_env;
+mod disk_cache;
+#[cfg(feature = "docgen")]
+pub mod docgen;
+mod duration;
+#[cfg(feature = "list")]
+mod dyn_list;
+mod env_file;
 mod error;
 mod error_reason;
+mod explain;
+mod feature_flag;
+mod from_str;
+#[cfg(feature = "ipnet")]
+mod ipnet;
+#[cfg(feature = "serde_json")]
+mod json;
+mod layers;
+#[cfg(feature = "list")]
 mod list_envar;
+#[cfg(any(feature = "log", feature = "tracing"))]
+mod log_level;
+mod macros;
+#[cfg(feature = "list")]
+mod map_envar;
+mod messages;
+mod multiline;
+mod namespace;
+mod net;
+mod pair;
+mod path;
+mod prefix_map;
+mod provenance;
+mod range;
+mod ratio;
+#[cfg(feature = "regex")]
+mod regex;
+#[cfg(feature = "registry")]
+pub mod registry;
+mod retry;
+mod secret;
+#[cfg(feature = "semver")]
+mod semver;
+#[cfg(feature = "list")]
+mod set_envar;
+#[cfg(feature = "registry")]
+pub mod snapshot;
+mod source;
 mod special_constants;
+mod stats;
+mod strings;
+mod suggest;
+pub mod test;
+#[cfg(feature = "time")]
+mod time;
+#[cfg(feature = "uuid")]
+mod uuid;
+mod verbosity;
+mod warnings;
+mod watch;
+mod weighted_list;
 
+pub use byte_size::*;
+pub use bytes::*;
 pub use core::*;
+pub use deployment_env::*;
+pub use disk_cache::*;
+#[cfg(feature = "list")]
+pub use dyn_list::*;
+pub use env_file::*;
 pub use error::*;
 pub use error_reason::*;
+pub use explain::*;
+pub use feature_flag::*;
+pub use from_str::*;
+#[cfg(feature = "serde_json")]
+pub use json::*;
+pub use layers::*;
+#[cfg(feature = "list")]
 pub use list_envar::*;
+#[cfg(feature = "list")]
+pub use map_envar::*;
+pub use messages::*;
+pub use multiline::*;
+pub use namespace::*;
+pub use net::*;
+pub use pair::*;
+pub use prefix_map::*;
+pub use provenance::*;
+pub use ratio::*;
+pub use retry::*;
+pub use secret::*;
+#[cfg(feature = "list")]
+pub use set_envar::*;
+pub use source::*;
+pub use stats::*;
+pub use strings::*;
+pub use verbosity::*;
+pub use warnings::*;
+pub use watch::*;
+pub use weighted_list::*;
+
+#[cfg(all(feature = "derive", feature = "registry"))]
+pub use typed_env_derive::main;
+#[cfg(feature = "derive")]
+pub use typed_env_derive::EnvConfig;
+#[cfg(feature = "derive")]
+pub use typed_env_derive::EnvarEnum;
+#[cfg(feature = "derive")]
+pub use typed_env_derive::EnvarParse;
 
 #[cfg(test)]
 mod tests;
@@ -1,6 +1,22 @@
+use crate::core::{parse_item, EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use std::borrow::Cow;
 use std::marker::PhantomData;
 use std::sync::Arc;
 
+/// Items of type `E` split from one value as configured by `C`.
+///
+/// Lists can be nested, e.g. `ListEnvar<ListEnvar<u32, CharSep<'|'>>, SemicolonList>`
+/// for `SHARDS="1|2|3;4|5"`. The inner separator must not contain the outer one,
+/// which is checked at compile time:
+///
+/// ```compile_fail
+/// use typed_env::{CommaList, Envar, EnvarDef, ListEnvar};
+///
+/// static GROUPS: Envar<ListEnvar<ListEnvar<u32, CommaList>, CommaList>> =
+///     Envar::on_demand("GROUPS", || EnvarDef::Unset);
+/// let _ = GROUPS.value();
+/// ```
 pub struct ListEnvar<E, C> {
     _marker: PhantomData<C>,
     _vec: Arc<Vec<E>>,
@@ -25,10 +41,250 @@ pub trait ListEnvarConfig {
 
     /// Whether to filter whitespace
     const FILTER_WHITESPACE: bool;
+
+    /// The fewest items a `ListEnvar` accepts
+    const MIN_LEN: usize = 0;
+
+    /// The most items a `ListEnvar` accepts
+    const MAX_LEN: usize = usize::MAX;
+
+    /// A character making the next one literal, e.g. `\\` for `a\,b,c`
+    const ESCAPE: Option<char> = None;
+
+    /// A character enclosing items that contain the separator, e.g. `"` for `"a,b",c`
+    const QUOTE: Option<char> = None;
+
+    /// Splits the raw value into items, before filtering and trimming.
+    ///
+    /// Items are produced as the iterator advances, so counting or scanning
+    /// a long list does not allocate every item up front.
+    fn split(value: &str) -> impl Iterator<Item = Cow<'_, str>> {
+        SplitUnescaped {
+            rest: Some(value),
+            sep: Self::SEP,
+            escape: Self::ESCAPE,
+            quote: Self::QUOTE,
+        }
+    }
+}
+
+/// Splits on `sep` outside quotes, removing the quotes and escape characters.
+///
+/// Items without quotes or escapes are borrowed from the raw value.
+struct SplitUnescaped<'a> {
+    rest: Option<&'a str>,
+    sep: &'static str,
+    escape: Option<char>,
+    quote: Option<char>,
+}
+
+impl<'a> Iterator for SplitUnescaped<'a> {
+    type Item = Cow<'a, str>;
+
+    fn next(&mut self) -> Option<Cow<'a, str>> {
+        let value = self.rest?;
+        if self.escape.is_none() && self.quote.is_none() {
+            return Some(Cow::Borrowed(match value.split_once(self.sep) {
+                Some((item, rest)) => {
+                    self.rest = Some(rest);
+                    item
+                }
+                None => {
+                    self.rest = None;
+                    value
+                }
+            }));
+        }
+
+        // only allocated once a quote or escape character has to be removed
+        let mut item: Option<String> = None;
+        let mut quoted = false;
+        let mut rest = value;
+        let item_of = |item: Option<String>, end: usize| match item {
+            Some(item) => Cow::Owned(item),
+            None => Cow::Borrowed(&value[..end]),
+        };
+        while let Some(c) = rest.chars().next() {
+            let offset = value.len() - rest.len();
+            if !quoted && rest.starts_with(self.sep) {
+                self.rest = Some(&rest[self.sep.len()..]);
+                return Some(item_of(item, offset));
+            }
+            rest = &rest[c.len_utf8()..];
+            if Some(c) == self.escape {
+                // a trailing escape character is kept as-is
+                let literal = rest.chars().next().unwrap_or(c);
+                item.get_or_insert_with(|| value[..offset].to_string())
+                    .push(literal);
+                rest = rest.get(literal.len_utf8()..).unwrap_or_default();
+            } else if Some(c) == self.quote {
+                item.get_or_insert_with(|| value[..offset].to_string());
+                quoted = !quoted;
+            } else if let Some(item) = &mut item {
+                item.push(c);
+            }
+        }
+        self.rest = None;
+        Some(item_of(item, value.len()))
+    }
+}
+
+/// An item as written in a list: with the separator escaped, or quoted, if `C` allows
+fn escape_item<C: ListEnvarConfig>(item: String) -> String {
+    let special = |c: char| Some(c) == C::ESCAPE || Some(c) == C::QUOTE;
+    if !item.contains(C::SEP) && !item.contains(special) {
+        return item;
+    }
+    if let Some(escape) = C::ESCAPE {
+        let mut escaped = String::with_capacity(item.len());
+        let mut rest = item.as_str();
+        while let Some(c) = rest.chars().next() {
+            if rest.starts_with(C::SEP) {
+                escaped.push(escape);
+                escaped.push_str(C::SEP);
+                rest = &rest[C::SEP.len()..];
+                continue;
+            }
+            if special(c) {
+                escaped.push(escape);
+            }
+            escaped.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+        return escaped;
+    }
+    match C::QUOTE {
+        // without an escape character, a quote cannot appear inside quotes
+        Some(quote) if !item.contains(quote) => format!("{quote}{item}{quote}"),
+        _ => item,
+    }
+}
+
+/// `a,b,c`
+pub struct CommaList;
+
+impl ListEnvarConfig for CommaList {
+    const SEP: &'static str = ",";
+    const FILTER_EMPTY_STR: bool = true;
+    const FILTER_WHITESPACE: bool = true;
+}
+
+/// `a:b:c`
+pub struct ColonList;
+
+impl ListEnvarConfig for ColonList {
+    const SEP: &'static str = ":";
+    const FILTER_EMPTY_STR: bool = true;
+    const FILTER_WHITESPACE: bool = true;
+}
+
+/// `a;b;c`
+pub struct SemicolonList;
+
+impl ListEnvarConfig for SemicolonList {
+    const SEP: &'static str = ";";
+    const FILTER_EMPTY_STR: bool = true;
+    const FILTER_WHITESPACE: bool = true;
+}
+
+/// `a b c`, split on any run of whitespace (spaces, tabs, newlines)
+pub struct WhitespaceList;
+
+impl ListEnvarConfig for WhitespaceList {
+    const SEP: &'static str = " ";
+    const FILTER_EMPTY_STR: bool = true;
+    const FILTER_WHITESPACE: bool = true;
+
+    fn split(value: &str) -> impl Iterator<Item = Cow<'_, str>> {
+        value.split_whitespace().map(Cow::Borrowed)
+    }
+}
+
+/// One item per line; escaped `\n` sequences count as line breaks too,
+/// see `unescape_newlines`.
+pub struct NewlineList;
+
+impl ListEnvarConfig for NewlineList {
+    const SEP: &'static str = "\n";
+    const FILTER_EMPTY_STR: bool = true;
+    const FILTER_WHITESPACE: bool = true;
+
+    fn split(value: &str) -> impl Iterator<Item = Cow<'_, str>> {
+        let lines: Box<dyn Iterator<Item = Cow<'_, str>>> =
+            match crate::multiline::unescape_newlines(value) {
+                Cow::Borrowed(value) => Box::new(value.lines().map(Cow::Borrowed)),
+                Cow::Owned(value) => Box::new(
+                    value
+                        .lines()
+                        .map(|line| Cow::Owned(line.to_string()))
+                        .collect::<Vec<_>>()
+                        .into_iter(),
+                ),
+            };
+        lines
+    }
+}
+
+/// `PATH`-style lists, split like `std::env::split_paths`: on `;` on Windows
+/// (where items may be quoted) and `:` elsewhere.
+pub struct PathList;
+
+impl ListEnvarConfig for PathList {
+    const SEP: &'static str = if cfg!(windows) { ";" } else { ":" };
+    const FILTER_EMPTY_STR: bool = true;
+    const FILTER_WHITESPACE: bool = true;
+
+    fn split(value: &str) -> impl Iterator<Item = Cow<'_, str>> {
+        std::env::split_paths(value).map(|path| Cow::Owned(path.to_string_lossy().into_owned()))
+    }
+}
+
+/// `str::contains`, usable in constants
+const fn str_contains(haystack: &str, needle: &str) -> bool {
+    let (haystack, needle) = (haystack.as_bytes(), needle.as_bytes());
+    let mut start = 0;
+    while start + needle.len() <= haystack.len() {
+        let mut i = 0;
+        while i < needle.len() && haystack[start + i] == needle[i] {
+            i += 1;
+        }
+        if i == needle.len() {
+            return true;
+        }
+        start += 1;
+    }
+    false
+}
+
+/// The trimmed items of a raw list value, with empty ones filtered as `C` asks
+pub(crate) fn list_items<C: ListEnvarConfig>(value: &str) -> impl Iterator<Item = Cow<'_, str>> {
+    filter_items(C::split(value), C::FILTER_EMPTY_STR, C::FILTER_WHITESPACE)
+}
+
+/// Trims split items, dropping empty and whitespace-only ones if asked to
+pub(crate) fn filter_items<'a>(
+    items: impl Iterator<Item = Cow<'a, str>>,
+    filter_empty_str: bool,
+    filter_whitespace: bool,
+) -> impl Iterator<Item = Cow<'a, str>> {
+    items.filter_map(move |item| {
+        if filter_empty_str && item.is_empty() {
+            return None;
+        }
+        let trimmed = match item {
+            Cow::Borrowed(item) => Cow::Borrowed(item.trim()),
+            Cow::Owned(item) => Cow::Owned(item.trim().to_string()),
+        };
+        if filter_whitespace && trimmed.is_empty() {
+            return None;
+        }
+        Some(trimmed)
+    })
 }
 
 impl<T, C: ListEnvarConfig> ListEnvar<T, C> {
-    pub(crate) fn new(vec: Vec<T>) -> Self {
+    /// A list of the given items, e.g. for `EnvarDef::Default`
+    pub fn new(vec: Vec<T>) -> Self {
         Self {
             _marker: PhantomData,
             _vec: Arc::new(vec),
@@ -36,6 +292,44 @@ impl<T, C: ListEnvarConfig> ListEnvar<T, C> {
     }
 }
 
+impl<T, C: ListEnvarConfig> From<Vec<T>> for ListEnvar<T, C> {
+    fn from(vec: Vec<T>) -> Self {
+        Self::new(vec)
+    }
+}
+
+impl<T, C: ListEnvarConfig> FromIterator<T> for ListEnvar<T, C> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+impl<T: Clone, C> ListEnvar<T, C> {
+    /// Moves the items out, cloning them only if the list is shared with other clones
+    /// (e.g. the cached copy inside an `Envar`). `to_vec()` always clones.
+    pub fn into_vec(self) -> Vec<T> {
+        Arc::try_unwrap(self._vec).unwrap_or_else(|shared| (*shared).clone())
+    }
+}
+
+impl<T: Clone, C> IntoIterator for ListEnvar<T, C> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_vec().into_iter()
+    }
+}
+
+impl<'a, T, C> IntoIterator for &'a ListEnvar<T, C> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self._vec.iter()
+    }
+}
+
 impl<T, C: ListEnvarConfig> std::ops::Deref for ListEnvar<T, C> {
     type Target = Vec<T>;
 
@@ -44,6 +338,28 @@ impl<T, C: ListEnvarConfig> std::ops::Deref for ListEnvar<T, C> {
     }
 }
 
+impl<T: PartialEq, C> PartialEq for ListEnvar<T, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self._vec == other._vec
+    }
+}
+
+impl<T: Eq, C> Eq for ListEnvar<T, C> {}
+
+impl<T: PartialEq, C> PartialEq<Vec<T>> for ListEnvar<T, C> {
+    fn eq(&self, other: &Vec<T>) -> bool {
+        *self._vec == *other
+    }
+}
+
+/// Serialized as a sequence of the items
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, C> serde::Serialize for ListEnvar<T, C> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self._vec.iter())
+    }
+}
+
 impl<T: std::fmt::Debug, C: ListEnvarConfig> std::fmt::Debug for ListEnvar<T, C> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "ListEnvar {{ _vec: {:?} }}", self._vec)
@@ -61,3 +377,172 @@ impl<T: std::fmt::Display, C: ListEnvarConfig> std::fmt::Display for ListEnvar<T
         Ok(())
     }
 }
+
+/// A single-character separator given as a const generic, e.g. `ListEnvar<T, CharSep<','>>`
+pub struct CharSep<
+    const SEP: char,
+    const FILTER_EMPTY_STR: bool = true,
+    const FILTER_WHITESPACE: bool = true,
+>;
+
+impl<const SEP: char, const FILTER_EMPTY_STR: bool, const FILTER_WHITESPACE: bool>
+    CharSep<SEP, FILTER_EMPTY_STR, FILTER_WHITESPACE>
+{
+    const ENCODED: [u8; 4] = {
+        let mut buf = [0u8; 4];
+        SEP.encode_utf8(&mut buf);
+        buf
+    };
+}
+
+impl<const SEP: char, const FILTER_EMPTY_STR: bool, const FILTER_WHITESPACE: bool> ListEnvarConfig
+    for CharSep<SEP, FILTER_EMPTY_STR, FILTER_WHITESPACE>
+{
+    const SEP: &'static str = {
+        let encoded: &'static [u8; 4] = &Self::ENCODED;
+        let (bytes, _) = encoded.split_at(SEP.len_utf8());
+        match std::str::from_utf8(bytes) {
+            Ok(sep) => sep,
+            Err(_) => panic!("a char always encodes to valid UTF-8"),
+        }
+    };
+    const FILTER_EMPTY_STR: bool = FILTER_EMPTY_STR;
+    const FILTER_WHITESPACE: bool = FILTER_WHITESPACE;
+}
+
+/// A list kept as its raw string, whose items are parsed only when iterated.
+///
+/// For very large lists this avoids materializing every item up front;
+/// cloning only bumps a reference count. Parse errors surface per item,
+/// while `C::MIN_LEN`/`C::MAX_LEN` are checked when the value is read.
+pub struct LazyList<T, C> {
+    _marker: PhantomData<(fn() -> T, C)>,
+    _varname: Cow<'static, str>,
+    _raw: Arc<str>,
+}
+
+impl<T, C> Clone for LazyList<T, C> {
+    fn clone(&self) -> Self {
+        Self {
+            _marker: PhantomData,
+            _varname: self._varname.clone(),
+            _raw: self._raw.clone(),
+        }
+    }
+}
+
+impl<T, C: ListEnvarConfig> LazyList<T, C> {
+    pub(crate) fn new(varname: Cow<'static, str>, raw: &str) -> Self {
+        Self {
+            _marker: PhantomData,
+            _varname: varname,
+            _raw: Arc::from(raw),
+        }
+    }
+
+    pub fn raw(&self) -> &str {
+        &self._raw
+    }
+
+    /// Number of items, counted without parsing them
+    pub fn len(&self) -> usize {
+        list_items::<C>(&self._raw).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        list_items::<C>(&self._raw).next().is_none()
+    }
+
+    /// Parses the items one by one as the iterator advances.
+    pub fn iter_parse(&self) -> impl Iterator<Item = Result<T, EnvarError>> + '_
+    where
+        EnvarParser<T>: EnvarParse<T>,
+    {
+        list_items::<C>(&self._raw)
+            .enumerate()
+            .map(move |(index, item)| parse_item::<T>(self._varname.clone(), index, &item))
+    }
+
+    /// Parses every item into a `ListEnvar`, failing on the first bad item.
+    pub fn to_list(&self) -> Result<ListEnvar<T, C>, EnvarError>
+    where
+        EnvarParser<T>: EnvarParse<T>,
+    {
+        Ok(ListEnvar::new(
+            self.iter_parse().collect::<Result<Vec<T>, _>>()?,
+        ))
+    }
+}
+
+impl<T, C> std::fmt::Debug for LazyList<T, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LazyList {{ _raw: {:?} }}", self._raw)
+    }
+}
+
+impl<T, C> EnvarParse<ListEnvar<T, C>> for EnvarParser<ListEnvar<T, C>>
+where
+    C: ListEnvarConfig,
+    EnvarParser<T>: EnvarParse<T>,
+{
+    const LIST_SEP: Option<&'static str> = Some(C::SEP);
+
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<ListEnvar<T, C>, EnvarError> {
+        const {
+            if let Some(inner) = EnvarParser::<T>::LIST_SEP {
+                assert!(
+                    !str_contains(inner, C::SEP),
+                    "the separator of a nested list must not contain the outer list's separator"
+                );
+            }
+        }
+        let mut list: Vec<T> = vec![];
+
+        for (index, item) in list_items::<C>(value).enumerate() {
+            list.push(parse_item::<T>(varname.clone(), index, &item)?);
+        }
+
+        if list.len() < C::MIN_LEN || list.len() > C::MAX_LEN {
+            return Err(EnvarError::ListLength {
+                varname,
+                len: list.len(),
+                min_len: C::MIN_LEN,
+                max_len: C::MAX_LEN,
+            });
+        }
+        Ok(ListEnvar::new(list))
+    }
+
+    fn render(value: &ListEnvar<T, C>) -> Option<String> {
+        let items: Option<Vec<String>> = value
+            .iter()
+            .map(|item| EnvarParser::<T>::render(item).map(escape_item::<C>))
+            .collect();
+        Some(items?.join(C::SEP))
+    }
+}
+
+impl<T, C> EnvarParse<LazyList<T, C>> for EnvarParser<LazyList<T, C>>
+where
+    C: ListEnvarConfig,
+    EnvarParser<T>: EnvarParse<T>,
+{
+    /// Only keeps the raw string, after counting its items against `C::MIN_LEN`
+    /// and `C::MAX_LEN`; items are parsed by `LazyList::iter_parse`
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<LazyList<T, C>, EnvarError> {
+        let len = list_items::<C>(value).count();
+        if len < C::MIN_LEN || len > C::MAX_LEN {
+            return Err(EnvarError::ListLength {
+                varname,
+                len,
+                min_len: C::MIN_LEN,
+                max_len: C::MAX_LEN,
+            });
+        }
+        Ok(LazyList::new(varname, value))
+    }
+
+    fn render(value: &LazyList<T, C>) -> Option<String> {
+        Some(value.raw().to_string())
+    }
+}
@@ -0,0 +1,123 @@
+use crate::core::{EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::ErrorReason;
+use std::borrow::Cow;
+
+/// Matches `value` case-insensitively against the level names, suggesting the
+/// closest one on a typo.
+fn parse_level<L: Copy>(
+    varname: Cow<'static, str>,
+    typename: &'static str,
+    value: &str,
+    levels: &[(&'static str, L)],
+) -> Result<L, EnvarError> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(EnvarError::TryDefault(varname));
+    }
+    if let Some((_, level)) = levels
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(value))
+    {
+        return Ok(*level);
+    }
+
+    let names: Vec<&'static str> = levels.iter().map(|(name, _)| *name).collect();
+    let mut reason = format!("expected one of {}", names.join(", "));
+    if let Some(suggestion) = crate::suggest::closest(value, &names) {
+        reason.push_str(&format!(" (did you mean {}?)", suggestion));
+    }
+    Err(EnvarError::ParseError {
+        varname,
+        typename,
+        value: value.to_string(),
+        reason: ErrorReason::new(move || reason),
+    })
+}
+
+#[cfg(feature = "log")]
+mod log_impls {
+    use super::*;
+    use ::log::{Level, LevelFilter};
+
+    const LEVELS: &[(&str, Level)] = &[
+        ("error", Level::Error),
+        ("warn", Level::Warn),
+        ("info", Level::Info),
+        ("debug", Level::Debug),
+        ("trace", Level::Trace),
+    ];
+
+    const LEVEL_FILTERS: &[(&str, LevelFilter)] = &[
+        ("off", LevelFilter::Off),
+        ("error", LevelFilter::Error),
+        ("warn", LevelFilter::Warn),
+        ("info", LevelFilter::Info),
+        ("debug", LevelFilter::Debug),
+        ("trace", LevelFilter::Trace),
+    ];
+
+    impl EnvarParse<Level> for EnvarParser<Level> {
+        fn parse(varname: Cow<'static, str>, value: &str) -> Result<Level, EnvarError> {
+            parse_level(varname, "Level", value, LEVELS)
+        }
+
+        fn render(value: &Level) -> Option<String> {
+            Some(value.as_str().to_lowercase())
+        }
+    }
+
+    impl EnvarParse<LevelFilter> for EnvarParser<LevelFilter> {
+        fn parse(varname: Cow<'static, str>, value: &str) -> Result<LevelFilter, EnvarError> {
+            parse_level(varname, "LevelFilter", value, LEVEL_FILTERS)
+        }
+
+        fn render(value: &LevelFilter) -> Option<String> {
+            Some(value.as_str().to_lowercase())
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+mod tracing_impls {
+    use super::*;
+    use ::tracing::level_filters::LevelFilter;
+    use ::tracing::Level;
+
+    const LEVELS: &[(&str, Level)] = &[
+        ("error", Level::ERROR),
+        ("warn", Level::WARN),
+        ("info", Level::INFO),
+        ("debug", Level::DEBUG),
+        ("trace", Level::TRACE),
+    ];
+
+    const LEVEL_FILTERS: &[(&str, LevelFilter)] = &[
+        ("off", LevelFilter::OFF),
+        ("error", LevelFilter::ERROR),
+        ("warn", LevelFilter::WARN),
+        ("info", LevelFilter::INFO),
+        ("debug", LevelFilter::DEBUG),
+        ("trace", LevelFilter::TRACE),
+    ];
+
+    impl EnvarParse<Level> for EnvarParser<Level> {
+        fn parse(varname: Cow<'static, str>, value: &str) -> Result<Level, EnvarError> {
+            parse_level(varname, "Level", value, LEVELS)
+        }
+
+        fn render(value: &Level) -> Option<String> {
+            Some(value.as_str().to_lowercase())
+        }
+    }
+
+    impl EnvarParse<LevelFilter> for EnvarParser<LevelFilter> {
+        fn parse(varname: Cow<'static, str>, value: &str) -> Result<LevelFilter, EnvarError> {
+            parse_level(varname, "LevelFilter", value, LEVEL_FILTERS)
+        }
+
+        fn render(value: &LevelFilter) -> Option<String> {
+            Some(value.to_string().to_lowercase())
+        }
+    }
+}
@@ -0,0 +1,74 @@
+/// Declares one or more `Envar` statics without the default-factory closures.
+///
+/// ```
+/// typed_env::envar! {
+///     /// The port to listen on
+///     pub static PORT: u16 = "APP_PORT", default 8080;
+///     static DATABASE_URL: String = "DATABASE_URL";
+///     static WORKERS: usize = "WORKERS", default 4, on_startup;
+/// }
+/// ```
+///
+/// Variables without a `default` are required (`EnvarDef::Unset`). Variables
+/// load on demand unless `on_startup` is given.
+#[macro_export]
+macro_rules! envar {
+    () => {};
+    (
+        $(#[$attr:meta])*
+        $vis:vis static $name:ident : $ty:ty = $varname:literal, default $default:expr, on_startup;
+        $($rest:tt)*
+    ) => {
+        $(#[$attr])*
+        $vis static $name: $crate::Envar<$ty> =
+            $crate::Envar::on_startup($varname, || $crate::EnvarDef::Default($default));
+        $crate::envar!($($rest)*);
+    };
+    (
+        $(#[$attr:meta])*
+        $vis:vis static $name:ident : $ty:ty = $varname:literal, default $default:expr;
+        $($rest:tt)*
+    ) => {
+        $(#[$attr])*
+        $vis static $name: $crate::Envar<$ty> =
+            $crate::Envar::on_demand($varname, || $crate::EnvarDef::Default($default));
+        $crate::envar!($($rest)*);
+    };
+    (
+        $(#[$attr:meta])*
+        $vis:vis static $name:ident : $ty:ty = $varname:literal, on_startup;
+        $($rest:tt)*
+    ) => {
+        $(#[$attr])*
+        $vis static $name: $crate::Envar<$ty> =
+            $crate::Envar::on_startup($varname, || $crate::EnvarDef::Unset);
+        $crate::envar!($($rest)*);
+    };
+    (
+        $(#[$attr:meta])*
+        $vis:vis static $name:ident : $ty:ty = $varname:literal;
+        $($rest:tt)*
+    ) => {
+        $(#[$attr])*
+        $vis static $name: $crate::Envar<$ty> =
+            $crate::Envar::on_demand($varname, || $crate::EnvarDef::Unset);
+        $crate::envar!($($rest)*);
+    };
+}
+
+/// Keeps the items the derives generate for the `docgen` feature only when it is enabled.
+#[doc(hidden)]
+#[cfg(feature = "docgen")]
+#[macro_export]
+macro_rules! __with_docgen {
+    ($($item:item)*) => {
+        $($item)*
+    };
+}
+
+#[doc(hidden)]
+#[cfg(not(feature = "docgen"))]
+#[macro_export]
+macro_rules! __with_docgen {
+    ($($item:item)*) => {};
+}
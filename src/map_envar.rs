@@ -0,0 +1,155 @@
+use crate::core::{parse_item, EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::ErrorReason;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// `key=value` pairs parsed into a `HashMap`, e.g. `LABELS="env=prod,team=core"`.
+pub struct MapEnvar<K, V, C> {
+    _marker: PhantomData<C>,
+    _map: Arc<HashMap<K, V>>,
+}
+
+impl<K, V, C> Clone for MapEnvar<K, V, C> {
+    fn clone(&self) -> Self {
+        Self {
+            _marker: PhantomData,
+            _map: self._map.clone(),
+        }
+    }
+}
+
+/// What to do when a key appears more than once
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Reject the value with `EnvarError::ParseError`
+    Error,
+    /// Keep the first value given for the key
+    First,
+    /// Keep the last value given for the key
+    Last,
+}
+
+/// Configuration for the `MapEnvar` type
+pub trait MapEnvarConfig {
+    /// The separator between pairs
+    const PAIR_SEP: &'static str;
+
+    /// The separator between a key and its value; only the first one in a pair counts
+    const KV_SEP: &'static str;
+
+    const DUPLICATES: DuplicateKeys;
+}
+
+/// `a=1,b=2`, rejecting duplicate keys
+pub struct CommaMap;
+
+impl MapEnvarConfig for CommaMap {
+    const PAIR_SEP: &'static str = ",";
+    const KV_SEP: &'static str = "=";
+    const DUPLICATES: DuplicateKeys = DuplicateKeys::Error;
+}
+
+/// `a=1;b=2`, rejecting duplicate keys
+pub struct SemicolonMap;
+
+impl MapEnvarConfig for SemicolonMap {
+    const PAIR_SEP: &'static str = ";";
+    const KV_SEP: &'static str = "=";
+    const DUPLICATES: DuplicateKeys = DuplicateKeys::Error;
+}
+
+/// `a:1,b:2`, e.g. for headers, rejecting duplicate keys
+pub struct ColonPairMap;
+
+impl MapEnvarConfig for ColonPairMap {
+    const PAIR_SEP: &'static str = ",";
+    const KV_SEP: &'static str = ":";
+    const DUPLICATES: DuplicateKeys = DuplicateKeys::Error;
+}
+
+impl<K, V, C: MapEnvarConfig> MapEnvar<K, V, C> {
+    pub(crate) fn new(map: HashMap<K, V>) -> Self {
+        Self {
+            _marker: PhantomData,
+            _map: Arc::new(map),
+        }
+    }
+}
+
+impl<K, V, C: MapEnvarConfig> std::ops::Deref for MapEnvar<K, V, C> {
+    type Target = HashMap<K, V>;
+
+    fn deref(&self) -> &Self::Target {
+        &self._map
+    }
+}
+
+impl<K: std::fmt::Debug, V: std::fmt::Debug, C: MapEnvarConfig> std::fmt::Debug
+    for MapEnvar<K, V, C>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MapEnvar {{ _map: {:?} }}", self._map)
+    }
+}
+
+impl<K, V, C> EnvarParse<MapEnvar<K, V, C>> for EnvarParser<MapEnvar<K, V, C>>
+where
+    K: Eq + Hash,
+    C: MapEnvarConfig,
+    EnvarParser<K>: EnvarParse<K>,
+    EnvarParser<V>: EnvarParse<V>,
+{
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<MapEnvar<K, V, C>, EnvarError> {
+        let invalid = |reason: String| EnvarError::ParseError {
+            varname: varname.clone(),
+            typename: "MapEnvar",
+            value: value.to_string(),
+            reason: ErrorReason::new(move || reason),
+        };
+        let mut map = HashMap::new();
+        let pairs = value.split(C::PAIR_SEP).map(str::trim);
+        for (index, pair) in pairs.filter(|pair| !pair.is_empty()).enumerate() {
+            let Some((key, raw)) = pair.split_once(C::KV_SEP) else {
+                return Err(invalid(format!(
+                    "expected key{}value, found {:?}",
+                    C::KV_SEP,
+                    pair
+                )));
+            };
+            let key_str = key.trim();
+            let key = parse_item::<K>(varname.clone(), index, key_str)?;
+            let parsed = parse_item::<V>(varname.clone(), index, raw.trim())?;
+            match (map.contains_key(&key), C::DUPLICATES) {
+                (true, DuplicateKeys::Error) => {
+                    return Err(invalid(format!("duplicate key {:?}", key_str)));
+                }
+                (true, DuplicateKeys::First) => {}
+                (false, _) | (true, DuplicateKeys::Last) => {
+                    map.insert(key, parsed);
+                }
+            }
+        }
+        Ok(MapEnvar::new(map))
+    }
+
+    /// Pairs are sorted, so the rendering does not depend on the map's iteration order
+    fn render(value: &MapEnvar<K, V, C>) -> Option<String> {
+        let mut pairs = value
+            .iter()
+            .map(|(key, value)| {
+                Some(format!(
+                    "{}{}{}",
+                    EnvarParser::<K>::render(key)?,
+                    C::KV_SEP,
+                    EnvarParser::<V>::render(value)?
+                ))
+            })
+            .collect::<Option<Vec<String>>>()?;
+        pairs.sort();
+        Some(pairs.join(C::PAIR_SEP))
+    }
+}
@@ -0,0 +1,181 @@
+use crate::error::EnvarError;
+use std::fmt;
+use std::sync::{PoisonError, RwLock};
+
+/// Renders `EnvarError` messages, `EnvarErrors` summaries and the reports of
+/// `Envar::expect_value`; install one with `set_message_formatter` to translate
+/// or restyle them. Only `format` is required, the others default to English.
+pub trait MessageFormatter: Send + Sync {
+    fn format(&self, error: &EnvarError, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+
+    /// Renders every error found while validating a set of variables,
+    /// each one rendered with `format`
+    fn format_errors(&self, errors: &[EnvarError], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} environment variable(s) failed validation:",
+            errors.len()
+        )?;
+        for error in errors {
+            write!(f, "  - ")?;
+            self.format(error, f)?;
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+
+    /// Renders the report printed when `expect_value` or `value_or_exit` exits
+    fn format_report(&self, report: &FailureReport<'_>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "error: ")?;
+        self.format(report.error, f)?;
+        writeln!(f)?;
+        writeln!(f, "  expected: {}", report.expected)?;
+        if let Some(description) = report.description {
+            writeln!(f, "  description: {}", description)?;
+        }
+        if let Some(unit) = report.unit {
+            writeln!(f, "  unit: {}", unit)?;
+        }
+        if let Some(example) = report.example {
+            writeln!(f, "  example: {}={}", report.varname, example)?;
+        }
+        Ok(())
+    }
+}
+
+/// What `expect_value` reports about a variable that failed to resolve:
+/// the error, along with the variable's type and metadata.
+#[derive(Debug)]
+pub struct FailureReport<'a> {
+    pub error: &'a EnvarError,
+    pub varname: &'a str,
+    /// The short name of the expected type, e.g. `u16`
+    pub expected: &'a str,
+    pub description: Option<&'a str>,
+    pub unit: Option<&'a str>,
+    pub example: Option<&'a str>,
+}
+
+impl fmt::Display for FailureReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        with_formatter(|formatter| formatter.format_report(self, f))
+    }
+}
+
+/// The built-in English messages.
+pub struct DefaultMessageFormatter;
+
+impl MessageFormatter for DefaultMessageFormatter {
+    fn format(&self, error: &EnvarError, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match error {
+            EnvarError::ParseError {
+                varname,
+                typename,
+                value,
+                ..
+            } => write!(
+                f,
+                "Cannot parse environment variable {varname} (value = {value:?}) as {typename}"
+            ),
+            EnvarError::TooLong {
+                varname,
+                len,
+                max_len,
+            } => write!(
+                f,
+                "Environment variable {varname} is {len} bytes long, exceeding the limit of {max_len}"
+            ),
+            EnvarError::ListLength {
+                varname,
+                len,
+                min_len,
+                max_len,
+            } => {
+                write!(f, "Environment variable {varname} has {len} item(s), expected ")?;
+                match (*min_len, *max_len) {
+                    (min_len, usize::MAX) => write!(f, "at least {min_len}"),
+                    (0, max_len) => write!(f, "at most {max_len}"),
+                    (min_len, max_len) => write!(f, "between {min_len} and {max_len}"),
+                }
+            }
+            EnvarError::NotSet(varname) => write!(f, "Environment variable {varname} is not set"),
+            EnvarError::NotSetAliases { varname, aliases } => write!(
+                f,
+                "None of the environment variables {varname}, {} is set",
+                aliases.join(", ")
+            ),
+            EnvarError::OutOfRange {
+                varname,
+                value,
+                range,
+            } => write!(f, "{varname}={value} is out of range {range}"),
+            EnvarError::NotAllowed {
+                varname,
+                value,
+                allowed,
+                suggestion,
+            } => {
+                write!(
+                    f,
+                    "{varname}={value} is not one of {}",
+                    allowed.join(", ")
+                )?;
+                match suggestion {
+                    Some(suggestion) => write!(f, " (did you mean {suggestion}?)"),
+                    None => Ok(()),
+                }
+            }
+            EnvarError::NotUnicode(varname) => {
+                write!(f, "Environment variable {varname} is not valid UTF-8")
+            }
+            EnvarError::FileError {
+                varname,
+                path,
+                reason,
+            } => write!(
+                f,
+                "Cannot read file {path:?} named by environment variable {varname}: {reason}"
+            ),
+            EnvarError::SourceError { varname, reason } => write!(
+                f,
+                "Cannot read environment variable {varname} from its source: {reason}"
+            ),
+            EnvarError::TryDefault(varname) => write!(
+                f,
+                "Environment variable {varname} is not set and default factory returned None"
+            ),
+        }
+    }
+}
+
+static FORMATTER: RwLock<Option<Box<dyn MessageFormatter>>> = RwLock::new(None);
+
+/// Replaces the formatter used by `EnvarError`'s `Display` implementation.
+pub fn set_message_formatter(formatter: impl MessageFormatter + 'static) {
+    *FORMATTER.write().unwrap_or_else(PoisonError::into_inner) = Some(Box::new(formatter));
+}
+
+/// Restores the built-in English messages.
+pub fn reset_message_formatter() {
+    *FORMATTER.write().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// Runs `f` with the installed formatter
+fn with_formatter(f: impl FnOnce(&dyn MessageFormatter) -> fmt::Result) -> fmt::Result {
+    match FORMATTER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_deref()
+    {
+        Some(formatter) => f(formatter),
+        None => f(&DefaultMessageFormatter),
+    }
+}
+
+pub(crate) fn format_error(error: &EnvarError, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    with_formatter(|formatter| formatter.format(error, f))
+}
+
+pub(crate) fn format_errors(errors: &[EnvarError], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    with_formatter(|formatter| formatter.format_errors(errors, f))
+}
@@ -0,0 +1,79 @@
+use crate::core::{EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use std::borrow::Cow;
+
+/// Replaces escaped newlines (the two characters `\n`, optionally preceded by `\r`)
+/// with real ones, as left behind by values templated from YAML block scalars.
+pub fn unescape_newlines(value: &str) -> Cow<'_, str> {
+    if !value.contains("\\n") {
+        return Cow::Borrowed(value);
+    }
+    Cow::Owned(value.replace("\\r\\n", "\n").replace("\\n", "\n"))
+}
+
+/// Escapes backslashes and newlines, so `value` fits on one line of a file
+pub(crate) fn escape_line(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+/// Reverses `escape_line`
+pub(crate) fn unescape_line(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                out.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                out.push('\\');
+                chars.next();
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// A multi-line string such as a PEM certificate chain, accepting either
+/// real newlines or escaped `\n` sequences.
+///
+/// Unlike `String`, surrounding whitespace is kept.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Multiline(pub String);
+
+impl Multiline {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn lines(&self) -> std::str::Lines<'_> {
+        self.0.lines()
+    }
+}
+
+impl std::ops::Deref for Multiline {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for Multiline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl EnvarParse<Multiline> for EnvarParser<Multiline> {
+    fn parse(_varname: Cow<'static, str>, value: &str) -> Result<Multiline, EnvarError> {
+        Ok(Multiline(unescape_newlines(value).into_owned()))
+    }
+
+    /// Rendered with escaped newlines, so the value fits on a single line of a manifest
+    fn render(value: &Multiline) -> Option<String> {
+        Some(value.0.replace('\n', "\\n"))
+    }
+}
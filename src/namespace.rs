@@ -0,0 +1,72 @@
+use crate::core::{Envar, EnvarDef, EnvarParse, EnvarParser};
+use std::sync::{Mutex, PoisonError};
+
+/// A name prefix shared by an application's variables, e.g. `MYAPP_`.
+///
+/// Variables declared through `var` get the prefix applied, and `unknown`
+/// lists prefixed variables in the environment that none of them read,
+/// which catches typos such as `MYAPP_PROT`.
+pub struct EnvNamespace {
+    _prefix: &'static str,
+    _declared: Mutex<Vec<String>>,
+}
+
+impl EnvNamespace {
+    pub const fn new(prefix: &'static str) -> Self {
+        Self {
+            _prefix: prefix,
+            _declared: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn prefix(&self) -> &'static str {
+        self._prefix
+    }
+
+    /// The full variable name for `name`, recording it as declared.
+    pub fn name(&self, name: &str) -> String {
+        let full_name = format!("{}{}", self._prefix, name);
+        let mut declared = self
+            ._declared
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if !declared.contains(&full_name) {
+            declared.push(full_name.clone());
+        }
+        full_name
+    }
+
+    /// An on-demand variable named `name` with the prefix applied.
+    pub fn var<T>(
+        &self,
+        name: &str,
+        default: EnvarDef<T>,
+    ) -> Envar<T, impl Fn() -> EnvarDef<T> + Send + Sync>
+    where
+        T: Clone + Send + Sync + 'static,
+        EnvarParser<T>: EnvarParse<T>,
+    {
+        Envar::on_demand_named(self.name(name), move || match &default {
+            EnvarDef::Default(default) => EnvarDef::Default(default.clone()),
+            EnvarDef::Unset => EnvarDef::Unset,
+        })
+    }
+
+    /// Names in the global source (or override layers) carrying the prefix
+    /// that were not declared through this namespace, sorted.
+    pub fn unknown(&self) -> Vec<String> {
+        let declared = self
+            ._declared
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut unknown: Vec<String> = crate::source::with_global(|source| source.vars())
+            .into_iter()
+            .filter_map(|(name, _)| name.into_string().ok())
+            .chain(crate::layers::vars().into_keys())
+            .filter(|name| name.starts_with(self._prefix) && !declared.contains(name))
+            .collect();
+        unknown.sort();
+        unknown.dedup();
+        unknown
+    }
+}
@@ -0,0 +1,164 @@
+use crate::core::{EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::ErrorReason;
+use std::borrow::Cow;
+use std::net::{IpAddr, SocketAddr, SocketAddrV4, SocketAddrV6, ToSocketAddrs};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// A `host:port` value resolved through `ToSocketAddrs` when parsed.
+///
+/// With `TTL_SECS > 0`, `addrs()` resolves again once the previous result is
+/// older than the TTL, keeping the previous addresses if that fails;
+/// with the default `0` the addresses are resolved only once.
+pub struct ResolvedAddrs<const TTL_SECS: u64 = 0> {
+    _target: Arc<str>,
+    /// (resolved at, addresses)
+    _cache: Arc<Mutex<(Instant, Vec<SocketAddr>)>>,
+}
+
+impl<const TTL_SECS: u64> Clone for ResolvedAddrs<TTL_SECS> {
+    fn clone(&self) -> Self {
+        Self {
+            _target: self._target.clone(),
+            _cache: self._cache.clone(),
+        }
+    }
+}
+
+fn resolve(target: &str) -> std::io::Result<Vec<SocketAddr>> {
+    let addrs: Vec<SocketAddr> = target.to_socket_addrs()?.collect();
+    if addrs.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "no addresses found",
+        ));
+    }
+    Ok(addrs)
+}
+
+impl<const TTL_SECS: u64> ResolvedAddrs<TTL_SECS> {
+    /// The `host:port` as configured
+    pub fn target(&self) -> &str {
+        &self._target
+    }
+
+    /// The resolved addresses, resolved again if the TTL has expired.
+    pub fn addrs(&self) -> Vec<SocketAddr> {
+        let mut cache = self._cache.lock().unwrap_or_else(PoisonError::into_inner);
+        if TTL_SECS > 0 && cache.0.elapsed() >= Duration::from_secs(TTL_SECS) {
+            if let Ok(addrs) = resolve(&self._target) {
+                cache.1 = addrs;
+            }
+            cache.0 = Instant::now();
+        }
+        cache.1.clone()
+    }
+
+    /// The first resolved address
+    pub fn first(&self) -> SocketAddr {
+        self.addrs()[0]
+    }
+}
+
+impl<const TTL_SECS: u64> std::fmt::Debug for ResolvedAddrs<TTL_SECS> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cache = self._cache.lock().unwrap_or_else(PoisonError::into_inner);
+        write!(
+            f,
+            "ResolvedAddrs {{ _target: {:?}, addrs: {:?} }}",
+            self._target, cache.1
+        )
+    }
+}
+
+impl<const TTL_SECS: u64> EnvarParse<ResolvedAddrs<TTL_SECS>>
+    for EnvarParser<ResolvedAddrs<TTL_SECS>>
+{
+    const DETERMINISTIC: bool = false;
+
+    fn parse(
+        varname: Cow<'static, str>,
+        value: &str,
+    ) -> Result<ResolvedAddrs<TTL_SECS>, EnvarError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(EnvarError::TryDefault(varname));
+        }
+
+        match resolve(value) {
+            Ok(addrs) => Ok(ResolvedAddrs {
+                _target: Arc::from(value),
+                _cache: Arc::new(Mutex::new((Instant::now(), addrs))),
+            }),
+            Err(e) => Err(EnvarError::ParseError {
+                varname,
+                typename: "ResolvedAddrs",
+                value: value.to_string(),
+                reason: ErrorReason::new(move || format!("cannot resolve host:port: {}", e)),
+            }),
+        }
+    }
+
+    fn render(value: &ResolvedAddrs<TTL_SECS>) -> Option<String> {
+        Some(value.target().to_string())
+    }
+}
+
+/// Explains why `value` is not an `ip:port`, telling a missing port apart from a bad IP.
+fn socket_addr_reason(value: &str, fallback: impl std::fmt::Display) -> String {
+    let unbracketed = |host: &str| {
+        host.strip_prefix('[')
+            .and_then(|host| host.strip_suffix(']'))
+            .unwrap_or(host)
+            .to_string()
+    };
+    if unbracketed(value).parse::<IpAddr>().is_ok() {
+        return "missing port, expected ip:port".to_string();
+    }
+    let Some((host, port)) = value.rsplit_once(':') else {
+        return format!(
+            "missing port, expected ip:port (invalid IP address {:?})",
+            value
+        );
+    };
+    let host = unbracketed(host);
+    if host.parse::<IpAddr>().is_err() {
+        return format!("invalid IP address {:?}", host);
+    }
+    if port.parse::<u16>().is_err() {
+        return format!("invalid port {:?}", port);
+    }
+    fallback.to_string()
+}
+
+macro_rules! impl_socket_addr {
+    ($($t:ident),*) => {
+        $(
+        impl EnvarParse<$t> for EnvarParser<$t> {
+            fn parse(varname: Cow<'static, str>, value: &str) -> Result<$t, EnvarError> {
+                let value = value.trim();
+                if value.is_empty() {
+                    return Err(EnvarError::TryDefault(varname));
+                }
+
+                value.parse::<$t>().map_err(|e| {
+                    let reason = socket_addr_reason(value, e);
+                    EnvarError::ParseError {
+                        varname,
+                        typename: stringify!($t),
+                        value: value.to_string(),
+                        reason: ErrorReason::new(move || reason),
+                    }
+                })
+            }
+
+            fn render(value: &$t) -> Option<String> {
+                Some(value.to_string())
+            }
+        }
+        )*
+    };
+}
+
+impl_socket_addr!(SocketAddr, SocketAddrV4, SocketAddrV6);
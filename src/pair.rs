@@ -0,0 +1,119 @@
+use crate::core::{parse_part, EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::ErrorReason;
+use std::borrow::Cow;
+use std::marker::PhantomData;
+
+/// Configuration for the `Pair` type
+pub trait PairConfig {
+    /// The separator between the two parts; only the first one counts, so the
+    /// second part may contain it
+    const SEP: &'static str;
+}
+
+/// `100:60`
+pub struct ColonPair;
+
+impl PairConfig for ColonPair {
+    const SEP: &'static str = ":";
+}
+
+/// `100,60`
+pub struct CommaPair;
+
+impl PairConfig for CommaPair {
+    const SEP: &'static str = ",";
+}
+
+/// `1920x1080`
+pub struct XPair;
+
+impl PairConfig for XPair {
+    const SEP: &'static str = "x";
+}
+
+/// Two values separated by `C::SEP`, e.g. `RATE_LIMIT="100:60s"` as a
+/// `Pair<u32, Duration>`. Each part is trimmed and parsed like `A` and `B`.
+pub struct Pair<A, B, C = ColonPair> {
+    pub first: A,
+    pub second: B,
+    _marker: PhantomData<C>,
+}
+
+impl<A, B, C> Pair<A, B, C> {
+    pub const fn new(first: A, second: B) -> Self {
+        Self {
+            first,
+            second,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn into_tuple(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A, B, C> From<(A, B)> for Pair<A, B, C> {
+    fn from((first, second): (A, B)) -> Self {
+        Self::new(first, second)
+    }
+}
+
+impl<A: Clone, B: Clone, C> Clone for Pair<A, B, C> {
+    fn clone(&self) -> Self {
+        Self::new(self.first.clone(), self.second.clone())
+    }
+}
+
+impl<A: Copy, B: Copy, C> Copy for Pair<A, B, C> {}
+
+impl<A: PartialEq, B: PartialEq, C> PartialEq for Pair<A, B, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.first == other.first && self.second == other.second
+    }
+}
+
+impl<A: Eq, B: Eq, C> Eq for Pair<A, B, C> {}
+
+impl<A: std::fmt::Debug, B: std::fmt::Debug, C> std::fmt::Debug for Pair<A, B, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Pair({:?}, {:?})", self.first, self.second)
+    }
+}
+
+impl<A, B, C> EnvarParse<Pair<A, B, C>> for EnvarParser<Pair<A, B, C>>
+where
+    C: PairConfig,
+    EnvarParser<A>: EnvarParse<A>,
+    EnvarParser<B>: EnvarParse<B>,
+{
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<Pair<A, B, C>, EnvarError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(EnvarError::TryDefault(varname));
+        }
+        let Some((first, second)) = value.split_once(C::SEP) else {
+            return Err(EnvarError::ParseError {
+                varname,
+                typename: "Pair",
+                value: value.to_string(),
+                reason: ErrorReason::new(|| {
+                    format!("expected two parts separated by {:?}", C::SEP)
+                }),
+            });
+        };
+        let first = parse_part::<A>(varname.clone(), "first part", first.trim())?;
+        let second = parse_part::<B>(varname, "second part", second.trim())?;
+        Ok(Pair::new(first, second))
+    }
+
+    fn render(value: &Pair<A, B, C>) -> Option<String> {
+        Some(format!(
+            "{}{}{}",
+            EnvarParser::<A>::render(&value.first)?,
+            C::SEP,
+            EnvarParser::<B>::render(&value.second)?
+        ))
+    }
+}
@@ -0,0 +1,85 @@
+use crate::core::{Envar, EnvarDef, EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use std::borrow::Cow;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+impl EnvarParse<PathBuf> for EnvarParser<PathBuf> {
+    const ACCEPTS_NON_UNICODE: bool = true;
+
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<PathBuf, EnvarError> {
+        Self::parse_os(varname, value.as_ref())
+    }
+
+    fn parse_os(varname: Cow<'static, str>, value: &OsStr) -> Result<PathBuf, EnvarError> {
+        if value.is_empty() {
+            return Err(EnvarError::TryDefault(varname));
+        }
+        Ok(PathBuf::from(value))
+    }
+
+    fn render(value: &PathBuf) -> Option<String> {
+        value.to_str().map(str::to_string)
+    }
+}
+
+fn exists(path: &Path) -> Result<(), String> {
+    match path.try_exists() {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!("{} does not exist", path.display())),
+        Err(e) => Err(format!("cannot access {}: {}", path.display(), e)),
+    }
+}
+
+fn is_dir(path: &Path) -> Result<(), String> {
+    exists(path)?;
+    if path.is_dir() {
+        Ok(())
+    } else {
+        Err(format!("{} is not a directory", path.display()))
+    }
+}
+
+fn is_file(path: &Path) -> Result<(), String> {
+    exists(path)?;
+    if path.is_file() {
+        Ok(())
+    } else {
+        Err(format!("{} is not a file", path.display()))
+    }
+}
+
+fn is_readable(path: &Path) -> Result<(), String> {
+    let readable = if path.is_dir() {
+        std::fs::read_dir(path).map(drop)
+    } else {
+        std::fs::File::open(path).map(drop)
+    };
+    readable.map_err(|e| format!("{} is not readable: {}", path.display(), e))
+}
+
+impl<F> Envar<PathBuf, F>
+where
+    F: Fn() -> EnvarDef<PathBuf>,
+{
+    /// Rejects paths that do not exist when the value is parsed (cached values are
+    /// not checked again).
+    pub const fn must_exist(self) -> Self {
+        self.with_validator(|path| exists(path))
+    }
+
+    /// Rejects paths that are not existing directories.
+    pub const fn must_be_dir(self) -> Self {
+        self.with_validator(|path| is_dir(path))
+    }
+
+    /// Rejects paths that are not existing regular files (or links to one).
+    pub const fn must_be_file(self) -> Self {
+        self.with_validator(|path| is_file(path))
+    }
+
+    /// Rejects paths that cannot be opened for reading (or, for directories, listed).
+    pub const fn must_be_readable(self) -> Self {
+        self.with_validator(|path| is_readable(path))
+    }
+}
@@ -0,0 +1,87 @@
+use crate::core::{EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Every environment variable sharing a prefix, keyed by the name with the prefix stripped.
+pub struct PrefixMap<T> {
+    _prefix: Cow<'static, str>,
+    _map: Arc<BTreeMap<String, T>>,
+}
+
+impl<T> Clone for PrefixMap<T> {
+    fn clone(&self) -> Self {
+        Self {
+            _prefix: self._prefix.clone(),
+            _map: self._map.clone(),
+        }
+    }
+}
+
+impl<T> PrefixMap<T> {
+    pub fn prefix(&self) -> &str {
+        &self._prefix
+    }
+
+    /// The full environment variable name for a key of this map.
+    pub fn full_name(&self, key: &str) -> String {
+        format!("{}{}", self._prefix, key)
+    }
+}
+
+impl<T> std::ops::Deref for PrefixMap<T> {
+    type Target = BTreeMap<String, T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self._map
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for PrefixMap<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "PrefixMap {{ _prefix: {:?}, _map: {:?} }}",
+            self._prefix, self._map
+        )
+    }
+}
+
+/// Collects and parses every environment variable whose name starts with `prefix`.
+///
+/// Variables with non-UTF-8 names or values are skipped, as are values
+/// whose parser asks for a default (e.g. empty values for `Option<T>`).
+pub fn collect_prefix<T>(prefix: impl Into<Cow<'static, str>>) -> Result<PrefixMap<T>, EnvarError>
+where
+    EnvarParser<T>: EnvarParse<T>,
+{
+    let prefix = prefix.into();
+    let mut vars = BTreeMap::new();
+    for (name, value) in crate::source::with_global(|source| source.vars()) {
+        if let (Ok(name), Ok(value)) = (name.into_string(), value.into_string()) {
+            vars.insert(name, value);
+        }
+    }
+    // override layers shadow the source
+    vars.extend(crate::layers::vars());
+
+    let mut map = BTreeMap::new();
+    for (name, value) in vars.iter() {
+        let Some(key) = name.strip_prefix(prefix.as_ref()) else {
+            continue;
+        };
+        match EnvarParser::<T>::parse(Cow::Owned(name.to_string()), value) {
+            Ok(parsed) => {
+                map.insert(key.to_string(), parsed);
+            }
+            Err(EnvarError::TryDefault(_)) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(PrefixMap {
+        _prefix: prefix,
+        _map: Arc::new(map),
+    })
+}
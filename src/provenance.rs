@@ -0,0 +1,39 @@
+/// Where the value of an `Envar` came from, returned by `Envar::provenance`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Provenance {
+    /// Read from the variable's source, the process environment unless replaced
+    Environment {
+        /// The primary name, or the alias that was set
+        name: String,
+        /// The label of the `EnvSource`, as shown by `Envar::explain`
+        origin: String,
+    },
+    /// Read from an override layer, e.g. one pushed by `load_env_file`
+    Layer {
+        name: String,
+        /// The index of the layer, counted from the bottom
+        index: usize,
+    },
+    /// Read from the file named by `<name>_FILE`
+    File { name: String, path: String },
+    /// Scoped to the current thread with `test::set_scoped`
+    TestScope { name: String },
+    /// The declared default, used because the variable is unset or blank,
+    /// or in place of an invalid value under `OnParseError::UseDefault`
+    Default,
+    /// Set with `Envar::set_override` or `Envar::override_scope`
+    Override,
+}
+
+impl std::fmt::Display for Provenance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Provenance::Environment { name, origin } => write!(f, "{} in {}", name, origin),
+            Provenance::Layer { name, index } => write!(f, "{} in override layer {}", name, index),
+            Provenance::File { name, path } => write!(f, "file {} (from {}_FILE)", path, name),
+            Provenance::TestScope { name } => write!(f, "{} in test scope", name),
+            Provenance::Default => f.write_str("default"),
+            Provenance::Override => f.write_str("override"),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use crate::core::{parse_part, EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::ErrorReason;
+use std::borrow::Cow;
+use std::ops::RangeInclusive;
+
+/// Splits `8000..=8999` or `8000-8999`; a leading `-` belongs to the start, so
+/// `-10--5` is `-10` to `-5`
+fn split_range(value: &str) -> Option<(&str, &str)> {
+    if let Some(bounds) = value.split_once("..=") {
+        return Some(bounds);
+    }
+    let (sep, _) = value.char_indices().skip(1).find(|(_, c)| *c == '-')?;
+    Some((&value[..sep], &value[sep + 1..]))
+}
+
+/// Accepts `start..=end` or `start-end`, rejecting ranges whose start is
+/// greater than their end; renders `start..=end`.
+impl<T: PartialOrd> EnvarParse<RangeInclusive<T>> for EnvarParser<RangeInclusive<T>>
+where
+    EnvarParser<T>: EnvarParse<T>,
+{
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<RangeInclusive<T>, EnvarError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(EnvarError::TryDefault(varname));
+        }
+        let invalid = |varname, reason: String| EnvarError::ParseError {
+            varname,
+            typename: "RangeInclusive",
+            value: value.to_string(),
+            reason: ErrorReason::new(move || reason),
+        };
+        let Some((start, end)) = split_range(value) else {
+            return Err(invalid(
+                varname,
+                "expected a range such as 8000-8999 or 8000..=8999".to_string(),
+            ));
+        };
+        let (start, end) = (start.trim(), end.trim());
+        let start_value = parse_part::<T>(varname.clone(), "start", start)?;
+        let end_value = parse_part::<T>(varname.clone(), "end", end)?;
+        if start_value > end_value {
+            return Err(invalid(
+                varname,
+                format!("start {} is greater than end {}", start, end),
+            ));
+        }
+        Ok(start_value..=end_value)
+    }
+
+    fn render(value: &RangeInclusive<T>) -> Option<String> {
+        Some(format!(
+            "{}..={}",
+            EnvarParser::<T>::render(value.start())?,
+            EnvarParser::<T>::render(value.end())?
+        ))
+    }
+}
@@ -0,0 +1,180 @@
+use crate::core::{EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::ErrorReason;
+use std::borrow::Cow;
+use std::marker::PhantomData;
+
+/// How a `Ratio` reads a number without a `%` sign
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BareRatio {
+    /// Bare numbers are rejected
+    Reject,
+    /// `0.75` is 75%
+    Fraction,
+    /// `75` is 75%
+    Percent,
+    /// Numbers up to 1 are fractions, larger ones percentages, so `0.75` and `75`
+    /// both mean 75% (and `1` means 100%)
+    Auto,
+}
+
+/// Configuration for the `Ratio` type
+pub trait RatioConfig {
+    /// Whether `75%` is accepted
+    const PERCENT_SIGN: bool;
+
+    const BARE: BareRatio;
+}
+
+/// Accepts `75%`, `0.75` and `75`
+pub struct AnyRatio;
+
+impl RatioConfig for AnyRatio {
+    const PERCENT_SIGN: bool = true;
+    const BARE: BareRatio = BareRatio::Auto;
+}
+
+/// Accepts only fractions such as `0.75`
+pub struct FractionRatio;
+
+impl RatioConfig for FractionRatio {
+    const PERCENT_SIGN: bool = false;
+    const BARE: BareRatio = BareRatio::Fraction;
+}
+
+/// Accepts only percentages, `75%` or `75`
+pub struct PercentRatio;
+
+impl RatioConfig for PercentRatio {
+    const PERCENT_SIGN: bool = true;
+    const BARE: BareRatio = BareRatio::Percent;
+}
+
+/// A fraction in `[0, 1]`, e.g. a sampling rate, read as configured by `C`.
+///
+/// Values outside of 0% to 100% are rejected with `EnvarError::ParseError`.
+pub struct Ratio<C = AnyRatio> {
+    _value: f64,
+    _marker: PhantomData<C>,
+}
+
+/// A `Ratio` written as a percentage, `75%` or `75`
+pub type Percent = Ratio<PercentRatio>;
+
+impl<C> Ratio<C> {
+    /// Panics if `value` is not in `[0, 1]`
+    pub fn new(value: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&value),
+            "ratio {} is not between 0 and 1",
+            value
+        );
+        Self {
+            _value: value,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The ratio as a fraction in `[0, 1]`
+    pub fn get(&self) -> f64 {
+        self._value
+    }
+
+    /// The ratio as a percentage in `[0, 100]`
+    pub fn as_percent(&self) -> f64 {
+        self._value * 100.0
+    }
+}
+
+impl<C> Clone for Ratio<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C> Copy for Ratio<C> {}
+
+impl<C> PartialEq for Ratio<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self._value == other._value
+    }
+}
+
+impl<C> PartialOrd for Ratio<C> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self._value.partial_cmp(&other._value)
+    }
+}
+
+impl<C> std::fmt::Debug for Ratio<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Ratio({})", self._value)
+    }
+}
+
+fn expected<C: RatioConfig>() -> &'static str {
+    match (C::PERCENT_SIGN, C::BARE) {
+        (true, BareRatio::Auto) => "expected a percentage (75% or 75) or a fraction (0.75)",
+        (true, BareRatio::Percent) => "expected a percentage, 75% or 75",
+        (true, BareRatio::Fraction) => "expected a percentage (75%) or a fraction (0.75)",
+        (true, BareRatio::Reject) => "expected a percentage such as 75%",
+        (false, BareRatio::Auto) => "expected a fraction (0.75) or a bare percentage (75)",
+        (false, BareRatio::Percent) => "expected a bare percentage such as 75",
+        (false, BareRatio::Fraction) => "expected a fraction such as 0.75",
+        (false, BareRatio::Reject) => "no ratio format is enabled",
+    }
+}
+
+fn parse_ratio<C: RatioConfig>(value: &str) -> Result<f64, String> {
+    let number = |s: &str| s.trim().parse::<f64>().map_err(|_| expected::<C>());
+    let ratio = match value.strip_suffix('%') {
+        Some(percent) if C::PERCENT_SIGN => number(percent)? / 100.0,
+        Some(_) => return Err(expected::<C>().to_string()),
+        None => match C::BARE {
+            BareRatio::Reject => return Err(expected::<C>().to_string()),
+            BareRatio::Fraction => number(value)?,
+            BareRatio::Percent => number(value)? / 100.0,
+            BareRatio::Auto => {
+                let n = number(value)?;
+                if n > 1.0 {
+                    n / 100.0
+                } else {
+                    n
+                }
+            }
+        },
+    };
+    if !(0.0..=1.0).contains(&ratio) {
+        return Err("must be between 0% and 100%".to_string());
+    }
+    Ok(ratio)
+}
+
+impl<C: RatioConfig> EnvarParse<Ratio<C>> for EnvarParser<Ratio<C>> {
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<Ratio<C>, EnvarError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(EnvarError::TryDefault(varname));
+        }
+
+        match parse_ratio::<C>(value) {
+            Ok(ratio) => Ok(Ratio::new(ratio)),
+            Err(reason) => Err(EnvarError::ParseError {
+                varname,
+                typename: "Ratio",
+                value: value.to_string(),
+                reason: ErrorReason::new(move || reason),
+            }),
+        }
+    }
+
+    /// Fractions where `C` reads them, otherwise percentages
+    fn render(value: &Ratio<C>) -> Option<String> {
+        match (C::BARE, C::PERCENT_SIGN) {
+            (BareRatio::Fraction | BareRatio::Auto, _) => Some(value.get().to_string()),
+            (_, true) => Some(format!("{}%", value.as_percent())),
+            (BareRatio::Percent, false) => Some(value.as_percent().to_string()),
+            (BareRatio::Reject, false) => None,
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use crate::core::{EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::ErrorReason;
+use ::regex::Regex;
+use std::borrow::Cow;
+
+/// Why `pattern` failed to compile, with the column of syntax errors, e.g.
+/// `unclosed group at column 4`
+fn describe_error(pattern: &str, error: ::regex::Error) -> String {
+    match regex_syntax::Parser::new().parse(pattern) {
+        Err(regex_syntax::Error::Parse(e)) => {
+            format!("{} at column {}", e.kind(), e.span().start.column)
+        }
+        Err(regex_syntax::Error::Translate(e)) => {
+            format!("{} at column {}", e.kind(), e.span().start.column)
+        }
+        // e.g. the compiled pattern exceeding the size limit
+        _ => error.to_string(),
+    }
+}
+
+/// Compiles the value as a pattern (surrounding whitespace is kept); with
+/// `on_demand` and `on_startup` loading, it is compiled once and cached.
+///
+/// An empty value falls back to the default rather than matching everything.
+impl EnvarParse<Regex> for EnvarParser<Regex> {
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<Regex, EnvarError> {
+        if value.is_empty() {
+            return Err(EnvarError::TryDefault(varname));
+        }
+        Regex::new(value).map_err(|e| {
+            let reason = describe_error(value, e);
+            EnvarError::ParseError {
+                varname,
+                typename: "Regex",
+                value: value.to_string(),
+                reason: ErrorReason::new(move || reason),
+            }
+        })
+    }
+
+    fn render(value: &Regex) -> Option<String> {
+        Some(value.as_str().to_string())
+    }
+}
@@ -0,0 +1,366 @@
+//! An opt-in registry of declared `Envar`s, for reports spanning every variable.
+//!
+//! Variables join the registry via `Envar::register`.
+
+use crate::core::{Envar, EnvarDef, EnvarMeta, EnvarParse, EnvarParser};
+use crate::error::{EnvarError, EnvarErrors};
+use crate::explain::Explanation;
+use crate::stats::EnvarStats;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::{LazyLock, PoisonError, RwLock};
+
+/// A type-erased view of a registered `Envar`.
+pub trait AnyEnvar: Sync {
+    fn name(&self) -> &str;
+
+    fn typename(&self) -> &'static str;
+
+    fn stats(&self) -> EnvarStats;
+
+    fn is_explicit_default(&self) -> bool;
+
+    fn meta(&self) -> &EnvarMeta;
+
+    /// Whether the value must be kept out of generated output; see `Envar::is_sensitive`
+    fn is_sensitive(&self) -> bool;
+
+    /// Whether the variable has no default
+    fn is_required(&self) -> bool;
+
+    /// The default rendered in raw environment form, if there is one and it is renderable
+    fn default_raw(&self) -> Option<String>;
+
+    /// Traces how the variable resolves; see `Envar::explain`
+    fn explain(&self) -> Explanation;
+
+    /// The resolved value in raw environment form, or its `Debug` rendering
+    /// when the type has no raw form
+    fn resolve_rendered(&self) -> Result<String, EnvarError>;
+
+    /// Like `resolve_rendered`, but the raw value the variable was read from when
+    /// there is one: unmasked even for sensitive types, e.g. to fingerprint them
+    fn resolve_unmasked(&self) -> Result<std::ffi::OsString, EnvarError>;
+
+    /// Resolves the variable like `Envar::value`, discarding the value,
+    /// and enforces `required_when` conditions
+    fn validate(&self) -> Result<(), EnvarError>;
+}
+
+impl<T, F> AnyEnvar for Envar<T, F>
+where
+    T: Clone + std::fmt::Debug + Send + Sync + 'static,
+    EnvarParser<T>: EnvarParse<T>,
+    F: Fn() -> EnvarDef<T> + Sync,
+{
+    fn name(&self) -> &str {
+        Envar::name(self)
+    }
+
+    fn typename(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn stats(&self) -> EnvarStats {
+        Envar::stats(self)
+    }
+
+    fn is_explicit_default(&self) -> bool {
+        Envar::is_explicit_default(self)
+    }
+
+    fn meta(&self) -> &EnvarMeta {
+        self.describe()
+    }
+
+    fn is_sensitive(&self) -> bool {
+        Envar::is_sensitive(self)
+    }
+
+    fn is_required(&self) -> bool {
+        matches!(self.default_value(), EnvarDef::Unset)
+    }
+
+    fn default_raw(&self) -> Option<String> {
+        self.default_value()
+            .to_option()
+            .and_then(|default| EnvarParser::<T>::render(&default))
+    }
+
+    fn explain(&self) -> Explanation {
+        Envar::explain(self)
+    }
+
+    fn resolve_rendered(&self) -> Result<String, EnvarError> {
+        let value = self.value()?;
+        Ok(EnvarParser::<T>::render(&value).unwrap_or_else(|| format!("{:?}", value)))
+    }
+
+    fn resolve_unmasked(&self) -> Result<std::ffi::OsString, EnvarError> {
+        let rendered = self.resolve_rendered()?;
+        // defaults and overrides have no raw value
+        Ok(self.raw_os().unwrap_or_else(|_| rendered.into()))
+    }
+
+    fn validate(&self) -> Result<(), EnvarError> {
+        if self.conditionally_required() && matches!(self.lookup_raw(), Ok(None)) {
+            return Err(self.not_set());
+        }
+        self.value().map(drop)
+    }
+}
+
+/// Registered variables, with indexes by address (for deduplication) and by name
+#[derive(Default)]
+struct Registry {
+    entries: Vec<&'static dyn AnyEnvar>,
+    by_addr: HashSet<usize>,
+    by_name: HashMap<String, usize>,
+}
+
+static REGISTRY: LazyLock<RwLock<Registry>> = LazyLock::new(Default::default);
+
+impl<T, F> Envar<T, F>
+where
+    T: Clone + std::fmt::Debug + Send + Sync + 'static,
+    EnvarParser<T>: EnvarParse<T>,
+    F: Fn() -> EnvarDef<T> + Sync,
+{
+    /// Adds this variable to the registry; registering twice is a no-op.
+    pub fn register(&'static self) -> &'static Self {
+        register(self);
+        self
+    }
+}
+
+/// Adds a variable to the registry; registering twice is a no-op.
+pub fn register(envar: &'static dyn AnyEnvar) {
+    let mut registry = REGISTRY.write().unwrap_or_else(PoisonError::into_inner);
+    let addr = envar as *const dyn AnyEnvar as *const () as usize;
+    if registry.by_addr.insert(addr) {
+        let index = registry.entries.len();
+        registry.entries.push(envar);
+        registry
+            .by_name
+            .entry(envar.name().to_string())
+            .or_insert(index);
+    }
+}
+
+/// The registered variable called `name`, found in constant time.
+///
+/// If several variables share a name, the first one registered is returned.
+pub fn lookup(name: &str) -> Option<&'static dyn AnyEnvar> {
+    let registry = REGISTRY.read().unwrap_or_else(PoisonError::into_inner);
+    registry
+        .by_name
+        .get(name)
+        .map(|index| registry.entries[*index])
+}
+
+/// `explain()` for the registered variable called `name`.
+pub fn explain(name: &str) -> Option<Explanation> {
+    lookup(name).map(|envar| envar.explain())
+}
+
+/// Every registered variable, in registration order.
+pub fn entries() -> Vec<&'static dyn AnyEnvar> {
+    REGISTRY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entries
+        .clone()
+}
+
+/// Groups variables by their category, keeping declaration order within each group.
+///
+/// Uncategorized variables come first, then categories in order of first appearance.
+pub fn group_by_category(
+    envars: &[&'static dyn AnyEnvar],
+) -> Vec<(Option<&'static str>, Vec<&'static dyn AnyEnvar>)> {
+    let mut groups: Vec<(Option<&'static str>, Vec<&'static dyn AnyEnvar>)> = vec![(None, vec![])];
+    for envar in envars {
+        let category = envar.meta().category;
+        match groups
+            .iter_mut()
+            .find(|(existing, _)| *existing == category)
+        {
+            Some((_, group)) => group.push(*envar),
+            None => groups.push((category, vec![*envar])),
+        }
+    }
+    groups.retain(|(_, group)| !group.is_empty());
+    groups
+}
+
+/// A variable declared at runtime through a `DynamicEnvarSet`.
+pub type DynamicEnvar<T> = Envar<T, Box<dyn Fn() -> EnvarDef<T> + Send + Sync>>;
+
+/// Variables declared at runtime, e.g. by a plugin, alongside the host's statics.
+///
+/// Declared variables live for the rest of the program and join the global
+/// registry, so they take part in validation and generated documentation.
+pub struct DynamicEnvarSet {
+    _owner: Cow<'static, str>,
+    _entries: Vec<&'static dyn AnyEnvar>,
+}
+
+impl DynamicEnvarSet {
+    /// An empty set; `owner` names the plugin declaring the variables.
+    pub fn new(owner: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            _owner: owner.into(),
+            _entries: Vec::new(),
+        }
+    }
+
+    pub fn owner(&self) -> &str {
+        &self._owner
+    }
+
+    /// Declares an on-demand variable from a name, default and description.
+    pub fn declare<T>(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        default: EnvarDef<T>,
+        description: impl Into<Cow<'static, str>>,
+    ) -> &'static DynamicEnvar<T>
+    where
+        T: Clone + std::fmt::Debug + Send + Sync + 'static,
+        EnvarParser<T>: EnvarParse<T>,
+    {
+        let default_factory: Box<dyn Fn() -> EnvarDef<T> + Send + Sync> = match default {
+            EnvarDef::Default(value) => Box::new(move || EnvarDef::Default(value.clone())),
+            EnvarDef::Unset => Box::new(|| EnvarDef::Unset),
+        };
+        let description: &'static str = match description.into() {
+            Cow::Borrowed(description) => description,
+            Cow::Owned(description) => Box::leak(description.into_boxed_str()),
+        };
+        self.insert(Envar::on_demand_named(name, default_factory).description(description))
+    }
+
+    /// Adds an already configured variable to the set.
+    pub fn insert<T, F>(&mut self, envar: Envar<T, F>) -> &'static Envar<T, F>
+    where
+        T: Clone + std::fmt::Debug + Send + Sync + 'static,
+        EnvarParser<T>: EnvarParse<T>,
+        F: Fn() -> EnvarDef<T> + Sync + 'static,
+    {
+        let envar: &'static Envar<T, F> = Box::leak(Box::new(envar));
+        self._entries.push(envar);
+        envar.register()
+    }
+
+    /// The variables of this set, in declaration order.
+    pub fn entries(&self) -> &[&'static dyn AnyEnvar] {
+        &self._entries
+    }
+
+    /// Resolves the variables of this set only; see `validate`.
+    pub fn validate(&self) -> Result<(), EnvarErrors> {
+        validate(&self._entries)
+    }
+}
+
+/// Runtime counters of every registered variable, hottest first.
+pub struct StatsReport {
+    pub entries: Vec<(String, EnvarStats)>,
+}
+
+pub fn stats_report() -> StatsReport {
+    let mut entries: Vec<(String, EnvarStats)> = entries()
+        .into_iter()
+        .map(|envar| (envar.name().to_string(), envar.stats()))
+        .collect();
+    entries.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.reads));
+    StatsReport { entries }
+}
+
+impl std::fmt::Display for StatsReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (name, stats) in &self.entries {
+            writeln!(
+                f,
+                "{}: reads={} cache_hits={} parse_failures={}",
+                name, stats.reads, stats.cache_hits, stats.parse_failures
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Names of registered variables explicitly set to their declared default,
+/// i.e. entries that can be dropped from deployment manifests.
+pub fn redundant_defaults() -> Vec<String> {
+    entries()
+        .into_iter()
+        .filter(|envar| envar.is_explicit_default())
+        .map(|envar| envar.name().to_string())
+        .collect()
+}
+
+/// Resolves every given variable, collecting all failures instead of stopping at the first.
+pub fn validate(envars: &[&dyn AnyEnvar]) -> Result<(), EnvarErrors> {
+    let errors: Vec<EnvarError> = envars
+        .iter()
+        .filter_map(|envar| envar.validate().err())
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(EnvarErrors { errors })
+    }
+}
+
+/// Resolves every registered variable; see `validate`.
+pub fn validate_all() -> Result<(), EnvarErrors> {
+    validate(&entries())
+}
+
+/// Like `validate`, but resolves the variables concurrently on scoped threads,
+/// so slow sources are fetched in parallel. Errors keep the order of `envars`.
+pub fn validate_parallel(envars: &[&dyn AnyEnvar]) -> Result<(), EnvarErrors> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = envars.len().div_ceil(threads).max(1);
+    let errors: Vec<EnvarError> = std::thread::scope(|scope| {
+        let handles: Vec<_> = envars
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .filter_map(|envar| envar.validate().err())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| match handle.join() {
+                Ok(errors) => errors,
+                Err(panic) => std::panic::resume_unwind(panic),
+            })
+            .collect()
+    });
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(EnvarErrors { errors })
+    }
+}
+
+/// Resolves every registered variable concurrently; see `validate_parallel`.
+pub fn validate_all_parallel() -> Result<(), EnvarErrors> {
+    validate_parallel(&entries())
+}
+
+/// Runs `validate_all`, printing the report and exiting the process on failure,
+/// with the code chosen by `EnvarErrors::exit_code`.
+///
+/// This is what `#[typed_env::main]` inserts at the top of `main`.
+pub fn validate_all_or_exit() {
+    if let Err(errors) = validate_all() {
+        crate::error::report_all_and_exit(&errors);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::source::EnvSource;
+use std::ffi::OsString;
+use std::time::Duration;
+
+/// Retries with exponential backoff, for fetching configuration from sources
+/// that can fail transiently (HTTP, Vault, SSM, ...), either per variable
+/// through a `RetryingSource`, or up front before it is handed to `push_layer`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Delay after the first failed attempt
+    pub initial_backoff: Duration,
+    /// Upper bound for any single delay
+    pub max_backoff: Duration,
+    /// Factor applied to the delay after each failed attempt
+    pub multiplier: u32,
+}
+
+impl RetryPolicy {
+    /// A single attempt, no retries.
+    pub const NONE: RetryPolicy = RetryPolicy::new(1);
+
+    /// `max_attempts` attempts, backing off from 100ms, doubling up to 5s.
+    pub const fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2,
+        }
+    }
+
+    pub const fn with_backoff(mut self, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    pub const fn with_multiplier(mut self, multiplier: u32) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// The delay after failed attempt number `attempt` (starting at 1).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = self
+            .multiplier
+            .checked_pow(attempt.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        self.initial_backoff
+            .checked_mul(factor)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff)
+    }
+
+    /// Runs `fetch` until it succeeds or the attempts are exhausted, sleeping
+    /// between attempts; returns the last error. `fetch` gets the attempt number.
+    pub fn run<T, E>(&self, mut fetch: impl FnMut(u32) -> Result<T, E>) -> Result<T, E> {
+        let mut attempt = 1;
+        loop {
+            match fetch(attempt) {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt >= self.max_attempts => return Err(e),
+                Err(_) => {
+                    std::thread::sleep(self.backoff(attempt));
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::new(3)
+    }
+}
+
+/// An `EnvSource` whose failed `try_get` fetches are retried with a `RetryPolicy`,
+/// e.g. `set_source(RetryingSource::new(vault, RetryPolicy::new(5)))`.
+///
+/// Once the attempts are exhausted, reads fail with `EnvarError::SourceError`,
+/// or serve the last good value of variables marked `serve_stale_on_error`.
+pub struct RetryingSource<S> {
+    _source: S,
+    _policy: RetryPolicy,
+}
+
+impl<S: EnvSource> RetryingSource<S> {
+    pub const fn new(source: S, policy: RetryPolicy) -> Self {
+        Self {
+            _source: source,
+            _policy: policy,
+        }
+    }
+}
+
+impl<S: EnvSource> EnvSource for RetryingSource<S> {
+    fn get(&self, name: &str) -> Option<OsString> {
+        self.try_get(name).ok().flatten()
+    }
+
+    fn try_get(&self, name: &str) -> Result<Option<OsString>, String> {
+        self._policy.run(|_attempt| self._source.try_get(name))
+    }
+
+    fn vars(&self) -> Vec<(OsString, OsString)> {
+        self._source.vars()
+    }
+
+    fn label(&self) -> &str {
+        self._source.label()
+    }
+
+    fn origin(&self, name: &str) -> String {
+        self._source.origin(name)
+    }
+}
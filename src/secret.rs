@@ -0,0 +1,116 @@
+use crate::core::{EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use std::borrow::Cow;
+
+/// Types a `Secret` can hold. `wipe` clears the value's memory when the secret is
+/// dropped; it does nothing by default.
+///
+/// With the `zeroize` feature, `String`, `OsString`, `Vec<u8>` and `[u8; N]` are zeroized.
+pub trait SecretValue {
+    fn wipe(&mut self) {}
+}
+
+#[cfg(not(feature = "zeroize"))]
+impl SecretValue for String {}
+#[cfg(not(feature = "zeroize"))]
+impl SecretValue for Vec<u8> {}
+
+#[cfg(feature = "zeroize")]
+impl SecretValue for String {
+    fn wipe(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl SecretValue for Vec<u8> {
+    fn wipe(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+#[cfg(not(feature = "zeroize"))]
+impl SecretValue for std::ffi::OsString {}
+
+#[cfg(feature = "zeroize")]
+impl SecretValue for std::ffi::OsString {
+    fn wipe(&mut self) {
+        zeroize::Zeroize::zeroize(&mut std::mem::take(self).into_encoded_bytes());
+    }
+}
+
+#[cfg(not(feature = "zeroize"))]
+impl<const N: usize> SecretValue for [u8; N] {}
+
+#[cfg(feature = "zeroize")]
+impl<const N: usize> SecretValue for [u8; N] {
+    fn wipe(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+impl<T: SecretValue> SecretValue for Option<T> {
+    fn wipe(&mut self) {
+        if let Some(value) = self {
+            value.wipe();
+        }
+    }
+}
+
+macro_rules! impl_secret_value {
+    ($($t:ty),*) => {
+        $(impl SecretValue for $t {})*
+    };
+}
+
+impl_secret_value!(u128, u64, u32, u16, u8, i128, i64, i32, i16, i8, usize, isize);
+
+/// A value that prints as `***` in `Debug` and `Display`, read through `expose`.
+///
+/// `Envar<Secret<T>>` parses like `Envar<T>`, but is treated as sensitive:
+/// it has no raw form (`render` is `None`), so generated manifests, schemas and
+/// snapshots never contain its value, and parse errors do not echo the raw value.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secret<T: SecretValue>(T);
+
+impl<T: SecretValue> Secret<T> {
+    pub const fn new(value: T) -> Self {
+        Secret(value)
+    }
+
+    /// The underlying value; keep it out of logs.
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: SecretValue> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.wipe();
+    }
+}
+
+impl<T: SecretValue> std::fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("***")
+    }
+}
+
+impl<T: SecretValue> std::fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("***")
+    }
+}
+
+impl<T: SecretValue> EnvarParse<Secret<T>> for EnvarParser<Secret<T>>
+where
+    EnvarParser<T>: EnvarParse<T>,
+{
+    const SENSITIVE: bool = true;
+
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<Secret<T>, EnvarError> {
+        EnvarParser::<T>::parse(varname, value)
+            .map(Secret)
+            .map_err(crate::core::redact_parse_error)
+    }
+}
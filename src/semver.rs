@@ -0,0 +1,47 @@
+use crate::core::{EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::ErrorReason;
+use ::semver::{Version, VersionReq};
+use std::borrow::Cow;
+
+/// Accepts `1.4.0`, `2.0.0-rc.1+build.5`, and the same with a leading `v`, as in
+/// git tags.
+impl EnvarParse<Version> for EnvarParser<Version> {
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<Version, EnvarError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(EnvarError::TryDefault(varname));
+        }
+        let version = value.strip_prefix('v').unwrap_or(value);
+        Version::parse(version).map_err(|e| EnvarError::ParseError {
+            varname,
+            typename: "Version",
+            value: value.to_string(),
+            reason: ErrorReason::new(move || e.to_string()),
+        })
+    }
+
+    fn render(value: &Version) -> Option<String> {
+        Some(value.to_string())
+    }
+}
+
+/// Accepts Cargo-style requirements, e.g. `>=1.4.0`, `^2.1` or `>=1.4, <3`.
+impl EnvarParse<VersionReq> for EnvarParser<VersionReq> {
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<VersionReq, EnvarError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(EnvarError::TryDefault(varname));
+        }
+        VersionReq::parse(value).map_err(|e| EnvarError::ParseError {
+            varname,
+            typename: "VersionReq",
+            value: value.to_string(),
+            reason: ErrorReason::new(move || e.to_string()),
+        })
+    }
+
+    fn render(value: &VersionReq) -> Option<String> {
+        Some(value.to_string())
+    }
+}
@@ -0,0 +1,123 @@
+use crate::core::{parse_item, EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::list_envar::{list_items, ListEnvarConfig};
+use crate::ErrorReason;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashSet};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// A set type `SetEnvar` can collect into: `HashSet<T>` or, for sorted iteration, `BTreeSet<T>`.
+pub trait SetLike: Default {
+    type Item;
+
+    /// Whether `items` iterates in a stable order
+    const ORDERED: bool;
+
+    /// Adds `item`, returning whether it was not present yet
+    fn insert_item(&mut self, item: Self::Item) -> bool;
+
+    fn items(&self) -> impl Iterator<Item = &Self::Item>;
+}
+
+impl<T: Eq + Hash> SetLike for HashSet<T> {
+    type Item = T;
+    const ORDERED: bool = false;
+
+    fn insert_item(&mut self, item: T) -> bool {
+        self.insert(item)
+    }
+
+    fn items(&self) -> impl Iterator<Item = &T> {
+        self.iter()
+    }
+}
+
+impl<T: Ord> SetLike for BTreeSet<T> {
+    type Item = T;
+    const ORDERED: bool = true;
+
+    fn insert_item(&mut self, item: T) -> bool {
+        self.insert(item)
+    }
+
+    fn items(&self) -> impl Iterator<Item = &T> {
+        self.iter()
+    }
+}
+
+/// A list parsed into a set `S`, split like `ListEnvar<S::Item, C>`.
+///
+/// Repeated items are collapsed, unless `UNIQUE` is set, which rejects them
+/// with `EnvarError::ParseError` instead.
+pub struct SetEnvar<S, C, const UNIQUE: bool = false> {
+    _marker: PhantomData<C>,
+    _set: Arc<S>,
+}
+
+impl<S, C, const UNIQUE: bool> Clone for SetEnvar<S, C, UNIQUE> {
+    fn clone(&self) -> Self {
+        Self {
+            _marker: PhantomData,
+            _set: self._set.clone(),
+        }
+    }
+}
+
+impl<S, C, const UNIQUE: bool> std::ops::Deref for SetEnvar<S, C, UNIQUE> {
+    type Target = S;
+
+    fn deref(&self) -> &Self::Target {
+        &self._set
+    }
+}
+
+impl<S: std::fmt::Debug, C, const UNIQUE: bool> std::fmt::Debug for SetEnvar<S, C, UNIQUE> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SetEnvar {{ _set: {:?} }}", self._set)
+    }
+}
+
+impl<S, C, const UNIQUE: bool> EnvarParse<SetEnvar<S, C, UNIQUE>>
+    for EnvarParser<SetEnvar<S, C, UNIQUE>>
+where
+    S: SetLike,
+    C: ListEnvarConfig,
+    EnvarParser<S::Item>: EnvarParse<S::Item>,
+{
+    fn parse(
+        varname: Cow<'static, str>,
+        value: &str,
+    ) -> Result<SetEnvar<S, C, UNIQUE>, EnvarError> {
+        let mut set = S::default();
+        for (index, item) in list_items::<C>(value).enumerate() {
+            let parsed = parse_item::<S::Item>(varname.clone(), index, &item)?;
+            if !set.insert_item(parsed) && UNIQUE {
+                let reason = format!("duplicate item {:?}", item);
+                return Err(EnvarError::ParseError {
+                    varname,
+                    typename: "SetEnvar",
+                    value: value.to_string(),
+                    reason: ErrorReason::new(move || reason),
+                });
+            }
+        }
+        Ok(SetEnvar {
+            _marker: PhantomData,
+            _set: Arc::new(set),
+        })
+    }
+
+    /// Items of unordered sets are sorted, so the rendering does not depend on hashing
+    fn render(value: &SetEnvar<S, C, UNIQUE>) -> Option<String> {
+        let mut items = value
+            .items()
+            .map(EnvarParser::<S::Item>::render)
+            .collect::<Option<Vec<String>>>()?;
+        if !S::ORDERED {
+            items.sort();
+        }
+        Some(items.join(C::SEP))
+    }
+}
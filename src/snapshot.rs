@@ -0,0 +1,237 @@
+//! Named snapshots of resolved configuration, and the drift between them.
+//!
+//! A snapshot can be written out with `Display` and read back with
+//! `Snapshot::parse`, so it survives restarts or can be shipped between
+//! instances, e.g. to compare a canary against the baseline.
+//!
+//! Sensitive values are recorded as an HMAC-SHA256 fingerprint. The key is
+//! random per process unless set with `set_fingerprint_key`, which snapshots
+//! compared across restarts or instances must share.
+
+use crate::multiline::{escape_line, unescape_line};
+use crate::registry::{self, AnyEnvar};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{LazyLock, PoisonError, RwLock};
+
+static FINGERPRINT_KEY: LazyLock<RwLock<Vec<u8>>> = LazyLock::new(|| {
+    // `RandomState` is seeded from the operating system's randomness
+    let state = std::collections::hash_map::RandomState::new();
+    let key = (0u8..4)
+        .flat_map(|i| {
+            let mut hasher = state.build_hasher();
+            hasher.write_u8(i);
+            hasher.finish().to_le_bytes()
+        })
+        .collect();
+    RwLock::new(key)
+});
+
+/// Sets the key sensitive values are fingerprinted with, so snapshots taken
+/// by other processes sharing the key can be compared. Treat it as a secret.
+pub fn set_fingerprint_key(key: impl Into<Vec<u8>>) {
+    *FINGERPRINT_KEY
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = key.into();
+}
+
+/// The resolved value of every variable at one point in time, e.g. to log the
+/// effective configuration at startup or serve it on a debug endpoint.
+///
+/// Failed variables are kept as `<error: ...>`; sensitive ones only as a keyed
+/// fingerprint, so drift in secrets is detected without recording them.
+///
+/// With the `serde` feature it serializes as its `name` and a map of `values`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    pub name: String,
+    pub values: BTreeMap<String, String>,
+}
+
+/// A variable whose resolved value differs between two snapshots.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+    Added {
+        name: String,
+        value: String,
+    },
+    Removed {
+        name: String,
+        value: String,
+    },
+    Modified {
+        name: String,
+        before: String,
+        after: String,
+    },
+}
+
+impl Change {
+    pub fn name(&self) -> &str {
+        match self {
+            Change::Added { name, .. }
+            | Change::Removed { name, .. }
+            | Change::Modified { name, .. } => name,
+        }
+    }
+}
+
+/// The changes from snapshot `from` to snapshot `to`, ordered by variable name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    pub from: String,
+    pub to: String,
+    pub changes: Vec<Change>,
+}
+
+impl SnapshotDiff {
+    /// Whether the two snapshots agree on every variable
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+fn fingerprint(value: &[u8]) -> String {
+    let key = FINGERPRINT_KEY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner);
+    let mut mac = Hmac::<Sha256>::new_from_slice(&key).expect("HMAC accepts keys of any length");
+    mac.update(value);
+    let digest = mac.finalize().into_bytes();
+    let hex: String = digest[..16]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("<sensitive {}>", hex)
+}
+
+impl Snapshot {
+    /// Resolves the given variables into a snapshot called `name`.
+    pub fn capture_of(name: impl Into<String>, entries: &[&dyn AnyEnvar]) -> Snapshot {
+        let values = entries
+            .iter()
+            .map(|envar| {
+                // sensitive types may render masked (`Secret` renders as `***`),
+                // so their raw value is fingerprinted instead
+                let value = if envar.is_sensitive() {
+                    envar
+                        .resolve_unmasked()
+                        .map(|raw| fingerprint(raw.as_encoded_bytes()))
+                } else {
+                    envar.resolve_rendered()
+                };
+                let value = value.unwrap_or_else(|e| format!("<error: {}>", e));
+                (envar.name().to_string(), value)
+            })
+            .collect();
+        Snapshot {
+            name: name.into(),
+            values,
+        }
+    }
+
+    /// `capture_of` every registered variable.
+    pub fn capture(name: impl Into<String>) -> Snapshot {
+        Snapshot::capture_of(name, &registry::entries())
+    }
+
+    /// What changed from this snapshot to `other`.
+    pub fn diff(&self, other: &Snapshot) -> SnapshotDiff {
+        let mut changes = vec![];
+        for (name, before) in &self.values {
+            match other.values.get(name) {
+                None => changes.push(Change::Removed {
+                    name: name.clone(),
+                    value: before.clone(),
+                }),
+                Some(after) if after != before => changes.push(Change::Modified {
+                    name: name.clone(),
+                    before: before.clone(),
+                    after: after.clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+        for (name, value) in &other.values {
+            if !self.values.contains_key(name) {
+                changes.push(Change::Added {
+                    name: name.clone(),
+                    value: value.clone(),
+                });
+            }
+        }
+        changes.sort_by(|a, b| a.name().cmp(b.name()));
+        SnapshotDiff {
+            from: self.name.clone(),
+            to: other.name.clone(),
+            changes,
+        }
+    }
+
+    /// What changed from this snapshot to the current configuration of the registered variables.
+    pub fn diff_current(&self) -> SnapshotDiff {
+        self.diff(&Snapshot::capture("current"))
+    }
+
+    /// Reads back a snapshot written with `Display`.
+    pub fn parse(text: &str) -> Option<Snapshot> {
+        let mut lines = text.lines();
+        let name = lines.next()?.strip_prefix("# snapshot ")?.to_string();
+        let mut values = BTreeMap::new();
+        for line in lines {
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line.split_once('=')?;
+            values.insert(key.to_string(), unescape_line(value));
+        }
+        Some(Snapshot { name, values })
+    }
+}
+
+impl std::fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "# snapshot {}", self.name)?;
+        for (name, value) in &self.values {
+            writeln!(f, "{}={}", name, escape_line(value))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Snapshot {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut snapshot = serializer.serialize_struct("Snapshot", 2)?;
+        snapshot.serialize_field("name", &self.name)?;
+        snapshot.serialize_field("values", &self.values)?;
+        snapshot.end()
+    }
+}
+
+impl std::fmt::Display for SnapshotDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} -> {}: {} change(s)",
+            self.from,
+            self.to,
+            self.changes.len()
+        )?;
+        for change in &self.changes {
+            match change {
+                Change::Added { name, value } => writeln!(f, "  + {} = {}", name, value)?,
+                Change::Removed { name, value } => writeln!(f, "  - {} = {}", name, value)?,
+                Change::Modified {
+                    name,
+                    before,
+                    after,
+                } => writeln!(f, "  ~ {}: {} -> {}", name, before, after)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// Where `Envar`s read raw values from once the test scopes and override layers
+/// have been consulted.
+///
+/// The process environment (`ProcessEnv`) is used unless another source is installed
+/// globally with `set_source` or per variable with `Envar::source`.
+pub trait EnvSource: Send + Sync {
+    /// The raw value of `name`, if set.
+    fn get(&self, name: &str) -> Option<OsString>;
+
+    /// Like `get`, but lets sources backed by a remote store (HTTP, Vault, SSM, ...)
+    /// report a failed fetch, which `Envar`s return as `EnvarError::SourceError`
+    /// instead of treating the name as unset. Wrap such a source in a
+    /// `RetryingSource` to retry transient failures first.
+    fn try_get(&self, name: &str) -> Result<Option<OsString>, String> {
+        Ok(self.get(name))
+    }
+
+    /// Every name and value in the source, used by prefix scans such as `collect_prefix`.
+    fn vars(&self) -> Vec<(OsString, OsString)>;
+
+    /// A short label shown by `Envar::explain`.
+    fn label(&self) -> &str {
+        "custom source"
+    }
+
+    /// Where the value of `name` comes from, shown by `Envar::explain`;
+    /// the `label` unless the source combines several others.
+    fn origin(&self, _name: &str) -> String {
+        self.label().to_string()
+    }
+}
+
+/// The process environment.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProcessEnv;
+
+impl EnvSource for ProcessEnv {
+    fn get(&self, name: &str) -> Option<OsString> {
+        std::env::var_os(name)
+    }
+
+    fn vars(&self) -> Vec<(OsString, OsString)> {
+        std::env::vars_os().collect()
+    }
+
+    fn label(&self) -> &str {
+        "process environment"
+    }
+}
+
+impl EnvSource for HashMap<String, String> {
+    fn get(&self, name: &str) -> Option<OsString> {
+        HashMap::get(self, name).map(OsString::from)
+    }
+
+    fn vars(&self) -> Vec<(OsString, OsString)> {
+        self.iter()
+            .map(|(k, v)| (OsString::from(k), OsString::from(v)))
+            .collect()
+    }
+
+    fn label(&self) -> &str {
+        "in-memory map"
+    }
+}
+
+impl EnvSource for BTreeMap<String, String> {
+    fn get(&self, name: &str) -> Option<OsString> {
+        BTreeMap::get(self, name).map(OsString::from)
+    }
+
+    fn vars(&self) -> Vec<(OsString, OsString)> {
+        self.iter()
+            .map(|(k, v)| (OsString::from(k), OsString::from(v)))
+            .collect()
+    }
+
+    fn label(&self) -> &str {
+        "in-memory map"
+    }
+}
+
+/// A fixed list of name/value pairs, usable in `const` contexts,
+/// e.g. `static DEFAULTS: [(&str, &str); 1] = [("PORT", "8080")];`
+impl<const N: usize> EnvSource for [(&'static str, &'static str); N] {
+    fn get(&self, name: &str) -> Option<OsString> {
+        self.iter()
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| OsString::from(v))
+    }
+
+    fn vars(&self) -> Vec<(OsString, OsString)> {
+        self.iter()
+            .map(|(k, v)| (OsString::from(k), OsString::from(v)))
+            .collect()
+    }
+
+    fn label(&self) -> &str {
+        "static pairs"
+    }
+}
+
+/// Sources consulted in priority order, the first one defining a name winning,
+/// e.g. the process environment, then a dotenv file, then built-in defaults:
+///
+/// `LayeredSource::new().layer("env", ProcessEnv).layer(".env", read_env_file(".env")?)`
+#[derive(Default)]
+pub struct LayeredSource {
+    _layers: Vec<(String, Box<dyn EnvSource>)>,
+}
+
+impl LayeredSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `source` below the existing layers, labelled `label`.
+    pub fn layer(mut self, label: impl Into<String>, source: impl EnvSource + 'static) -> Self {
+        self._layers.push((label.into(), Box::new(source)));
+        self
+    }
+
+    /// The label of the layer supplying `name`, if any does.
+    pub fn layer_of(&self, name: &str) -> Option<&str> {
+        self._layers
+            .iter()
+            .find(|(_, source)| source.get(name).is_some())
+            .map(|(label, _)| label.as_str())
+    }
+}
+
+impl EnvSource for LayeredSource {
+    fn get(&self, name: &str) -> Option<OsString> {
+        self._layers.iter().find_map(|(_, source)| source.get(name))
+    }
+
+    fn try_get(&self, name: &str) -> Result<Option<OsString>, String> {
+        for (_, source) in &self._layers {
+            if let Some(value) = source.try_get(name)? {
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+
+    fn vars(&self) -> Vec<(OsString, OsString)> {
+        let mut vars = HashMap::new();
+        for (_, source) in self._layers.iter().rev() {
+            vars.extend(source.vars());
+        }
+        vars.into_iter().collect()
+    }
+
+    fn label(&self) -> &str {
+        "layered source"
+    }
+
+    fn origin(&self, name: &str) -> String {
+        match self.layer_of(name) {
+            Some(label) => format!("layered source ({})", label),
+            None => self.label().to_string(),
+        }
+    }
+}
+
+static SOURCE: RwLock<Option<Arc<dyn EnvSource>>> = RwLock::new(None);
+
+/// Makes every `Envar` without its own source read from `source` instead of
+/// the process environment.
+pub fn set_source(source: impl EnvSource + 'static) {
+    *SOURCE.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(source));
+    crate::layers::bump_generation();
+}
+
+/// Restores reading from the process environment.
+pub fn reset_source() {
+    *SOURCE.write().unwrap_or_else(PoisonError::into_inner) = None;
+    crate::layers::bump_generation();
+}
+
+/// Runs `f` with the globally installed source
+pub(crate) fn with_global<R>(f: impl FnOnce(&dyn EnvSource) -> R) -> R {
+    match SOURCE
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_deref()
+    {
+        Some(source) => f(source),
+        None => f(&ProcessEnv),
+    }
+}
@@ -1,2 +1,27 @@
 pub const TRUE_ALTERNATIVES: &[&str] = &["true", "1", "yes", "y", "on", "enabled"];
 pub const FALSE_ALTERNATIVES: &[&str] = &["false", "0", "no", "n", "off", "disabled"];
+pub const VERBOSITY_NAMES: &[(&str, usize)] = &[
+    ("off", 0),
+    ("quiet", 0),
+    ("error", 1),
+    ("warn", 2),
+    ("warning", 2),
+    ("info", 3),
+    ("debug", 4),
+    ("trace", 5),
+];
+pub const DEPLOYMENT_ENV_NAMES: &[(&str, crate::DeploymentEnv)] = &[
+    ("dev", crate::DeploymentEnv::Development),
+    ("develop", crate::DeploymentEnv::Development),
+    ("development", crate::DeploymentEnv::Development),
+    ("local", crate::DeploymentEnv::Development),
+    ("test", crate::DeploymentEnv::Test),
+    ("testing", crate::DeploymentEnv::Test),
+    ("ci", crate::DeploymentEnv::Test),
+    ("stage", crate::DeploymentEnv::Staging),
+    ("staging", crate::DeploymentEnv::Staging),
+    ("preprod", crate::DeploymentEnv::Staging),
+    ("prod", crate::DeploymentEnv::Production),
+    ("production", crate::DeploymentEnv::Production),
+    ("live", crate::DeploymentEnv::Production),
+];
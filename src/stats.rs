@@ -0,0 +1,100 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A snapshot of an `Envar`'s runtime counters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EnvarStats {
+    /// Number of `value()` calls
+    pub reads: u64,
+    /// Reads answered from the cache without re-parsing
+    pub cache_hits: u64,
+    /// Times a raw value failed to parse. Failures are cached against the raw
+    /// value, so repeated reads of the same bad value count once
+    pub parse_failures: u64,
+    /// When `value()` was last called, to the second: the clock is written at
+    /// most once a second, so hot variables do not contend on it
+    pub last_read: Option<SystemTime>,
+    /// When a read last missed the cache and resolved the value; cache hits
+    /// only bump the counters, keeping the clock off the hot path
+    pub last_resolved: Option<SystemTime>,
+    /// When a freshly resolved value was last stored
+    pub last_change: Option<SystemTime>,
+}
+
+pub(crate) struct StatsCell {
+    reads: AtomicU64,
+    cache_hits: AtomicU64,
+    parse_failures: AtomicU64,
+    // whole seconds since the unix epoch, 0 meaning never
+    last_read: AtomicU64,
+    // nanoseconds since the unix epoch, 0 meaning never
+    last_resolved: AtomicU64,
+    last_change: AtomicU64,
+}
+
+fn now_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+fn from_nanos(nanos: u64) -> Option<SystemTime> {
+    if nanos == 0 {
+        None
+    } else {
+        Some(UNIX_EPOCH + Duration::from_nanos(nanos))
+    }
+}
+
+impl StatsCell {
+    pub(crate) const fn new() -> Self {
+        Self {
+            reads: AtomicU64::new(0),
+            cache_hits: AtomicU64::new(0),
+            parse_failures: AtomicU64::new(0),
+            last_read: AtomicU64::new(0),
+            last_resolved: AtomicU64::new(0),
+            last_change: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn record_read(&self) {
+        self.reads.fetch_add(1, Ordering::Relaxed);
+        let now = now_nanos() / 1_000_000_000;
+        // the load keeps the cache line shared while the second has not changed
+        if self.last_read.load(Ordering::Relaxed) < now {
+            self.last_read.store(now, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn record_cache_miss(&self) {
+        self.last_resolved.store(now_nanos(), Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_cache_hit(&self) {
+        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_parse_failure(&self) {
+        self.parse_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_change(&self) {
+        self.last_change.store(now_nanos(), Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> EnvarStats {
+        EnvarStats {
+            reads: self.reads.load(Ordering::Relaxed),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            parse_failures: self.parse_failures.load(Ordering::Relaxed),
+            last_read: match self.last_read.load(Ordering::Relaxed) {
+                0 => None,
+                secs => Some(UNIX_EPOCH + Duration::from_secs(secs)),
+            },
+            last_resolved: from_nanos(self.last_resolved.load(Ordering::Relaxed)),
+            last_change: from_nanos(self.last_change.load(Ordering::Relaxed)),
+        }
+    }
+}
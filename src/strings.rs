@@ -0,0 +1,106 @@
+use crate::core::{EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::ErrorReason;
+use std::borrow::Cow;
+
+/// A string that is not empty or whitespace-only, e.g. a required name or URL.
+///
+/// Blank values are rejected with `EnvarError::ParseError` rather than falling
+/// back to the default. The value is kept as given; see `TrimmedString`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NonEmptyString(String);
+
+impl NonEmptyString {
+    /// `None` if `value` is empty or whitespace-only
+    pub fn new(value: impl Into<String>) -> Option<Self> {
+        let value = value.into();
+        if value.trim().is_empty() {
+            None
+        } else {
+            Some(Self(value))
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl std::ops::Deref for NonEmptyString {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for NonEmptyString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl EnvarParse<NonEmptyString> for EnvarParser<NonEmptyString> {
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<NonEmptyString, EnvarError> {
+        match NonEmptyString::new(value) {
+            Some(value) => Ok(value),
+            None => Err(EnvarError::ParseError {
+                varname,
+                typename: "NonEmptyString",
+                value: value.to_string(),
+                reason: ErrorReason::new(|| "must not be empty or whitespace-only".to_string()),
+            }),
+        }
+    }
+
+    fn render(value: &NonEmptyString) -> Option<String> {
+        Some(value.0.clone())
+    }
+}
+
+/// A string with surrounding whitespace trimmed, e.g. from a value copied
+/// with a trailing newline.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TrimmedString(String);
+
+impl TrimmedString {
+    pub fn new(value: &str) -> Self {
+        Self(value.trim().to_string())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl std::ops::Deref for TrimmedString {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for TrimmedString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl EnvarParse<TrimmedString> for EnvarParser<TrimmedString> {
+    fn parse(_varname: Cow<'static, str>, value: &str) -> Result<TrimmedString, EnvarError> {
+        Ok(TrimmedString::new(value))
+    }
+
+    fn render(value: &TrimmedString) -> Option<String> {
+        Some(value.0.clone())
+    }
+}
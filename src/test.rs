@@ -0,0 +1,179 @@
+//! Thread-local overrides for tests.
+//!
+//! Values set here are only visible to the current thread and are restored when
+//! their guard is dropped, so tests running in parallel do not see each other's
+//! configuration and need neither a global lock nor `std::env::set_var`.
+//!
+//! Variables frozen by an earlier `on_startup` read keep their value under
+//! `set_scoped`; `Envar::override_scope` shadows them too.
+//!
+//! While a guard is alive on a thread, reads on that thread bypass the caches,
+//! so scoped values neither freeze `on_startup` variables nor outlive the scope
+//! in `on_demand` caches; their provenance is kept per thread as well. Guards
+//! cannot be sent to another thread, as they restore the thread they were
+//! created on.
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+thread_local! {
+    /// raw values by name; `None` scopes the variable as unset
+    static SCOPED_VARS: RefCell<HashMap<String, Option<String>>> = RefCell::new(HashMap::new());
+    /// typed values by `Envar` address
+    static SCOPED_OVERRIDES: RefCell<HashMap<usize, Box<dyn Any>>> = RefCell::new(HashMap::new());
+    /// what reads inside the scopes resolved, by `Envar` address, dropped with the last guard
+    static SCOPED_RESOLVED: RefCell<HashMap<usize, Box<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+// guards alive on any thread, so reads skip the thread-local maps when there are none
+static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+
+/// Restores the previous raw value of a variable when dropped.
+///
+/// The guard stays on the thread it was created on:
+///
+/// ```compile_fail
+/// let guard = typed_env::test::set_scoped("PORT", "8080");
+/// std::thread::spawn(move || drop(guard));
+/// ```
+#[must_use = "the value is restored as soon as the guard is dropped"]
+pub struct ScopedVar {
+    name: String,
+    previous: Option<Option<String>>,
+    _not_send: PhantomData<*const ()>,
+}
+
+/// Makes `name` read as `value` on the current thread until the guard is dropped.
+pub fn set_scoped(name: impl Into<String>, value: impl Into<String>) -> ScopedVar {
+    scope_var(name.into(), Some(value.into()))
+}
+
+/// Makes `name` read as unset on the current thread until the guard is dropped.
+pub fn unset_scoped(name: impl Into<String>) -> ScopedVar {
+    scope_var(name.into(), None)
+}
+
+fn scope_var(name: String, value: Option<String>) -> ScopedVar {
+    let previous = SCOPED_VARS.with(|vars| vars.borrow_mut().insert(name.clone(), value));
+    ACTIVE.fetch_add(1, Ordering::SeqCst);
+    ScopedVar {
+        name,
+        previous,
+        _not_send: PhantomData,
+    }
+}
+
+impl Drop for ScopedVar {
+    fn drop(&mut self) {
+        SCOPED_VARS.with(|vars| {
+            let mut vars = vars.borrow_mut();
+            match self.previous.take() {
+                Some(previous) => vars.insert(self.name.clone(), previous),
+                None => vars.remove(&self.name),
+            }
+        });
+        ACTIVE.fetch_sub(1, Ordering::SeqCst);
+        forget_resolved_if_unscoped();
+    }
+}
+
+/// The raw value scoped on the current thread: `Some(None)` if scoped as unset.
+pub(crate) fn lookup(name: &str) -> Option<Option<String>> {
+    if ACTIVE.load(Ordering::SeqCst) == 0 {
+        return None;
+    }
+    SCOPED_VARS.with(|vars| vars.borrow().get(name).cloned())
+}
+
+/// Every raw value scoped on the current thread, `None` for those scoped as unset.
+#[cfg(feature = "serde")]
+pub(crate) fn scoped_vars() -> HashMap<String, Option<String>> {
+    if ACTIVE.load(Ordering::SeqCst) == 0 {
+        return HashMap::new();
+    }
+    SCOPED_VARS.with(|vars| vars.borrow().clone())
+}
+
+/// Restores the previous typed override of an `Envar` when dropped;
+/// returned by `Envar::override_scope`.
+#[must_use = "the value is restored as soon as the guard is dropped"]
+pub struct OverrideGuard {
+    key: usize,
+    previous: Option<Box<dyn Any>>,
+    _not_send: PhantomData<*const ()>,
+}
+
+pub(crate) fn scope_override<T: 'static>(key: usize, value: T) -> OverrideGuard {
+    let previous =
+        SCOPED_OVERRIDES.with(|overrides| overrides.borrow_mut().insert(key, Box::new(value)));
+    ACTIVE.fetch_add(1, Ordering::SeqCst);
+    OverrideGuard {
+        key,
+        previous,
+        _not_send: PhantomData,
+    }
+}
+
+impl Drop for OverrideGuard {
+    fn drop(&mut self) {
+        SCOPED_OVERRIDES.with(|overrides| {
+            let mut overrides = overrides.borrow_mut();
+            match self.previous.take() {
+                Some(previous) => overrides.insert(self.key, previous),
+                None => overrides.remove(&self.key),
+            }
+        });
+        ACTIVE.fetch_sub(1, Ordering::SeqCst);
+        forget_resolved_if_unscoped();
+    }
+}
+
+/// The typed override scoped on the current thread for the `Envar` at `key`
+pub(crate) fn override_value<T: Clone + 'static>(key: usize) -> Option<T> {
+    if ACTIVE.load(Ordering::SeqCst) == 0 {
+        return None;
+    }
+    SCOPED_OVERRIDES.with(|overrides| {
+        overrides
+            .borrow()
+            .get(&key)
+            .and_then(|value| value.downcast_ref::<T>())
+            .cloned()
+    })
+}
+
+/// Whether a guard is alive on the current thread
+pub(crate) fn in_scope() -> bool {
+    if ACTIVE.load(Ordering::SeqCst) == 0 {
+        return false;
+    }
+    SCOPED_VARS.with(|vars| !vars.borrow().is_empty())
+        || SCOPED_OVERRIDES.with(|overrides| !overrides.borrow().is_empty())
+}
+
+fn forget_resolved_if_unscoped() {
+    let unscoped = SCOPED_VARS.with(|vars| vars.borrow().is_empty())
+        && SCOPED_OVERRIDES.with(|overrides| overrides.borrow().is_empty());
+    if unscoped {
+        SCOPED_RESOLVED.with(|resolved| resolved.borrow_mut().clear());
+    }
+}
+
+/// Remembers what a read inside a scope resolved for the `Envar` at `key`
+pub(crate) fn record_resolved<R: 'static>(key: usize, resolved: R) {
+    SCOPED_RESOLVED.with(|scoped| scoped.borrow_mut().insert(key, Box::new(resolved)));
+}
+
+/// What the last read inside the current scopes resolved for the `Envar` at `key`
+pub(crate) fn resolved<R: Clone + 'static>(key: usize) -> Option<R> {
+    SCOPED_RESOLVED.with(|scoped| {
+        scoped
+            .borrow()
+            .get(&key)
+            .and_then(|resolved| resolved.downcast_ref::<R>())
+            .cloned()
+    })
+}
@@ -1,4 +1,6 @@
-use crate::{Envar, EnvarDef, EnvarError, ListEnvar, ListEnvarConfig};
+use crate::{Envar, EnvarDef, EnvarError, OnParseError};
+#[cfg(feature = "list")]
+use crate::{ListEnvar, ListEnvarConfig};
 use std::sync::Mutex;
 
 static SINGLE_THREAD_ASSURANCE: Mutex<()> = Mutex::new(());
@@ -15,7 +17,9 @@ fn set_env_var(name: &str, value: &str) {
 
 // Helper to get lock and handle poisoning
 fn get_test_lock() -> std::sync::MutexGuard<'static, ()> {
-    SINGLE_THREAD_ASSURANCE.lock().unwrap()
+    SINGLE_THREAD_ASSURANCE
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
 }
 
 #[test]
@@ -30,7 +34,7 @@ fn test_parse_i32() {
         matches!(value.err().unwrap(), EnvarError::NotSet(varname) if varname == "T1_TEST_I32")
     );
 
-    let _ = unsafe { std::env::set_var("T1_TEST_I32", "123") };
+    unsafe { std::env::set_var("T1_TEST_I32", "123") };
 
     static VAR2: Envar<i32> = Envar::on_demand("T1_TEST_I32", || EnvarDef::Default(123));
 
@@ -106,6 +110,62 @@ fn test_all_integer_types() {
 }
 
 #[test]
+fn test_wide_and_nonzero_integer_types() {
+    let _lock = get_test_lock();
+
+    clear_env_var("TEST_U128");
+    static VAR_U128: Envar<u128> = Envar::on_demand("TEST_U128", || EnvarDef::Unset);
+    set_env_var("TEST_U128", "340282366920938463463374607431768211455");
+    assert_eq!(VAR_U128.value().unwrap(), u128::MAX);
+
+    clear_env_var("TEST_I128");
+    static VAR_I128: Envar<i128> = Envar::on_demand("TEST_I128", || EnvarDef::Unset);
+    set_env_var("TEST_I128", "-170141183460469231731687303715884105728");
+    assert_eq!(VAR_I128.value().unwrap(), i128::MIN);
+
+    clear_env_var("TEST_NONZERO_U32");
+    static VAR_NONZERO_U32: Envar<std::num::NonZeroU32> =
+        Envar::on_demand("TEST_NONZERO_U32", || EnvarDef::Unset);
+    set_env_var("TEST_NONZERO_U32", "8");
+    assert_eq!(VAR_NONZERO_U32.value().unwrap().get(), 8);
+
+    clear_env_var("TEST_NONZERO_I64");
+    static VAR_NONZERO_I64: Envar<std::num::NonZeroI64> =
+        Envar::on_demand("TEST_NONZERO_I64", || EnvarDef::Unset);
+    set_env_var("TEST_NONZERO_I64", "0");
+    match VAR_NONZERO_I64.value() {
+        Err(EnvarError::ParseError {
+            varname,
+            typename,
+            value,
+            ..
+        }) => {
+            assert_eq!(varname, "TEST_NONZERO_I64");
+            assert_eq!(typename, "NonZeroI64");
+            assert_eq!(value, "0");
+        }
+        other => panic!("expected ParseError, got {:?}", other),
+    }
+    clear_env_var("TEST_NONZERO_I64");
+
+    #[cfg(feature = "docgen")]
+    {
+        let schema: serde_json::Value = serde_json::from_str(&crate::docgen::json_schema_of(&[
+            &VAR_NONZERO_U32,
+            &VAR_NONZERO_I64,
+        ]))
+        .unwrap();
+        let properties = &schema["properties"];
+        assert_eq!(properties["TEST_NONZERO_U32"]["minimum"], 1);
+        assert_eq!(
+            properties["TEST_NONZERO_I64"]["not"],
+            serde_json::json!({"const": 0})
+        );
+    }
+}
+
+#[test]
+#[allow(clippy::approx_constant)]
 fn test_float_types() {
     let _lock = get_test_lock();
 
@@ -155,12 +215,7 @@ fn test_bool_type() {
     ];
     for value in &true_values {
         set_env_var("TEST_BOOL", value);
-        assert_eq!(
-            VAR_BOOL.value().unwrap(),
-            true,
-            "Failed for value: {}",
-            value
-        );
+        assert!(VAR_BOOL.value().unwrap(), "Failed for value: {}", value);
     }
 
     // Test false alternatives
@@ -169,21 +224,16 @@ fn test_bool_type() {
     ];
     for value in &false_values {
         set_env_var("TEST_BOOL", value);
-        assert_eq!(
-            VAR_BOOL.value().unwrap(),
-            false,
-            "Failed for value: {}",
-            value
-        );
+        assert!(!VAR_BOOL.value().unwrap(), "Failed for value: {}", value);
     }
 
     // Test empty string (should be false)
     set_env_var("TEST_BOOL", "");
-    assert_eq!(VAR_BOOL.value().unwrap(), false);
+    assert!(!VAR_BOOL.value().unwrap());
 
     // Test whitespace only (should be false)
     set_env_var("TEST_BOOL", "   ");
-    assert_eq!(VAR_BOOL.value().unwrap(), false);
+    assert!(!VAR_BOOL.value().unwrap());
 
     // Test invalid bool value
     set_env_var("TEST_BOOL", "invalid");
@@ -292,30 +342,37 @@ fn test_envar_name() {
 }
 
 // Define test configurations for ListEnvar
+#[cfg(feature = "list")]
 #[derive(Clone)]
 struct CommaConfig;
+#[cfg(feature = "list")]
 impl ListEnvarConfig for CommaConfig {
     const SEP: &'static str = ",";
     const FILTER_EMPTY_STR: bool = true;
     const FILTER_WHITESPACE: bool = true;
 }
 
+#[cfg(feature = "list")]
 #[derive(Clone)]
 struct SemicolonConfig;
+#[cfg(feature = "list")]
 impl ListEnvarConfig for SemicolonConfig {
     const SEP: &'static str = ";";
     const FILTER_EMPTY_STR: bool = false;
     const FILTER_WHITESPACE: bool = false;
 }
 
+#[cfg(feature = "list")]
 #[derive(Clone)]
 struct NoFilterConfig;
+#[cfg(feature = "list")]
 impl ListEnvarConfig for NoFilterConfig {
     const SEP: &'static str = ",";
     const FILTER_EMPTY_STR: bool = false;
     const FILTER_WHITESPACE: bool = false;
 }
 
+#[cfg(feature = "list")]
 #[test]
 fn test_envar_list_basic() {
     let _lock = get_test_lock();
@@ -334,6 +391,7 @@ fn test_envar_list_basic() {
     assert_eq!(result[4], 5);
 }
 
+#[cfg(feature = "list")]
 #[test]
 fn test_envar_list_string() {
     let _lock = get_test_lock();
@@ -350,6 +408,7 @@ fn test_envar_list_string() {
     assert_eq!(result[2], "test");
 }
 
+#[cfg(feature = "list")]
 #[test]
 fn test_envar_list_filtering() {
     let _lock = get_test_lock();
@@ -381,6 +440,7 @@ fn test_envar_list_filtering() {
     assert_eq!(result[4], "test");
 }
 
+#[cfg(feature = "list")]
 #[test]
 fn test_envar_list_different_separator() {
     let _lock = get_test_lock();
@@ -397,6 +457,7 @@ fn test_envar_list_different_separator() {
     assert_eq!(result[2], 30);
 }
 
+#[cfg(feature = "list")]
 #[test]
 fn test_envar_list_empty() {
     let _lock = get_test_lock();
@@ -410,6 +471,7 @@ fn test_envar_list_empty() {
     assert_eq!(result.len(), 0);
 }
 
+#[cfg(feature = "list")]
 #[test]
 fn test_envar_list_parse_error() {
     let _lock = get_test_lock();
@@ -432,6 +494,7 @@ fn test_envar_list_parse_error() {
     }
 }
 
+#[cfg(feature = "list")]
 #[test]
 fn test_envar_list_display() {
     let _lock = get_test_lock();
@@ -445,6 +508,7 @@ fn test_envar_list_display() {
     assert_eq!(format!("{}", result), "hello,world,test");
 }
 
+#[cfg(feature = "list")]
 #[test]
 fn test_envar_list_debug() {
     let _lock = get_test_lock();
@@ -469,6 +533,7 @@ fn test_envar_def_to_option() {
     assert_eq!(unset_def.to_option(), None);
 }
 
+#[cfg(feature = "option")]
 #[test]
 fn test_envar_option() {
     let _lock = get_test_lock();
@@ -483,3 +548,4507 @@ fn test_envar_option() {
     assert!(std::env::var("TEST_OPTION2").is_err());
     assert_eq!(VAR_OPTION2.value().unwrap(), Some(42));
 }
+
+#[derive(Clone, Debug, PartialEq, typed_env_derive::EnvarParse)]
+struct TenantId(String);
+
+fn validate_pool_size(size: &u32) -> Result<(), String> {
+    if *size == 0 {
+        return Err("pool size must be positive".to_string());
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq, typed_env_derive::EnvarParse)]
+#[envar(validate = validate_pool_size)]
+struct PoolSize(u32);
+
+#[test]
+fn test_derive_envar_parse_newtype() {
+    let _lock = get_test_lock();
+
+    set_env_var("TEST_DERIVE_TENANT", "acme");
+    static TENANT: Envar<TenantId> = Envar::on_demand("TEST_DERIVE_TENANT", || EnvarDef::Unset);
+    assert_eq!(TENANT.value().unwrap(), TenantId("acme".to_string()));
+
+    static POOL: Envar<PoolSize> = Envar::on_demand("TEST_DERIVE_POOL", || EnvarDef::Unset);
+    set_env_var("TEST_DERIVE_POOL", "16");
+    assert_eq!(POOL.value().unwrap(), PoolSize(16));
+
+    set_env_var("TEST_DERIVE_POOL", "zero");
+    match POOL.value().err().unwrap() {
+        EnvarError::ParseError { typename, .. } => assert_eq!(typename, "u32"),
+        _ => panic!("Expected ParseError"),
+    }
+
+    set_env_var("TEST_DERIVE_POOL", "0");
+    match POOL.value().err().unwrap() {
+        EnvarError::ParseError {
+            varname, reason, ..
+        } => {
+            assert_eq!(varname, "TEST_DERIVE_POOL");
+            assert_eq!(reason.as_str(), "pool size must be positive");
+        }
+        _ => panic!("Expected ParseError"),
+    }
+}
+
+#[derive(Clone, Debug, typed_env_derive::EnvarParse)]
+struct ApiToken(crate::Secret<String>);
+
+#[derive(Clone, Debug, PartialEq, typed_env_derive::EnvarParse)]
+struct DataDir(std::path::PathBuf);
+
+#[test]
+fn test_derive_envar_parse_newtype_forwards_inner() {
+    use crate::{EnvarParse, EnvarParser};
+    let _lock = get_test_lock();
+
+    const { assert!(<EnvarParser<ApiToken> as EnvarParse<ApiToken>>::SENSITIVE) };
+    const { assert!(!<EnvarParser<TenantId> as EnvarParse<TenantId>>::SENSITIVE) };
+    const { assert!(<EnvarParser<DataDir> as EnvarParse<DataDir>>::ACCEPTS_NON_UNICODE) };
+
+    static TOKEN: Envar<ApiToken> = Envar::on_demand("TEST_DERIVE_TOKEN", || EnvarDef::Unset);
+    set_env_var("TEST_DERIVE_TOKEN", "s3cr3t");
+    assert_eq!(TOKEN.explain().raw.as_deref(), Some("***"));
+    clear_env_var("TEST_DERIVE_TOKEN");
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        static DIR: Envar<DataDir> = Envar::on_demand("TEST_DERIVE_DATA_DIR", || EnvarDef::Unset);
+        let invalid = std::ffi::OsStr::from_bytes(b"/srv/caf\xe9");
+        unsafe { std::env::set_var("TEST_DERIVE_DATA_DIR", invalid) };
+        assert_eq!(DIR.value().unwrap().0.as_os_str(), invalid);
+        clear_env_var("TEST_DERIVE_DATA_DIR");
+    }
+}
+
+#[test]
+fn test_collect_prefix() {
+    let _lock = get_test_lock();
+
+    set_env_var("TEST_PLUGIN_ALPHA", "1");
+    set_env_var("TEST_PLUGIN_BETA", "2");
+    set_env_var("TEST_PLUGINX", "3");
+    let plugins = crate::collect_prefix::<u32>("TEST_PLUGIN_").unwrap();
+    assert_eq!(plugins.prefix(), "TEST_PLUGIN_");
+    assert_eq!(plugins.len(), 2);
+    assert_eq!(plugins["ALPHA"], 1);
+    assert_eq!(plugins["BETA"], 2);
+    assert_eq!(plugins.full_name("ALPHA"), "TEST_PLUGIN_ALPHA");
+
+    set_env_var("TEST_PLUGIN_GAMMA", "not_a_number");
+    match crate::collect_prefix::<u32>("TEST_PLUGIN_").err().unwrap() {
+        EnvarError::ParseError { varname, .. } => assert_eq!(varname, "TEST_PLUGIN_GAMMA"),
+        _ => panic!("Expected ParseError"),
+    }
+    clear_env_var("TEST_PLUGIN_GAMMA");
+}
+
+#[test]
+fn test_verbosity() {
+    let _lock = get_test_lock();
+
+    static VERBOSE: Envar<crate::Verbosity> =
+        Envar::on_demand("TEST_VERBOSITY", || EnvarDef::Default(crate::Verbosity(0)));
+    clear_env_var("TEST_VERBOSITY");
+    assert!(VERBOSE.value().unwrap().is_quiet());
+
+    for (raw, level) in [
+        ("vvv", 3),
+        ("V", 1),
+        ("4", 4),
+        ("Debug", 4),
+        ("warning", 2),
+        ("", 0),
+    ] {
+        set_env_var("TEST_VERBOSITY", raw);
+        assert_eq!(
+            VERBOSE.value().unwrap().level(),
+            level,
+            "Failed for value: {}",
+            raw
+        );
+    }
+
+    set_env_var("TEST_VERBOSITY", "loud");
+    match VERBOSE.value().err().unwrap() {
+        EnvarError::ParseError { typename, .. } => assert_eq!(typename, "Verbosity"),
+        _ => panic!("Expected ParseError"),
+    }
+}
+
+#[cfg(feature = "list")]
+#[test]
+fn test_list_presets() {
+    let _lock = get_test_lock();
+
+    static COMMA: Envar<ListEnvar<i32, crate::CommaList>> =
+        Envar::on_demand("TEST_PRESET_COMMA", || EnvarDef::Unset);
+    set_env_var("TEST_PRESET_COMMA", "1, 2,,3");
+    assert_eq!(*COMMA.value().unwrap(), vec![1, 2, 3]);
+
+    static COLON: Envar<ListEnvar<String, crate::ColonList>> =
+        Envar::on_demand("TEST_PRESET_COLON", || EnvarDef::Unset);
+    set_env_var("TEST_PRESET_COLON", "/usr/bin::/bin");
+    assert_eq!(*COLON.value().unwrap(), vec!["/usr/bin", "/bin"]);
+
+    static SEMICOLON: Envar<ListEnvar<bool, crate::SemicolonList>> =
+        Envar::on_demand("TEST_PRESET_SEMICOLON", || EnvarDef::Unset);
+    set_env_var("TEST_PRESET_SEMICOLON", "yes; off;");
+    assert_eq!(*SEMICOLON.value().unwrap(), vec![true, false]);
+
+    static WHITESPACE: Envar<ListEnvar<String, crate::WhitespaceList>> =
+        Envar::on_demand("TEST_PRESET_WHITESPACE", || EnvarDef::Unset);
+    set_env_var("TEST_PRESET_WHITESPACE", " a\tb\n  c ");
+    let result = WHITESPACE.value().unwrap();
+    assert_eq!(*result, vec!["a", "b", "c"]);
+    assert_eq!(format!("{}", result), "a b c");
+}
+
+#[cfg(feature = "list")]
+#[test]
+fn test_char_sep_list() {
+    let _lock = get_test_lock();
+
+    static PIPES: Envar<ListEnvar<i32, crate::CharSep<'|'>>> =
+        Envar::on_demand("TEST_CHAR_SEP", || EnvarDef::Unset);
+    set_env_var("TEST_CHAR_SEP", "1| 2||3");
+    let result = PIPES.value().unwrap();
+    assert_eq!(*result, vec![1, 2, 3]);
+    assert_eq!(format!("{}", result), "1|2|3");
+
+    static ARROWS: Envar<ListEnvar<String, crate::CharSep<'→', false, false>>> =
+        Envar::on_demand("TEST_CHAR_SEP_UNICODE", || EnvarDef::Unset);
+    set_env_var("TEST_CHAR_SEP_UNICODE", "a→→b");
+    assert_eq!(*ARROWS.value().unwrap(), vec!["a", "", "b"]);
+}
+
+#[test]
+fn test_max_value_len() {
+    let _lock = get_test_lock();
+
+    static CAPPED: Envar<String> =
+        Envar::on_demand("TEST_MAX_LEN", || EnvarDef::Unset).with_max_len(4);
+    set_env_var("TEST_MAX_LEN", "abcd");
+    assert_eq!(CAPPED.value().unwrap(), "abcd");
+
+    set_env_var("TEST_MAX_LEN", "abcde");
+    match CAPPED.value().err().unwrap() {
+        EnvarError::TooLong {
+            varname,
+            len,
+            max_len,
+        } => {
+            assert_eq!(varname, "TEST_MAX_LEN");
+            assert_eq!(len, 5);
+            assert_eq!(max_len, 4);
+        }
+        _ => panic!("Expected TooLong"),
+    }
+
+    static GLOBAL: Envar<String> = Envar::on_demand("TEST_MAX_LEN_GLOBAL", || EnvarDef::Unset);
+    set_env_var("TEST_MAX_LEN_GLOBAL", "abcdef");
+    crate::set_max_value_len(3);
+    let result = GLOBAL.value();
+    crate::set_max_value_len(usize::MAX);
+    assert!(matches!(
+        result,
+        Err(EnvarError::TooLong { max_len: 3, .. })
+    ));
+    assert_eq!(GLOBAL.value().unwrap(), "abcdef");
+}
+
+#[test]
+fn test_reentrant_default_factory() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    let _lock = get_test_lock();
+
+    static DEPTH: AtomicUsize = AtomicUsize::new(0);
+    static REENTRANT: Envar<i32> = Envar::on_demand("TEST_REENTRANT", || {
+        if DEPTH.fetch_add(1, Ordering::SeqCst) == 0 {
+            EnvarDef::Default(REENTRANT.value().unwrap() + 1)
+        } else {
+            EnvarDef::Default(41)
+        }
+    });
+    clear_env_var("TEST_REENTRANT");
+    assert_eq!(REENTRANT.value().unwrap(), 42);
+    assert_eq!(REENTRANT.value().unwrap(), 42);
+
+    static INNER: Envar<i32> = Envar::on_startup("TEST_REENTRANT_NESTED", || EnvarDef::Default(1));
+    static OUTER: Envar<i32> = Envar::on_demand("TEST_REENTRANT_NESTED", || {
+        EnvarDef::Default(INNER.value().unwrap() + 1)
+    });
+    clear_env_var("TEST_REENTRANT_NESTED");
+    assert_eq!(OUTER.value().unwrap(), 2);
+}
+
+#[test]
+fn test_poisoned_lock_recovery() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    let _lock = get_test_lock();
+
+    static PANIC_ON_CLONE: AtomicBool = AtomicBool::new(false);
+
+    #[derive(Debug, PartialEq)]
+    struct Fragile(i32);
+
+    impl Clone for Fragile {
+        fn clone(&self) -> Self {
+            if PANIC_ON_CLONE.load(Ordering::SeqCst) {
+                panic!("clone failed");
+            }
+            Fragile(self.0)
+        }
+    }
+
+    impl crate::EnvarParse<Fragile> for crate::EnvarParser<Fragile> {
+        fn parse(
+            varname: std::borrow::Cow<'static, str>,
+            value: &str,
+        ) -> Result<Fragile, EnvarError> {
+            crate::EnvarParser::<i32>::parse(varname, value).map(Fragile)
+        }
+    }
+
+    static FRAGILE: Envar<Fragile> = Envar::on_demand("TEST_POISON", || EnvarDef::Unset);
+    set_env_var("TEST_POISON", "7");
+    assert_eq!(FRAGILE.value().unwrap(), Fragile(7));
+
+    PANIC_ON_CLONE.store(true, Ordering::SeqCst);
+    assert!(std::panic::catch_unwind(|| FRAGILE.value()).is_err());
+    PANIC_ON_CLONE.store(false, Ordering::SeqCst);
+
+    assert_eq!(FRAGILE.value().unwrap(), Fragile(7));
+    set_env_var("TEST_POISON", "8");
+    assert_eq!(FRAGILE.value().unwrap(), Fragile(8));
+}
+
+#[test]
+fn test_error_reason_panicking_provider() {
+    let reason = crate::ErrorReason::new(|| panic!("provider failed"));
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        reason.as_str().to_string()
+    }))
+    .is_err());
+    assert_eq!(reason.as_str(), "<error reason unavailable>");
+}
+
+#[test]
+fn test_message_formatter() {
+    let _lock = get_test_lock();
+
+    struct French;
+    impl crate::MessageFormatter for French {
+        fn format(&self, error: &EnvarError, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match error {
+                EnvarError::NotSet(varname) => {
+                    write!(f, "La variable d'environnement {varname} n'est pas définie")
+                }
+                other => crate::DefaultMessageFormatter.format(other, f),
+            }
+        }
+
+        fn format_report(
+            &self,
+            report: &crate::FailureReport<'_>,
+            f: &mut std::fmt::Formatter<'_>,
+        ) -> std::fmt::Result {
+            write!(f, "erreur : ")?;
+            self.format(report.error, f)?;
+            writeln!(f, " (attendu : {})", report.expected)
+        }
+    }
+
+    let error = EnvarError::NotSet("TEST_MESSAGES".into());
+    assert_eq!(
+        error.to_string(),
+        "Environment variable TEST_MESSAGES is not set"
+    );
+
+    static MESSAGES: Envar<u16> = Envar::on_demand("TEST_MESSAGES", || EnvarDef::Unset);
+    crate::set_message_formatter(French);
+    let translated = error.to_string();
+    let fallback = EnvarError::TryDefault("TEST_MESSAGES".into()).to_string();
+    let summary = crate::EnvarErrors {
+        errors: vec![error.clone()],
+    }
+    .to_string();
+    let report = MESSAGES.failure_report(&error);
+    crate::reset_message_formatter();
+
+    // summaries and reports go through the installed formatter too
+    assert_eq!(
+        summary,
+        "1 environment variable(s) failed validation:\n  - La variable d'environnement TEST_MESSAGES n'est pas définie\n"
+    );
+    assert_eq!(
+        report,
+        "erreur : La variable d'environnement TEST_MESSAGES n'est pas définie (attendu : u16)\n"
+    );
+
+    assert_eq!(
+        translated,
+        "La variable d'environnement TEST_MESSAGES n'est pas définie"
+    );
+    assert_eq!(
+        fallback,
+        "Environment variable TEST_MESSAGES is not set and default factory returned None"
+    );
+    assert_eq!(
+        error.to_string(),
+        "Environment variable TEST_MESSAGES is not set"
+    );
+}
+
+#[cfg(feature = "registry")]
+#[test]
+fn test_envar_stats() {
+    let _lock = get_test_lock();
+
+    static HOT: Envar<u32> = Envar::on_demand("TEST_STATS_HOT", || EnvarDef::Unset);
+    static COLD: Envar<u32> = Envar::on_startup("TEST_STATS_COLD", || EnvarDef::Default(1));
+    HOT.register();
+    COLD.register().register();
+
+    assert_eq!(HOT.stats(), crate::EnvarStats::default());
+
+    set_env_var("TEST_STATS_HOT", "1");
+    HOT.value().unwrap();
+    HOT.value().unwrap();
+    set_env_var("TEST_STATS_HOT", "oops");
+    assert!(HOT.value().is_err());
+    set_env_var("TEST_STATS_HOT", "2");
+    HOT.value().unwrap();
+
+    let stats = HOT.stats();
+    assert_eq!(stats.reads, 4);
+    assert_eq!(stats.cache_hits, 1);
+    assert_eq!(stats.parse_failures, 1);
+    assert!(stats.last_read.unwrap() <= std::time::SystemTime::now());
+    assert!(stats.last_resolved.unwrap() >= stats.last_read.unwrap());
+    assert!(stats.last_change.is_some());
+
+    clear_env_var("TEST_STATS_COLD");
+    COLD.value().unwrap();
+
+    let report = crate::registry::stats_report();
+    let names: Vec<&str> = report
+        .entries
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .filter(|name| name.starts_with("TEST_STATS_"))
+        .collect();
+    assert_eq!(names, vec!["TEST_STATS_HOT", "TEST_STATS_COLD"]);
+    assert!(report
+        .to_string()
+        .contains("TEST_STATS_HOT: reads=4 cache_hits=1 parse_failures=1"));
+
+    // cache hits only bump the counters, and the read time to the second
+    HOT.value().unwrap();
+    assert_eq!(HOT.stats().last_resolved, stats.last_resolved);
+    assert!(HOT.stats().last_read >= stats.last_read);
+    assert_eq!(HOT.stats().cache_hits, 2);
+
+    // a cached failure is not parsed, nor counted, again
+    set_env_var("TEST_STATS_HOT", "oops");
+    assert!(HOT.value().is_err());
+    assert!(HOT.value().is_err());
+    assert_eq!(HOT.stats().parse_failures, 2);
+    clear_env_var("TEST_STATS_HOT");
+}
+
+#[cfg(feature = "registry")]
+#[test]
+fn test_redundant_defaults() {
+    let _lock = get_test_lock();
+
+    static WORKERS: Envar<u32> =
+        Envar::on_demand("TEST_REDUNDANT_WORKERS", || EnvarDef::Default(4));
+    static HOST: Envar<String> = Envar::on_demand("TEST_REDUNDANT_HOST", || {
+        EnvarDef::Default("localhost".to_string())
+    });
+    static REQUIRED: Envar<u32> = Envar::on_demand("TEST_REDUNDANT_REQUIRED", || EnvarDef::Unset);
+    WORKERS.register();
+    HOST.register();
+    REQUIRED.register();
+
+    set_env_var("TEST_REDUNDANT_WORKERS", "4");
+    set_env_var("TEST_REDUNDANT_HOST", "example.com");
+    set_env_var("TEST_REDUNDANT_REQUIRED", "4");
+    assert!(WORKERS.is_explicit_default());
+    assert!(!HOST.is_explicit_default());
+    assert!(!REQUIRED.is_explicit_default());
+
+    set_env_var("TEST_REDUNDANT_HOST", "localhost");
+    let redundant: Vec<String> = crate::registry::redundant_defaults()
+        .into_iter()
+        .filter(|name| name.starts_with("TEST_REDUNDANT_"))
+        .collect();
+    assert_eq!(
+        redundant,
+        vec!["TEST_REDUNDANT_WORKERS", "TEST_REDUNDANT_HOST"]
+    );
+
+    clear_env_var("TEST_REDUNDANT_WORKERS");
+    assert!(!WORKERS.is_explicit_default());
+    // compared in raw form, so other spellings of the default count too
+    set_env_var("TEST_REDUNDANT_WORKERS", "04");
+    assert!(WORKERS.is_explicit_default());
+    clear_env_var("TEST_REDUNDANT_WORKERS");
+
+    // a `Secret` has no raw form, so it is never reported as redundant
+    static TOKEN: Envar<crate::Secret<String>> = Envar::on_demand("TEST_REDUNDANT_TOKEN", || {
+        EnvarDef::Default(crate::Secret::new("dev".to_string()))
+    });
+    set_env_var("TEST_REDUNDANT_TOKEN", "prod-token");
+    assert!(!TOKEN.is_explicit_default());
+    clear_env_var("TEST_REDUNDANT_TOKEN");
+    clear_env_var("TEST_REDUNDANT_HOST");
+    clear_env_var("TEST_REDUNDANT_REQUIRED");
+}
+
+#[test]
+fn test_explain() {
+    let _lock = get_test_lock();
+
+    static PORT: Envar<u16> = Envar::on_demand("TEST_EXPLAIN_PORT", || EnvarDef::Default(8080));
+    clear_env_var("TEST_EXPLAIN_PORT");
+    let explanation = PORT.explain();
+    assert_eq!(explanation.lookups.len(), 1);
+    assert_eq!(explanation.lookups[0].source, "process environment");
+    assert_eq!(explanation.raw, None);
+    assert_eq!(explanation.parser, "u16");
+    assert!(explanation.default_applied);
+    assert_eq!(explanation.outcome, Ok("8080".to_string()));
+    assert_eq!(PORT.stats().reads, 0);
+
+    set_env_var("TEST_EXPLAIN_PORT", "99999");
+    let explanation = PORT.explain();
+    assert_eq!(explanation.raw.as_deref(), Some("99999"));
+    assert!(!explanation.default_applied);
+    assert!(explanation.outcome.is_err());
+    assert!(explanation
+        .to_string()
+        .contains("TEST_EXPLAIN_PORT in process environment: \"99999\""));
+
+    static FROZEN: Envar<u16> = Envar::on_startup("TEST_EXPLAIN_FROZEN", || EnvarDef::Unset);
+    set_env_var("TEST_EXPLAIN_FROZEN", "1");
+    FROZEN.value().unwrap();
+    set_env_var("TEST_EXPLAIN_FROZEN", "2");
+    let explanation = FROZEN.explain();
+    assert!(explanation.cached);
+    assert_eq!(explanation.outcome, Ok("1".to_string()));
+}
+
+#[test]
+fn test_override_layers() {
+    let _lock = get_test_lock();
+
+    static MODE: Envar<String> =
+        Envar::on_demand("TEST_LAYER_MODE", || EnvarDef::Default("serve".to_string()));
+    static DERIVED: Envar<bool> = Envar::on_demand("TEST_LAYER_DERIVED", || {
+        EnvarDef::Default(MODE.value().unwrap() == "migrate")
+    });
+    clear_env_var("TEST_LAYER_MODE");
+    clear_env_var("TEST_LAYER_DERIVED");
+    assert_eq!(MODE.value().unwrap(), "serve");
+    assert!(!DERIVED.value().unwrap());
+
+    let depth = crate::layer_depth();
+    let migrate = crate::push_layer([("TEST_LAYER_MODE", "migrate")]);
+    assert_eq!(crate::layer_depth(), depth + 1);
+    assert_eq!(MODE.value().unwrap(), "migrate");
+    // the cached default is re-resolved after a push
+    assert!(DERIVED.value().unwrap());
+
+    set_env_var("TEST_LAYER_MODE", "from-env");
+    let maintenance = crate::push_layer([("TEST_LAYER_MODE", "maintenance")]);
+    assert_eq!(MODE.value().unwrap(), "maintenance");
+    assert_eq!(MODE.explain().lookups[0].source, "override layer");
+    set_env_var("TEST_LAYER_PREFIX_A", "1");
+    crate::push_layer([("TEST_LAYER_PREFIX_A", "2"), ("TEST_LAYER_PREFIX_B", "3")]).keep();
+    let collected = crate::collect_prefix::<u32>("TEST_LAYER_PREFIX_").unwrap();
+    assert_eq!((collected["A"], collected["B"]), (2, 3));
+    assert!(crate::pop_layer()
+        .unwrap()
+        .contains_key("TEST_LAYER_PREFIX_B"));
+
+    // a guard removes its own layer, even when it is not the topmost one
+    let scratch = crate::push_layer([("TEST_LAYER_SCRATCH", "1")]);
+    drop(migrate);
+    assert_eq!(MODE.value().unwrap(), "maintenance");
+    assert_eq!(
+        maintenance.pop().unwrap().get("TEST_LAYER_MODE").unwrap(),
+        "maintenance"
+    );
+    assert_eq!(crate::pop_layer().unwrap()["TEST_LAYER_SCRATCH"], "1");
+    // its layer was already popped, so dropping the guard removes nothing
+    let outer = crate::push_layer([("TEST_LAYER_MODE", "outer")]);
+    drop(scratch);
+    assert_eq!(MODE.value().unwrap(), "outer");
+    drop(outer);
+    assert_eq!(crate::layer_depth(), depth);
+    assert_eq!(MODE.value().unwrap(), "from-env");
+    clear_env_var("TEST_LAYER_MODE");
+    assert!(!DERIVED.value().unwrap());
+}
+
+/// A type without a raw form, whose defaults cannot be written out
+#[derive(Clone, Debug)]
+struct Unrendered;
+
+impl crate::EnvarParse<Unrendered> for crate::EnvarParser<Unrendered> {
+    fn parse(
+        _varname: std::borrow::Cow<'static, str>,
+        _value: &str,
+    ) -> Result<Unrendered, EnvarError> {
+        Ok(Unrendered)
+    }
+}
+
+#[cfg(all(feature = "docgen", feature = "list"))]
+#[test]
+fn test_docgen_kubernetes() {
+    static K8S_PORT: Envar<u16> = Envar::on_demand("TEST_K8S_PORT", || EnvarDef::Default(8080));
+    static K8S_HOSTS: Envar<ListEnvar<String, crate::CommaList>> =
+        Envar::on_demand("TEST_K8S_HOSTS", || {
+            EnvarDef::Default(ListEnvar::new(vec!["a".to_string(), "b".to_string()]))
+        });
+    static K8S_URL: Envar<String> = Envar::on_demand("TEST_K8S_URL", || EnvarDef::Unset);
+    static K8S_TOKEN: Envar<String> =
+        Envar::on_demand("TEST_K8S_TOKEN", || EnvarDef::Unset).sensitive();
+    static K8S_OPAQUE: Envar<Unrendered> =
+        Envar::on_demand("TEST_K8S_OPAQUE", || EnvarDef::Default(Unrendered));
+    K8S_PORT.register();
+    K8S_HOSTS.register();
+    K8S_URL.register();
+    K8S_TOKEN.register();
+    K8S_OPAQUE.register();
+
+    assert!(K8S_TOKEN.describe().sensitive);
+    assert!(!K8S_PORT.describe().sensitive);
+
+    let manifest = crate::docgen::kubernetes("myapp");
+    assert!(manifest.contains("  name: myapp-config\n"));
+    assert!(manifest.contains("  TEST_K8S_PORT: \"8080\"\n"));
+    assert!(manifest.contains("  TEST_K8S_HOSTS: \"a,b\"\n"));
+    assert!(manifest.contains("  # TEST_K8S_URL is required, provide it at deploy time\n"));
+    assert!(!manifest.contains("  TEST_K8S_URL:"));
+    assert!(manifest.contains("  # TEST_K8S_OPAQUE has a default that cannot be rendered\n"));
+    assert!(!manifest.contains("  TEST_K8S_TOKEN:"));
+    assert!(manifest.contains(
+        "  - name: TEST_K8S_TOKEN\n    valueFrom:\n      secretKeyRef:\n        name: myapp-secrets\n        key: TEST_K8S_TOKEN\n"
+    ));
+}
+
+#[cfg(feature = "docgen")]
+#[test]
+fn test_docgen_docker() {
+    static DOCKER_PORT: Envar<u16> = Envar::on_demand("TEST_DOCKER_PORT", || EnvarDef::Default(80));
+    static DOCKER_GREETING: Envar<String> = Envar::on_demand("TEST_DOCKER_GREETING", || {
+        EnvarDef::Default("hi \"$USER\"".to_string())
+    });
+    static DOCKER_URL: Envar<String> = Envar::on_demand("TEST_DOCKER_URL", || EnvarDef::Unset);
+    static DOCKER_TOKEN: Envar<String> =
+        Envar::on_demand("TEST_DOCKER_TOKEN", || EnvarDef::Default("dev".to_string())).sensitive();
+    DOCKER_PORT.register();
+    DOCKER_GREETING.register();
+    DOCKER_URL.register();
+    DOCKER_TOKEN.register();
+
+    let dockerfile = crate::docgen::dockerfile();
+    assert!(dockerfile.contains("ENV TEST_DOCKER_PORT=\"80\"\n"));
+    assert!(dockerfile.contains("ENV TEST_DOCKER_GREETING=\"hi \\\"\\$USER\\\"\"\n"));
+    assert!(dockerfile.contains("# TEST_DOCKER_URL is required, provide it at runtime\n"));
+    assert!(dockerfile.contains("# TEST_DOCKER_TOKEN is sensitive, provide it at runtime\n"));
+    assert!(!dockerfile.contains("dev"));
+
+    let compose = crate::docgen::docker_compose();
+    assert!(compose.starts_with("environment:\n"));
+    assert!(compose.contains("  TEST_DOCKER_PORT: \"${TEST_DOCKER_PORT:-80}\"\n"));
+    assert!(compose
+        .contains("  TEST_DOCKER_GREETING: \"${TEST_DOCKER_GREETING:-hi \\\"$$USER\\\"}\"\n"));
+    assert!(compose
+        .contains("  TEST_DOCKER_URL: \"${TEST_DOCKER_URL:?TEST_DOCKER_URL is required}\"\n"));
+    assert!(compose.contains(
+        "  # TEST_DOCKER_TOKEN is sensitive, provide it at runtime to replace its default\n"
+    ));
+    assert!(!compose.contains("  TEST_DOCKER_TOKEN:"));
+    assert!(!compose.contains("dev"));
+
+    static DOCKER_OPAQUE: Envar<Unrendered> =
+        Envar::on_demand("TEST_DOCKER_OPAQUE", || EnvarDef::Default(Unrendered));
+    DOCKER_OPAQUE.register();
+    let compose = crate::docgen::docker_compose();
+    assert!(compose.contains("  # TEST_DOCKER_OPAQUE has a default that cannot be rendered\n"));
+    assert!(!compose.contains("  TEST_DOCKER_OPAQUE:"));
+}
+
+#[cfg(feature = "docgen")]
+#[test]
+fn test_docgen_systemd() {
+    let _lock = get_test_lock();
+
+    static UNIT_RATE: Envar<String> =
+        Envar::on_demand("TEST_UNIT_RATE", || EnvarDef::Default("50%".to_string()));
+    static UNIT_URL: Envar<String> = Envar::on_demand("TEST_UNIT_URL", || EnvarDef::Unset);
+    static UNIT_TOKEN: Envar<String> =
+        Envar::on_demand("TEST_UNIT_TOKEN", || EnvarDef::Unset).sensitive();
+    UNIT_RATE.register();
+    UNIT_URL.register();
+    UNIT_TOKEN.register();
+
+    let unit = crate::docgen::systemd(&Default::default());
+    assert!(unit.starts_with("[Service]\n"));
+    assert!(!unit.contains("EnvironmentFile="));
+    assert!(unit.contains("Environment=\"TEST_UNIT_RATE=50%%\"\n"));
+    assert!(unit.contains("# TEST_UNIT_URL is required, provide it at runtime\n"));
+    assert!(unit.contains("# TEST_UNIT_TOKEN is sensitive, provide it at runtime\n"));
+
+    let unit = crate::docgen::systemd(&crate::docgen::SystemdOptions {
+        environment_file: Some("/etc/myapp/env".to_string()),
+        credentials_dir: Some("/etc/credstore".to_string()),
+    });
+    assert!(unit.contains("EnvironmentFile=-/etc/myapp/env\n"));
+    assert!(unit.contains("LoadCredential=TEST_UNIT_TOKEN:/etc/credstore/TEST_UNIT_TOKEN\n"));
+
+    // the unit points the variable at the loaded credential, as systemd would expand it
+    let pointer = unit
+        .lines()
+        .find_map(|line| line.strip_prefix("Environment=\"TEST_UNIT_TOKEN_FILE="))
+        .and_then(|path| path.strip_suffix('"'))
+        .unwrap();
+    assert_eq!(pointer, "%d/TEST_UNIT_TOKEN");
+    let credentials =
+        std::env::temp_dir().join(format!("typed-env-credentials-{}", std::process::id()));
+    std::fs::create_dir_all(&credentials).unwrap();
+    std::fs::write(credentials.join("TEST_UNIT_TOKEN"), "s3cr3t\n").unwrap();
+    set_env_var(
+        "TEST_UNIT_TOKEN_FILE",
+        &pointer.replace("%d", &credentials.to_string_lossy()),
+    );
+    assert_eq!(UNIT_TOKEN.value().unwrap(), "s3cr3t");
+
+    clear_env_var("TEST_UNIT_TOKEN_FILE");
+    std::fs::remove_dir_all(&credentials).unwrap();
+}
+
+#[cfg(all(feature = "docgen", feature = "list", feature = "option"))]
+#[test]
+fn test_docgen_json_schema() {
+    static SCHEMA_PORT: Envar<u16> =
+        Envar::on_demand("TEST_SCHEMA_PORT", || EnvarDef::Default(8080));
+    static SCHEMA_RATIO: Envar<Option<f64>> =
+        Envar::on_demand("TEST_SCHEMA_RATIO", || EnvarDef::Unset);
+    static SCHEMA_TAGS: Envar<ListEnvar<bool, crate::CommaList>> =
+        Envar::on_demand("TEST_SCHEMA_TAGS", || {
+            EnvarDef::Default(ListEnvar::new(vec![true]))
+        });
+    static SCHEMA_NAME: Envar<String> =
+        Envar::on_demand("TEST_SCHEMA_NAME", || EnvarDef::Default("a\"b".to_string())).sensitive();
+
+    let schema =
+        crate::docgen::json_schema_of(&[&SCHEMA_PORT, &SCHEMA_RATIO, &SCHEMA_TAGS, &SCHEMA_NAME]);
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&schema).unwrap(),
+        serde_json::json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "properties": {
+                "TEST_SCHEMA_PORT": {"type": "integer", "minimum": 0, "default": 8080},
+                "TEST_SCHEMA_RATIO": {"type": "number"},
+                "TEST_SCHEMA_TAGS": {"type": "array", "items": {"type": "boolean"}},
+                "TEST_SCHEMA_NAME": {"type": "string", "writeOnly": true}
+            },
+            "required": ["TEST_SCHEMA_RATIO"]
+        })
+    );
+}
+
+#[cfg(feature = "docgen")]
+#[test]
+fn test_derive_env_config_json_schema() {
+    #[derive(Debug, typed_env_derive::EnvConfig)]
+    #[envar(prefix = "TEST_CONFIG_SCHEMA_")]
+    #[allow(dead_code)]
+    struct SchemaConfig {
+        #[envar(description = "say \"hi\"\n")]
+        greeting: String,
+        #[envar(default = 0.5)]
+        ratio: f64,
+        #[envar(default = "a\"b")]
+        label: String,
+        #[envar(default = "hunter2", sensitive)]
+        password: String,
+    }
+
+    let schema: serde_json::Value = serde_json::from_str(&SchemaConfig::json_schema()).unwrap();
+    assert_eq!(
+        schema["properties"],
+        serde_json::json!({
+            "TEST_CONFIG_SCHEMA_GREETING": {"type": "string", "description": "say \"hi\"\n"},
+            "TEST_CONFIG_SCHEMA_RATIO": {"type": "number", "default": 0.5},
+            "TEST_CONFIG_SCHEMA_LABEL": {"type": "string", "default": "a\"b"},
+            "TEST_CONFIG_SCHEMA_PASSWORD": {"type": "string", "writeOnly": true}
+        })
+    );
+    assert_eq!(
+        schema["required"],
+        serde_json::json!(["TEST_CONFIG_SCHEMA_GREETING"])
+    );
+
+    let schema: serde_json::Value = serde_json::from_str(&SchemaConfig::json_schema_from(
+        &crate::EnvNamespace::new("OTHER_"),
+    ))
+    .unwrap();
+    assert_eq!(schema["required"], serde_json::json!(["OTHER_GREETING"]));
+}
+
+#[test]
+fn test_on_parse_error_policy() {
+    let _lock = get_test_lock();
+
+    static STRICT_PORT: Envar<u16> =
+        Envar::on_demand("TEST_LENIENT_STRICT_PORT", || EnvarDef::Default(8080));
+    static LENIENT_PORT: Envar<u16> =
+        Envar::on_demand("TEST_LENIENT_PORT", || EnvarDef::Default(8080))
+            .on_parse_error(OnParseError::UseDefaultAndWarn);
+    static LENIENT_STARTUP_PORT: Envar<u16> =
+        Envar::on_startup("TEST_LENIENT_STARTUP_PORT", || EnvarDef::Default(8080))
+            .on_parse_error(OnParseError::UseDefault);
+    static LENIENT_UNSET: Envar<u16> = Envar::on_demand("TEST_LENIENT_UNSET", || EnvarDef::Unset)
+        .on_parse_error(OnParseError::UseDefault);
+
+    set_env_var("TEST_LENIENT_STRICT_PORT", "80a");
+    set_env_var("TEST_LENIENT_PORT", "80a");
+    set_env_var("TEST_LENIENT_STARTUP_PORT", "80a");
+    set_env_var("TEST_LENIENT_UNSET", "80a");
+
+    assert!(matches!(
+        STRICT_PORT.value(),
+        Err(EnvarError::ParseError { .. })
+    ));
+    assert_eq!(LENIENT_PORT.value().unwrap(), 8080);
+    assert_eq!(LENIENT_PORT.stats().parse_failures, 1);
+    assert!(LENIENT_PORT.explain().default_applied);
+    assert_eq!(LENIENT_STARTUP_PORT.value().unwrap(), 8080);
+    // without a default there is nothing to fall back to
+    assert!(matches!(
+        LENIENT_UNSET.value(),
+        Err(EnvarError::ParseError { .. })
+    ));
+
+    set_env_var("TEST_LENIENT_PORT", "80");
+    assert_eq!(LENIENT_PORT.value().unwrap(), 80);
+
+    clear_env_var("TEST_LENIENT_STRICT_PORT");
+    clear_env_var("TEST_LENIENT_PORT");
+    clear_env_var("TEST_LENIENT_STARTUP_PORT");
+    clear_env_var("TEST_LENIENT_UNSET");
+}
+
+#[cfg(feature = "registry")]
+#[test]
+fn test_validate_collects_errors() {
+    let _lock = get_test_lock();
+
+    static VALID_PORT: Envar<u16> =
+        Envar::on_demand("TEST_VALIDATE_PORT", || EnvarDef::Default(8080));
+    static BAD_RATIO: Envar<f64> =
+        Envar::on_demand("TEST_VALIDATE_RATIO", || EnvarDef::Default(0.5));
+    static MISSING_URL: Envar<String> = Envar::on_demand("TEST_VALIDATE_URL", || EnvarDef::Unset);
+
+    set_env_var("TEST_VALIDATE_RATIO", "half");
+
+    assert!(crate::registry::validate(&[&VALID_PORT]).is_ok());
+    let errors = crate::registry::validate(&[&VALID_PORT, &BAD_RATIO, &MISSING_URL]).unwrap_err();
+    assert_eq!(errors.errors.len(), 2);
+    assert!(matches!(errors.errors[0], EnvarError::ParseError { .. }));
+    assert!(matches!(errors.errors[1], EnvarError::NotSet(_)));
+    let report = errors.to_string();
+    assert!(report.starts_with("2 environment variable(s) failed validation:"));
+    assert!(report.contains("TEST_VALIDATE_URL"));
+
+    clear_env_var("TEST_VALIDATE_RATIO");
+}
+
+#[cfg(feature = "registry")]
+#[test]
+fn test_validate_all_reports_every_registered_failure() {
+    let _lock = get_test_lock();
+
+    static ALL_PORT: Envar<u16> =
+        Envar::on_demand("TEST_VALIDATE_ALL_PORT", || EnvarDef::Default(8080));
+    static ALL_RATIO: Envar<f64> =
+        Envar::on_demand("TEST_VALIDATE_ALL_RATIO", || EnvarDef::Default(0.5));
+    static ALL_URL: Envar<String> = Envar::on_demand("TEST_VALIDATE_ALL_URL", || EnvarDef::Unset);
+    static UNREGISTERED: Envar<String> =
+        Envar::on_demand("TEST_VALIDATE_ALL_UNREGISTERED", || EnvarDef::Unset);
+
+    ALL_PORT.register();
+    ALL_RATIO.register();
+    ALL_URL.register();
+    set_env_var("TEST_VALIDATE_ALL_PORT", "eighty");
+    set_env_var("TEST_VALIDATE_ALL_RATIO", "half");
+
+    // other tests register into the same global registry, so only look at ours
+    let ours = |errors: &crate::EnvarErrors| -> Vec<String> {
+        errors
+            .errors
+            .iter()
+            .map(|e| e.to_string())
+            .filter(|e| e.contains("TEST_VALIDATE_ALL_"))
+            .collect()
+    };
+    let errors = crate::registry::validate_all().unwrap_err();
+    let failures = ours(&errors);
+    assert_eq!(failures.len(), 3, "{failures:?}");
+    assert!(failures[0].contains("TEST_VALIDATE_ALL_PORT"));
+    assert!(failures[1].contains("TEST_VALIDATE_ALL_RATIO"));
+    assert!(failures[2].contains("TEST_VALIDATE_ALL_URL"));
+    assert!(failures.iter().all(|e| !e.contains("UNREGISTERED")));
+    assert!(crate::registry::validate(&[&UNREGISTERED]).is_err());
+
+    set_env_var("TEST_VALIDATE_ALL_PORT", "80");
+    set_env_var("TEST_VALIDATE_ALL_RATIO", "0.25");
+    set_env_var("TEST_VALIDATE_ALL_URL", "postgres://localhost");
+    if let Err(errors) = crate::registry::validate_all() {
+        assert!(ours(&errors).is_empty(), "{:?}", ours(&errors));
+    }
+
+    clear_env_var("TEST_VALIDATE_ALL_PORT");
+    clear_env_var("TEST_VALIDATE_ALL_RATIO");
+    clear_env_var("TEST_VALIDATE_ALL_URL");
+}
+
+#[cfg(feature = "registry")]
+// only checks that the attribute expands; running it would validate the whole registry
+#[allow(dead_code)]
+#[typed_env_derive::main(register(MAIN_PORT))]
+fn validated_main() {}
+
+#[cfg(feature = "registry")]
+static MAIN_PORT: Envar<u16> = Envar::on_demand("TEST_MAIN_PORT", || EnvarDef::Default(8080));
+
+#[test]
+fn test_runtime_named_envar() {
+    let _lock = get_test_lock();
+
+    let tenant = "ACME";
+    let limit: Envar<u32> = Envar::on_demand_named(format!("TEST_TENANT_{}_LIMIT", tenant), || {
+        EnvarDef::Default(10)
+    });
+    assert_eq!(limit.name(), "TEST_TENANT_ACME_LIMIT");
+    assert_eq!(limit.value().unwrap(), 10);
+
+    set_env_var("TEST_TENANT_ACME_LIMIT", "25");
+    assert_eq!(limit.value().unwrap(), 25);
+
+    set_env_var("TEST_TENANT_ACME_LIMIT", "lots");
+    match limit.value() {
+        Err(EnvarError::ParseError { varname, .. }) => {
+            assert_eq!(varname, "TEST_TENANT_ACME_LIMIT")
+        }
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+
+    let required: Envar<String> =
+        Envar::on_startup_named(String::from("TEST_TENANT_ACME_URL"), || EnvarDef::Unset);
+    assert!(
+        matches!(required.value(), Err(EnvarError::NotSet(name)) if name == "TEST_TENANT_ACME_URL")
+    );
+
+    clear_env_var("TEST_TENANT_ACME_LIMIT");
+}
+
+#[cfg(feature = "docgen")]
+#[test]
+fn test_dynamic_envar_set() {
+    let _lock = get_test_lock();
+
+    let mut plugin = crate::registry::DynamicEnvarSet::new("thumbnailer");
+    let width = plugin.declare(
+        "TEST_PLUGIN_WIDTH",
+        EnvarDef::Default(128u32),
+        "thumbnail width in pixels",
+    );
+    let endpoint = plugin.declare::<String>(
+        "TEST_PLUGIN_ENDPOINT",
+        EnvarDef::Unset,
+        format!("endpoint of the {} service", plugin.owner()),
+    );
+    assert_eq!(plugin.owner(), "thumbnailer");
+    assert_eq!(plugin.entries().len(), 2);
+
+    assert_eq!(width.value().unwrap(), 128);
+    set_env_var("TEST_PLUGIN_WIDTH", "256");
+    assert_eq!(width.value().unwrap(), 256);
+    assert_eq!(
+        endpoint.describe().description,
+        Some("endpoint of the thumbnailer service")
+    );
+
+    let errors = plugin.validate().unwrap_err();
+    assert_eq!(errors.errors.len(), 1);
+    assert!(
+        matches!(&errors.errors[0], EnvarError::NotSet(name) if name == "TEST_PLUGIN_ENDPOINT")
+    );
+
+    let names: Vec<&str> = crate::registry::entries()
+        .iter()
+        .map(|e| e.name())
+        .collect();
+    assert!(names.contains(&"TEST_PLUGIN_WIDTH"));
+    assert!(crate::docgen::json_schema().contains("\"description\": \"thumbnail width in pixels\""));
+
+    clear_env_var("TEST_PLUGIN_WIDTH");
+}
+
+#[test]
+fn test_feature_flag() {
+    use crate::FeatureFlag;
+    let _lock = get_test_lock();
+
+    static NEW_UI: Envar<FeatureFlag> =
+        Envar::on_demand("TEST_FLAG_NEW_UI", || EnvarDef::Default(FeatureFlag::Off));
+    assert!(!NEW_UI.value().unwrap().is_enabled());
+
+    set_env_var("TEST_FLAG_NEW_UI", "On");
+    assert!(NEW_UI.value().unwrap().is_enabled_for("anyone"));
+
+    set_env_var("TEST_FLAG_NEW_UI", "allow: alice, bob");
+    let flag = NEW_UI.value().unwrap();
+    assert_eq!(flag.to_string(), "allow:alice,bob");
+    assert!(flag.is_enabled_for("bob"));
+    assert!(!flag.is_enabled_for("carol"));
+    assert!(!flag.is_enabled());
+
+    set_env_var("TEST_FLAG_NEW_UI", "25%");
+    let quarter = NEW_UI.value().unwrap();
+    assert_eq!(
+        quarter,
+        FeatureFlag::Percent {
+            percent: 25,
+            flag: "TEST_FLAG_NEW_UI".into()
+        }
+    );
+    let keys: Vec<String> = (0..1000).map(|i| format!("user{}", i)).collect();
+    let enabled = keys
+        .iter()
+        .filter(|key| quarter.is_enabled_for(key))
+        .count();
+    assert!(
+        (150..350).contains(&enabled),
+        "enabled for {} of 1000",
+        enabled
+    );
+    // raising the percentage keeps everyone already enabled
+    let half = FeatureFlag::Percent {
+        percent: 50,
+        flag: "TEST_FLAG_NEW_UI".into(),
+    };
+    assert!(keys
+        .iter()
+        .filter(|key| quarter.is_enabled_for(key))
+        .all(|key| half.is_enabled_for(key)));
+    // another flag at the same percentage reaches a different set of keys
+    let other = FeatureFlag::Percent {
+        percent: 25,
+        flag: "TEST_FLAG_OTHER".into(),
+    };
+    assert!(keys
+        .iter()
+        .any(|key| quarter.is_enabled_for(key) != other.is_enabled_for(key)));
+    assert!(FeatureFlag::Percent {
+        percent: 100,
+        flag: "TEST_FLAG_NEW_UI".into()
+    }
+    .is_enabled());
+
+    set_env_var("TEST_FLAG_NEW_UI", "120%");
+    assert!(matches!(
+        NEW_UI.value(),
+        Err(EnvarError::ParseError {
+            typename: "FeatureFlag",
+            ..
+        })
+    ));
+
+    clear_env_var("TEST_FLAG_NEW_UI");
+}
+
+#[test]
+fn test_deployment_env() {
+    use crate::DeploymentEnv;
+    let _lock = get_test_lock();
+
+    static DEPLOY_ENV: Envar<DeploymentEnv> = Envar::on_demand("TEST_DEPLOY_ENV", || {
+        EnvarDef::Default(DeploymentEnv::Development)
+    });
+    assert!(DEPLOY_ENV.value().unwrap().is_development());
+
+    for (raw, expected) in [
+        ("prod", DeploymentEnv::Production),
+        ("PRODUCTION", DeploymentEnv::Production),
+        (" staging ", DeploymentEnv::Staging),
+        ("ci", DeploymentEnv::Test),
+        ("local", DeploymentEnv::Development),
+        ("", DeploymentEnv::Development),
+    ] {
+        set_env_var("TEST_DEPLOY_ENV", raw);
+        assert_eq!(
+            DEPLOY_ENV.value().unwrap(),
+            expected,
+            "Failed for value: {}",
+            raw
+        );
+    }
+    assert_eq!(DeploymentEnv::Production.to_string(), "production");
+
+    set_env_var("TEST_DEPLOY_ENV", "qa-cluster");
+    assert!(matches!(
+        DEPLOY_ENV.value(),
+        Err(EnvarError::ParseError {
+            typename: "DeploymentEnv",
+            ..
+        })
+    ));
+
+    clear_env_var("TEST_DEPLOY_ENV");
+}
+
+#[cfg(feature = "registry")]
+#[test]
+fn test_required_when() {
+    use crate::DeploymentEnv;
+    let _lock = get_test_lock();
+
+    static STAGE: Envar<DeploymentEnv> = Envar::on_demand("TEST_REQ_WHEN_STAGE", || {
+        EnvarDef::Default(DeploymentEnv::Development)
+    });
+    static API_KEY: Envar<String> = Envar::on_demand("TEST_REQ_WHEN_API_KEY", || {
+        EnvarDef::Default("dev-key".to_string())
+    })
+    .required_when(|| matches!(STAGE.value(), Ok(DeploymentEnv::Production)));
+
+    assert!(crate::registry::validate(&[&API_KEY]).is_ok());
+
+    set_env_var("TEST_REQ_WHEN_STAGE", "prod");
+    let errors = crate::registry::validate(&[&API_KEY]).unwrap_err();
+    assert!(
+        matches!(&errors.errors[0], EnvarError::NotSet(name) if name == "TEST_REQ_WHEN_API_KEY")
+    );
+    // reading the value still falls back to the default
+    assert_eq!(API_KEY.value().unwrap(), "dev-key");
+
+    set_env_var("TEST_REQ_WHEN_API_KEY", "prod-key");
+    assert!(crate::registry::validate(&[&API_KEY]).is_ok());
+
+    clear_env_var("TEST_REQ_WHEN_STAGE");
+    clear_env_var("TEST_REQ_WHEN_API_KEY");
+}
+
+#[test]
+fn test_on_demand_caches_parse_failures() {
+    let _lock = get_test_lock();
+
+    static RETRIES: Envar<u8> = Envar::on_demand("TEST_NEG_CACHE_RETRIES", || EnvarDef::Default(3));
+    set_env_var("TEST_NEG_CACHE_RETRIES", "three");
+
+    let first = RETRIES.value().unwrap_err();
+    let second = RETRIES.value().unwrap_err();
+    assert_eq!(first.to_string(), second.to_string());
+    let stats = RETRIES.stats();
+    assert_eq!(stats.parse_failures, 1);
+    assert_eq!(stats.cache_hits, 1);
+
+    set_env_var("TEST_NEG_CACHE_RETRIES", "5");
+    assert_eq!(RETRIES.value().unwrap(), 5);
+    assert_eq!(RETRIES.stats().parse_failures, 1);
+
+    // failures that depend on more than the raw value are retried on every read
+    let dir = std::env::temp_dir().join(format!("typed-env-neg-cache-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    static SPOOL: Envar<std::path::PathBuf> =
+        Envar::on_demand("TEST_NEG_CACHE_SPOOL", || EnvarDef::Unset).must_exist();
+    set_env_var("TEST_NEG_CACHE_SPOOL", dir.to_str().unwrap());
+    assert!(SPOOL.value().is_err());
+    std::fs::create_dir(&dir).unwrap();
+    assert_eq!(SPOOL.value().unwrap(), dir);
+    const { assert!(!<crate::EnvarParser<crate::ResolvedAddrs> as crate::EnvarParse<_>>::DETERMINISTIC) };
+
+    std::fs::remove_dir(&dir).unwrap();
+    clear_env_var("TEST_NEG_CACHE_RETRIES");
+    clear_env_var("TEST_NEG_CACHE_SPOOL");
+}
+
+#[test]
+fn test_multiline_values() {
+    use crate::Multiline;
+    let _lock = get_test_lock();
+
+    #[cfg(feature = "list")]
+    {
+        static HOSTS: Envar<ListEnvar<String, crate::NewlineList>> =
+            Envar::on_demand("TEST_NEWLINE_HOSTS", || EnvarDef::Unset);
+        set_env_var(
+            "TEST_NEWLINE_HOSTS",
+            "a.example\r\nb.example\n\n  c.example  \n",
+        );
+        assert_eq!(
+            *HOSTS.value().unwrap(),
+            vec!["a.example", "b.example", "c.example"]
+        );
+        set_env_var("TEST_NEWLINE_HOSTS", "a.example\\nb.example\\r\\nc.example");
+        assert_eq!(
+            *HOSTS.value().unwrap(),
+            vec!["a.example", "b.example", "c.example"]
+        );
+    }
+
+    static CERT: Envar<Multiline> = Envar::on_demand("TEST_MULTILINE_CERT", || EnvarDef::Unset);
+    set_env_var(
+        "TEST_MULTILINE_CERT",
+        "-----BEGIN CERTIFICATE-----\\nMIIB\\n-----END CERTIFICATE-----\\n",
+    );
+    let cert = CERT.value().unwrap();
+    assert_eq!(
+        cert.as_str(),
+        "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n"
+    );
+    assert_eq!(cert.lines().count(), 3);
+    assert_eq!(
+        <crate::EnvarParser<Multiline> as crate::EnvarParse<Multiline>>::render(&cert).unwrap(),
+        "-----BEGIN CERTIFICATE-----\\nMIIB\\n-----END CERTIFICATE-----\\n"
+    );
+
+    clear_env_var("TEST_NEWLINE_HOSTS");
+    clear_env_var("TEST_MULTILINE_CERT");
+}
+
+#[cfg(feature = "registry")]
+#[test]
+fn test_snapshot_diff() {
+    use crate::snapshot::{Change, Snapshot};
+    let _lock = get_test_lock();
+
+    static WORKERS: Envar<u32> = Envar::on_demand("TEST_SNAPSHOT_WORKERS", || EnvarDef::Default(4));
+    static REGION: Envar<String> = Envar::on_demand("TEST_SNAPSHOT_REGION", || EnvarDef::Unset);
+    static TOKEN: Envar<String> = Envar::on_demand("TEST_SNAPSHOT_TOKEN", || {
+        EnvarDef::Default("t0".to_string())
+    })
+    .sensitive();
+
+    set_env_var("TEST_SNAPSHOT_REGION", "eu-west-1");
+    let baseline = Snapshot::capture_of("baseline", &[&WORKERS, &REGION, &TOKEN]);
+    assert_eq!(baseline.values["TEST_SNAPSHOT_WORKERS"], "4");
+    assert!(baseline.values["TEST_SNAPSHOT_TOKEN"].starts_with("<sensitive "));
+    assert!(!baseline.values["TEST_SNAPSHOT_TOKEN"].contains("t0"));
+
+    set_env_var("TEST_SNAPSHOT_WORKERS", "8");
+    set_env_var("TEST_SNAPSHOT_TOKEN", "t1");
+    clear_env_var("TEST_SNAPSHOT_REGION");
+    let canary = Snapshot::capture_of("canary", &[&WORKERS, &REGION, &TOKEN]);
+
+    let diff = baseline.diff(&canary);
+    assert_eq!(diff.changes.len(), 3);
+    assert_eq!(
+        diff.changes[2],
+        Change::Modified {
+            name: "TEST_SNAPSHOT_WORKERS".to_string(),
+            before: "4".to_string(),
+            after: "8".to_string(),
+        }
+    );
+    assert!(diff
+        .to_string()
+        .starts_with("baseline -> canary: 3 change(s)"));
+    assert!(diff
+        .to_string()
+        .contains("~ TEST_SNAPSHOT_REGION: eu-west-1 -> <error: "));
+    assert!(baseline.diff(&baseline).is_empty());
+
+    let restored = Snapshot::parse(&canary.to_string()).unwrap();
+    assert_eq!(restored, canary);
+
+    // fingerprints depend on the key, so they can only be compared under a shared one
+    crate::snapshot::set_fingerprint_key("key-1");
+    let first = Snapshot::capture_of("first", &[&TOKEN]);
+    assert!(first
+        .diff(&Snapshot::capture_of("again", &[&TOKEN]))
+        .is_empty());
+    crate::snapshot::set_fingerprint_key("key-2");
+    let second = Snapshot::capture_of("second", &[&TOKEN]);
+    assert_ne!(
+        first.values["TEST_SNAPSHOT_TOKEN"],
+        second.values["TEST_SNAPSHOT_TOKEN"]
+    );
+
+    // `Secret` values have no raw form, so the raw value is fingerprinted
+    static API_KEY: Envar<crate::Secret<String>> =
+        Envar::on_demand("TEST_SNAPSHOT_API_KEY", || EnvarDef::Unset);
+    set_env_var("TEST_SNAPSHOT_API_KEY", "k1");
+    let before = Snapshot::capture_of("before", &[&API_KEY]);
+    set_env_var("TEST_SNAPSHOT_API_KEY", "k2");
+    let after = Snapshot::capture_of("after", &[&API_KEY]);
+    assert_ne!(
+        before.values["TEST_SNAPSHOT_API_KEY"],
+        after.values["TEST_SNAPSHOT_API_KEY"]
+    );
+    assert!(!after.to_string().contains("k2"));
+
+    clear_env_var("TEST_SNAPSHOT_WORKERS");
+    clear_env_var("TEST_SNAPSHOT_TOKEN");
+    clear_env_var("TEST_SNAPSHOT_API_KEY");
+}
+
+#[cfg(feature = "registry")]
+#[test]
+fn test_registry_snapshot() {
+    use crate::snapshot::Snapshot;
+    let _lock = get_test_lock();
+
+    static PORT: Envar<u16> = Envar::on_demand("TEST_RESOLVED_PORT", || EnvarDef::Default(8080));
+    static HOST: Envar<String> = Envar::on_demand("TEST_RESOLVED_HOST", || EnvarDef::Unset);
+    static PASSWORD: Envar<String> = Envar::on_demand("TEST_RESOLVED_PASSWORD", || {
+        EnvarDef::Default("hunter2".to_string())
+    })
+    .sensitive();
+    PORT.register();
+    HOST.register();
+    PASSWORD.register();
+
+    set_env_var("TEST_RESOLVED_PORT", "9090");
+    let config = Snapshot::capture("startup");
+    assert_eq!(config.values["TEST_RESOLVED_PORT"], "9090");
+    assert!(config.values["TEST_RESOLVED_PASSWORD"].starts_with("<sensitive "));
+    assert!(config.values["TEST_RESOLVED_HOST"].starts_with("<error: "));
+    let text = config.to_string();
+    assert!(text.contains("TEST_RESOLVED_PORT=9090\n"));
+    assert!(!text.contains("hunter2"));
+
+    #[cfg(feature = "serde")]
+    {
+        let config = Snapshot::capture_of("startup", &[&PORT, &PASSWORD]);
+        let json: serde_json::Value = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["name"], "startup");
+        assert_eq!(json["values"]["TEST_RESOLVED_PORT"], "9090");
+        assert_eq!(
+            json["values"]["TEST_RESOLVED_PASSWORD"],
+            config.values["TEST_RESOLVED_PASSWORD"]
+        );
+        assert!(!json.to_string().contains("hunter2"));
+    }
+
+    clear_env_var("TEST_RESOLVED_PORT");
+}
+
+#[cfg(feature = "list")]
+#[test]
+fn test_lazy_list() {
+    use crate::LazyList;
+    let _lock = get_test_lock();
+
+    static BACKENDS: Envar<LazyList<u16, crate::CommaList>> =
+        Envar::on_demand("TEST_LAZY_BACKENDS", || EnvarDef::Unset);
+    let raw: Vec<String> = (0..5000).map(|i| i.to_string()).collect();
+    set_env_var("TEST_LAZY_BACKENDS", &format!("{}, ,", raw.join(",")));
+
+    let backends = BACKENDS.value().unwrap();
+    assert_eq!(backends.len(), 5000);
+    assert_eq!(backends.iter_parse().nth(4999).unwrap().unwrap(), 4999);
+    assert_eq!(backends.to_list().unwrap().len(), 5000);
+
+    // bad items only fail when reached
+    set_env_var("TEST_LAZY_BACKENDS", "1,2,x");
+    let backends = BACKENDS.value().unwrap();
+    let mut items = backends.iter_parse();
+    assert_eq!(items.next().unwrap().unwrap(), 1);
+    assert_eq!(items.next().unwrap().unwrap(), 2);
+    assert!(matches!(
+        items.next(),
+        Some(Err(EnvarError::ParseError { .. }))
+    ));
+    assert!(backends.to_list().is_err());
+
+    clear_env_var("TEST_LAZY_BACKENDS");
+}
+
+#[test]
+fn test_weighted_list() {
+    use crate::WeightedList;
+    let _lock = get_test_lock();
+
+    static SPLIT: Envar<WeightedList<String>> =
+        Envar::on_demand("TEST_WEIGHTED_SPLIT", || EnvarDef::Unset);
+    set_env_var("TEST_WEIGHTED_SPLIT", "stable:3, canary:1, host:8080:0");
+    let split = SPLIT.value().unwrap();
+    assert_eq!(split.items().len(), 3);
+    assert_eq!(split.weight_of(&"stable".to_string()), 0.75);
+    assert_eq!(split.weight_of(&"canary".to_string()), 0.25);
+    assert_eq!(split.weight_of(&"host:8080".to_string()), 0.0);
+    assert_eq!(split.pick(0.0), "stable");
+    assert_eq!(split.pick(0.74), "stable");
+    assert_eq!(split.pick(0.75), "canary");
+    assert_eq!(split.pick(1.0), "canary");
+    let total: f64 = split.normalized().map(|(_, weight)| weight).sum();
+    assert_eq!(total, 1.0);
+
+    for bad in ["stable", "stable:-1", "stable:0", "stable:x"] {
+        set_env_var("TEST_WEIGHTED_SPLIT", bad);
+        assert!(
+            matches!(
+                SPLIT.value(),
+                Err(EnvarError::ParseError {
+                    typename: "WeightedList",
+                    ..
+                })
+            ),
+            "Failed for value: {}",
+            bad
+        );
+    }
+
+    clear_env_var("TEST_WEIGHTED_SPLIT");
+}
+
+#[test]
+fn test_resolved_addrs() {
+    use crate::ResolvedAddrs;
+    let _lock = get_test_lock();
+
+    static UPSTREAM: Envar<ResolvedAddrs> =
+        Envar::on_demand("TEST_RESOLVED_UPSTREAM", || EnvarDef::Unset);
+    set_env_var("TEST_RESOLVED_UPSTREAM", "127.0.0.1:8080");
+    let upstream = UPSTREAM.value().unwrap();
+    assert_eq!(upstream.target(), "127.0.0.1:8080");
+    assert_eq!(upstream.first(), "127.0.0.1:8080".parse().unwrap());
+
+    static REFRESHED: Envar<ResolvedAddrs<60>> =
+        Envar::on_demand("TEST_RESOLVED_REFRESHED", || EnvarDef::Unset);
+    set_env_var("TEST_RESOLVED_REFRESHED", "localhost:443");
+    let refreshed = REFRESHED.value().unwrap();
+    assert!(refreshed
+        .addrs()
+        .iter()
+        .all(|addr| addr.port() == 443 && addr.ip().is_loopback()));
+
+    set_env_var("TEST_RESOLVED_UPSTREAM", "missing-port");
+    assert!(matches!(
+        UPSTREAM.value(),
+        Err(EnvarError::ParseError {
+            typename: "ResolvedAddrs",
+            ..
+        })
+    ));
+
+    clear_env_var("TEST_RESOLVED_UPSTREAM");
+    clear_env_var("TEST_RESOLVED_REFRESHED");
+}
+
+#[cfg(feature = "registry")]
+#[test]
+fn test_registry_lookup() {
+    let _lock = get_test_lock();
+
+    static INDEXED: Envar<u32> = Envar::on_demand("TEST_REGISTRY_INDEXED", || EnvarDef::Default(7));
+    assert!(crate::registry::lookup("TEST_REGISTRY_INDEXED").is_none());
+
+    INDEXED.register();
+    INDEXED.register();
+    let found = crate::registry::lookup("TEST_REGISTRY_INDEXED").unwrap();
+    assert_eq!(found.typename(), "u32");
+    assert_eq!(
+        crate::registry::entries()
+            .iter()
+            .filter(|envar| envar.name() == "TEST_REGISTRY_INDEXED")
+            .count(),
+        1
+    );
+
+    set_env_var("TEST_REGISTRY_INDEXED", "9");
+    let explanation = crate::registry::explain("TEST_REGISTRY_INDEXED").unwrap();
+    assert_eq!(explanation.outcome, Ok("9".to_string()));
+    assert!(crate::registry::explain("TEST_REGISTRY_MISSING").is_none());
+
+    clear_env_var("TEST_REGISTRY_INDEXED");
+}
+
+#[cfg(feature = "option")]
+#[derive(Clone, Debug, PartialEq, typed_env_derive::EnvarParse)]
+struct DbConfig {
+    host: String,
+    port: u16,
+    #[envar(default)]
+    tls: bool,
+    #[envar(default)]
+    replica: Option<String>,
+}
+
+#[derive(Clone, Debug, typed_env_derive::EnvarParse)]
+struct DbCredentials {
+    user: String,
+    password: crate::Secret<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, typed_env_derive::EnvarParse)]
+#[envar(separator = ',')]
+struct Pair {
+    left: i32,
+    right: i32,
+}
+
+#[cfg(feature = "option")]
+#[test]
+fn test_derive_key_values() {
+    let _lock = get_test_lock();
+
+    static DB: Envar<DbConfig> = Envar::on_demand("TEST_DERIVE_DB", || EnvarDef::Unset);
+    set_env_var("TEST_DERIVE_DB", "host=db.internal; port=5432;tls=true;");
+    let db = DB.value().unwrap();
+    assert_eq!(
+        db,
+        DbConfig {
+            host: "db.internal".to_string(),
+            port: 5432,
+            tls: true,
+            replica: None,
+        }
+    );
+    assert_eq!(
+        <crate::EnvarParser<DbConfig> as crate::EnvarParse<DbConfig>>::render(&db).unwrap(),
+        "host=db.internal;port=5432;tls=true;replica="
+    );
+
+    for (raw, reason) in [
+        ("host=db", "missing key \"port\""),
+        ("host=db;port=1;user=x", "unknown key \"user\""),
+        ("host=db;port", "expected key=value"),
+        ("host=db;port=1;host=db2", "duplicate key \"host\""),
+    ] {
+        set_env_var("TEST_DERIVE_DB", raw);
+        match DB.value() {
+            Err(EnvarError::ParseError {
+                reason: r,
+                typename,
+                ..
+            }) => {
+                assert!(r.as_str().contains(reason), "{} for {}", r, raw);
+                assert!(typename.ends_with("DbConfig"));
+            }
+            other => panic!("expected a parse error for {}, got {:?}", raw, other),
+        }
+    }
+    set_env_var("TEST_DERIVE_DB", "host=db;port=http");
+    assert!(matches!(
+        DB.value(),
+        Err(EnvarError::ParseError {
+            typename: "u16",
+            reason,
+            ..
+        }) if reason.as_str().starts_with("key \"port\": ")
+    ));
+
+    // errors name only the offending pair, so sibling fields do not leak
+    set_env_var("TEST_DERIVE_DB", "host=db;port=1;replica=r;tls=maybe");
+    assert!(matches!(
+        DB.value(),
+        Err(EnvarError::ParseError { value, .. }) if value == "maybe"
+    ));
+    set_env_var("TEST_DERIVE_DB", "host=db;port=1;port=2");
+    assert!(matches!(
+        DB.value(),
+        Err(EnvarError::ParseError { value, .. }) if value == "port=2"
+    ));
+
+    use crate::{EnvarParse, EnvarParser};
+    const { assert!(<EnvarParser<DbCredentials> as EnvarParse<DbCredentials>>::SENSITIVE) };
+    for raw in [
+        "password=hunter2;user=a;user=b",
+        "password=hunter2;usr=a",
+        "user=a;password=hunter2;password=hunter3",
+        "password=hunter2;oops",
+    ] {
+        let error = <EnvarParser<DbCredentials> as EnvarParse<DbCredentials>>::parse(
+            "TEST_DERIVE_CREDENTIALS".into(),
+            raw,
+        )
+        .unwrap_err();
+        let EnvarError::ParseError { value, reason, .. } = &error else {
+            panic!("expected a parse error for {}, got {:?}", raw, error);
+        };
+        assert!(!value.contains("hunter"), "{} for {}", value, raw);
+        assert!(
+            !reason.as_str().contains("hunter"),
+            "{} for {}",
+            reason,
+            raw
+        );
+        assert!(!error.to_string().contains("hunter"), "{}", error);
+    }
+    static CREDENTIALS: Envar<DbCredentials> =
+        Envar::on_demand("TEST_DERIVE_CREDENTIALS", || EnvarDef::Unset);
+    assert!(CREDENTIALS.is_sensitive());
+    set_env_var("TEST_DERIVE_CREDENTIALS", "password=hunter2;usr=a");
+    assert!(matches!(
+        CREDENTIALS.value(),
+        Err(EnvarError::ParseError { value, .. }) if value == "***"
+    ));
+    clear_env_var("TEST_DERIVE_CREDENTIALS");
+
+    static PAIR: Envar<Pair> = Envar::on_demand("TEST_DERIVE_PAIR", || EnvarDef::Unset);
+    set_env_var("TEST_DERIVE_PAIR", "left=1, right=-2");
+    assert_eq!(PAIR.value().unwrap(), Pair { left: 1, right: -2 });
+
+    clear_env_var("TEST_DERIVE_DB");
+    clear_env_var("TEST_DERIVE_PAIR");
+}
+
+#[cfg(feature = "registry")]
+#[test]
+fn test_validate_parallel() {
+    let _lock = get_test_lock();
+
+    let mut set = crate::registry::DynamicEnvarSet::new("parallel");
+    for i in 0..64 {
+        set.declare(format!("TEST_PARALLEL_{}", i), EnvarDef::Default(i), "");
+    }
+    set_env_var("TEST_PARALLEL_7", "seven");
+    set_env_var("TEST_PARALLEL_42", "forty-two");
+
+    let errors = crate::registry::validate_parallel(set.entries()).unwrap_err();
+    let names: Vec<String> = errors
+        .errors
+        .iter()
+        .map(|error| match error {
+            EnvarError::ParseError { varname, .. } => varname.to_string(),
+            other => panic!("expected a parse error, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(names, vec!["TEST_PARALLEL_7", "TEST_PARALLEL_42"]);
+
+    clear_env_var("TEST_PARALLEL_7");
+    clear_env_var("TEST_PARALLEL_42");
+    assert!(crate::registry::validate_parallel(set.entries()).is_ok());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_non_unicode_policy() {
+    use crate::NonUnicode;
+    use std::os::unix::ffi::OsStrExt;
+    let _lock = get_test_lock();
+
+    static STRICT: Envar<String> = Envar::on_demand("TEST_NON_UNICODE_STRICT", || {
+        EnvarDef::Default("d".to_string())
+    });
+    static LOSSY: Envar<String> = Envar::on_demand("TEST_NON_UNICODE_LOSSY", || {
+        EnvarDef::Default("d".to_string())
+    })
+    .on_non_unicode(NonUnicode::Lossy);
+    static UNSET: Envar<String> = Envar::on_startup("TEST_NON_UNICODE_UNSET", || {
+        EnvarDef::Default("d".to_string())
+    })
+    .on_non_unicode(NonUnicode::TreatAsUnset);
+
+    let invalid = std::ffi::OsStr::from_bytes(b"caf\xe9");
+    for name in [
+        "TEST_NON_UNICODE_STRICT",
+        "TEST_NON_UNICODE_LOSSY",
+        "TEST_NON_UNICODE_UNSET",
+    ] {
+        unsafe { std::env::set_var(name, invalid) };
+    }
+
+    match STRICT.value() {
+        Err(e @ EnvarError::NotUnicode(_)) => {
+            assert_eq!(
+                e.to_string(),
+                "Environment variable TEST_NON_UNICODE_STRICT is not valid UTF-8"
+            )
+        }
+        other => panic!("expected NotUnicode, got {:?}", other),
+    }
+    assert!(STRICT.explain().outcome.is_err());
+    assert_eq!(LOSSY.value().unwrap(), "caf\u{fffd}");
+    assert_eq!(LOSSY.raw().unwrap(), "caf\u{fffd}");
+    assert_eq!(LOSSY.raw_os().unwrap(), invalid);
+    assert_eq!(UNSET.value().unwrap(), "d");
+
+    clear_env_var("TEST_NON_UNICODE_STRICT");
+    clear_env_var("TEST_NON_UNICODE_LOSSY");
+    clear_env_var("TEST_NON_UNICODE_UNSET");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_non_unicode_os_values() {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::PathBuf;
+    let _lock = get_test_lock();
+
+    static DATA_DIR: Envar<PathBuf> = Envar::on_demand("TEST_OS_DATA_DIR", || EnvarDef::Unset);
+    static LABEL: Envar<OsString> = Envar::on_startup("TEST_OS_LABEL", || EnvarDef::Unset);
+    static NAME: Envar<String> = Envar::on_demand("TEST_OS_NAME", || EnvarDef::Unset);
+
+    let invalid = std::ffi::OsStr::from_bytes(b"/srv/caf\xe9");
+    for name in ["TEST_OS_DATA_DIR", "TEST_OS_LABEL", "TEST_OS_NAME"] {
+        unsafe { std::env::set_var(name, invalid) };
+    }
+
+    // read from the original bytes, regardless of the `NonUnicode` policy
+    assert_eq!(DATA_DIR.value().unwrap().as_os_str(), invalid);
+    assert_eq!(LABEL.value().unwrap(), invalid);
+    assert!(DATA_DIR.explain().outcome.is_ok());
+    assert_eq!(DATA_DIR.raw_os().unwrap(), invalid);
+    assert!(matches!(DATA_DIR.raw(), Err(EnvarError::NotUnicode(_))));
+    assert!(matches!(NAME.value(), Err(EnvarError::NotUnicode(_))));
+
+    assert!(matches!(
+        <crate::EnvarParser<u16> as crate::EnvarParse<_>>::parse_os("TEST_OS_PORT".into(), invalid),
+        Err(EnvarError::NotUnicode(_))
+    ));
+    assert_eq!(
+        <crate::EnvarParser<OsString> as crate::EnvarParse<_>>::parse("TEST_OS_LABEL".into(), "a")
+            .unwrap(),
+        "a"
+    );
+
+    clear_env_var("TEST_OS_DATA_DIR");
+    clear_env_var("TEST_OS_LABEL");
+    clear_env_var("TEST_OS_NAME");
+}
+
+#[test]
+fn test_retry_and_stale_values() {
+    use crate::RetryPolicy;
+    use std::time::Duration;
+    let _lock = get_test_lock();
+
+    let policy =
+        RetryPolicy::new(4).with_backoff(Duration::from_millis(1), Duration::from_millis(3));
+    assert_eq!(policy.backoff(1), Duration::from_millis(1));
+    assert_eq!(policy.backoff(2), Duration::from_millis(2));
+    assert_eq!(policy.backoff(3), Duration::from_millis(3));
+    assert_eq!(policy.backoff(40), Duration::from_millis(3));
+
+    let fetched = policy.run(|attempt| {
+        if attempt < 3 {
+            Err(attempt)
+        } else {
+            Ok("config")
+        }
+    });
+    assert_eq!(fetched, Ok("config"));
+    assert_eq!(policy.run(Err::<(), _>), Err(4));
+    assert_eq!(RetryPolicy::NONE.run(Err::<(), _>), Err(1));
+
+    static TIMEOUT: Envar<u32> =
+        Envar::on_demand("TEST_STALE_TIMEOUT", || EnvarDef::Unset).serve_stale_on_error();
+    assert!(matches!(TIMEOUT.value(), Err(EnvarError::NotSet(_))));
+    set_env_var("TEST_STALE_TIMEOUT", "30");
+    assert_eq!(TIMEOUT.value().unwrap(), 30);
+    set_env_var("TEST_STALE_TIMEOUT", "30s");
+    assert_eq!(TIMEOUT.value().unwrap(), 30);
+    assert_eq!(TIMEOUT.value().unwrap(), 30);
+    assert_eq!(TIMEOUT.stats().parse_failures, 1);
+    set_env_var("TEST_STALE_TIMEOUT", "45");
+    assert_eq!(TIMEOUT.value().unwrap(), 45);
+
+    // a remote source failing its next `FAILURES` fetches
+    static FAILURES: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+    struct FlakySource;
+    impl crate::EnvSource for FlakySource {
+        fn get(&self, name: &str) -> Option<std::ffi::OsString> {
+            self.try_get(name).ok().flatten()
+        }
+        fn try_get(&self, _name: &str) -> Result<Option<std::ffi::OsString>, String> {
+            use std::sync::atomic::Ordering;
+            match FAILURES.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1)) {
+                Ok(_) => Err("connection reset".to_string()),
+                Err(_) => Ok(Some("30".into())),
+            }
+        }
+        fn vars(&self) -> Vec<(std::ffi::OsString, std::ffi::OsString)> {
+            vec![]
+        }
+    }
+    static REMOTE: crate::RetryingSource<FlakySource> = crate::RetryingSource::new(
+        FlakySource,
+        RetryPolicy::new(3).with_backoff(Duration::from_millis(1), Duration::from_millis(1)),
+    );
+    static REMOTE_TIMEOUT: Envar<u32> = Envar::on_demand("TEST_REMOTE_TIMEOUT", || EnvarDef::Unset)
+        .source(&REMOTE)
+        .serve_stale_on_error();
+    static REMOTE_STRICT: Envar<u32> =
+        Envar::on_demand("TEST_REMOTE_STRICT", || EnvarDef::Unset).source(&REMOTE);
+
+    FAILURES.store(2, std::sync::atomic::Ordering::SeqCst);
+    assert_eq!(REMOTE_TIMEOUT.value().unwrap(), 30);
+    FAILURES.store(3, std::sync::atomic::Ordering::SeqCst);
+    assert_eq!(REMOTE_TIMEOUT.value().unwrap(), 30);
+    FAILURES.store(3, std::sync::atomic::Ordering::SeqCst);
+    let error = REMOTE_STRICT.value().unwrap_err();
+    assert!(
+        matches!(&error, EnvarError::SourceError { reason, .. } if reason == "connection reset")
+    );
+    assert_eq!(error.exit_code(), crate::EX_TEMPFAIL);
+    assert_eq!(REMOTE_STRICT.value().unwrap(), 30);
+
+    clear_env_var("TEST_STALE_TIMEOUT");
+}
+
+#[test]
+fn test_disk_cache() {
+    use crate::DiskCache;
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+
+    let path = std::env::temp_dir().join(format!("typed-env-cache-{}", std::process::id()));
+    let cache = DiskCache::new(&path);
+    assert!(cache.load().unwrap().is_none());
+
+    let mut remote = BTreeMap::new();
+    remote.insert("DB_URL".to_string(), "postgres://db".to_string());
+    remote.insert("BANNER".to_string(), "line 1\nline 2 \\ done".to_string());
+
+    let fetched = cache
+        .fetch_or_load(|| Ok::<_, String>(remote.clone()))
+        .unwrap();
+    assert_eq!(fetched, remote);
+    // the backend is down: the last known-good values are used
+    let fallback = cache
+        .fetch_or_load(|| Err("connection refused".to_string()))
+        .unwrap();
+    assert_eq!(fallback, remote);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+    // no temporary files are left behind
+    let leftovers = std::fs::read_dir(std::env::temp_dir())
+        .unwrap()
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            name.starts_with(&format!("typed-env-cache-{}.", std::process::id()))
+        })
+        .count();
+    assert_eq!(leftovers, 0);
+
+    // expired entries are ignored
+    std::thread::sleep(Duration::from_millis(1100));
+    let expiring = DiskCache::new(&path).with_ttl(Duration::from_millis(500));
+    assert!(expiring.load().unwrap().is_none());
+    assert_eq!(
+        expiring.fetch_or_load(|| Err("connection refused".to_string())),
+        Err("connection refused".to_string())
+    );
+
+    // tampered files fail the integrity check
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::write(&path, contents.replace("postgres://db", "postgres://evil")).unwrap();
+    let error = cache.load().unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_exit_codes() {
+    use crate::{EnvarErrors, EX_CONFIG, EX_USAGE};
+    let _lock = get_test_lock();
+
+    static MISSING: Envar<String> = Envar::on_demand("TEST_EXIT_MISSING", || EnvarDef::Unset);
+    static BROKEN: Envar<u8> = Envar::on_demand("TEST_EXIT_BROKEN", || EnvarDef::Unset);
+    set_env_var("TEST_EXIT_BROKEN", "256");
+
+    let missing = MISSING.value().unwrap_err();
+    let broken = BROKEN.value().unwrap_err();
+    assert_eq!(missing.exit_code(), EX_USAGE);
+    assert_eq!(broken.exit_code(), EX_CONFIG);
+    assert_eq!(
+        EnvarErrors {
+            errors: vec![missing.clone()]
+        }
+        .exit_code(),
+        EX_USAGE
+    );
+    assert_eq!(
+        EnvarErrors {
+            errors: vec![missing, broken]
+        }
+        .exit_code(),
+        EX_CONFIG
+    );
+
+    clear_env_var("TEST_EXIT_BROKEN");
+}
+
+#[cfg(feature = "docgen")]
+#[test]
+fn test_categories() {
+    let _lock = get_test_lock();
+
+    static DB_HOST: Envar<String> =
+        Envar::on_demand("TEST_CAT_DB_HOST", || EnvarDef::Default("db".to_string()))
+            .category("Database");
+    static TRACE_RATE: Envar<f64> =
+        Envar::on_demand("TEST_CAT_TRACE_RATE", || EnvarDef::Default(0.1)).category("Telemetry");
+    static DB_POOL: Envar<u32> =
+        Envar::on_demand("TEST_CAT_DB_POOL", || EnvarDef::Default(8)).category("Database");
+    static LOOSE: Envar<u32> = Envar::on_demand("TEST_CAT_LOOSE", || EnvarDef::Default(1));
+
+    assert_eq!(DB_HOST.describe().category, Some("Database"));
+    let groups = crate::registry::group_by_category(&[&DB_HOST, &TRACE_RATE, &DB_POOL, &LOOSE]);
+    let layout: Vec<(Option<&str>, Vec<&str>)> = groups
+        .iter()
+        .map(|(category, group)| (*category, group.iter().map(|envar| envar.name()).collect()))
+        .collect();
+    assert_eq!(
+        layout,
+        vec![
+            (None, vec!["TEST_CAT_LOOSE"]),
+            (
+                Some("Database"),
+                vec!["TEST_CAT_DB_HOST", "TEST_CAT_DB_POOL"]
+            ),
+            (Some("Telemetry"), vec!["TEST_CAT_TRACE_RATE"]),
+        ]
+    );
+
+    DB_HOST.register();
+    TRACE_RATE.register();
+    DB_POOL.register();
+    let dockerfile = crate::docgen::dockerfile();
+    assert!(dockerfile
+        .contains("# Database\nENV TEST_CAT_DB_HOST=\"db\"\nENV TEST_CAT_DB_POOL=\"8\"\n"));
+    assert!(crate::docgen::docker_compose()
+        .contains("  # Telemetry\n  TEST_CAT_TRACE_RATE: \"${TEST_CAT_TRACE_RATE:-0.1}\"\n"));
+    assert!(crate::docgen::kubernetes("app").contains("  # Database\n  TEST_CAT_DB_HOST: \"db\"\n"));
+}
+
+#[test]
+fn test_env_file() {
+    use crate::{parse_env_file, EnvFileError};
+    let _lock = get_test_lock();
+
+    set_env_var("TEST_ENVFILE_HOME", "/home/me");
+    let entries = parse_env_file(
+        r#"
+# direnv config
+export APP_ROOT=$TEST_ENVFILE_HOME/app
+export GREETING="hello \"world\"" # trailing comment
+LITERAL='$APP_ROOT stays'
+CACHE_DIR=${APP_ROOT}/cache
+ESCAPED=a\ b
+EMPTY=
+layout python3
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        entries,
+        vec![
+            ("APP_ROOT".to_string(), "/home/me/app".to_string()),
+            ("GREETING".to_string(), "hello \"world\"".to_string()),
+            ("LITERAL".to_string(), "$APP_ROOT stays".to_string()),
+            ("CACHE_DIR".to_string(), "/home/me/app/cache".to_string()),
+            ("ESCAPED".to_string(), "a b".to_string()),
+            ("EMPTY".to_string(), "".to_string()),
+        ]
+    );
+
+    match parse_env_file("A=1\nB=\"open") {
+        Err(EnvFileError::Syntax { line, .. }) => assert_eq!(line, 2),
+        other => panic!("expected a syntax error, got {:?}", other),
+    }
+    assert!(parse_env_file("A=two words").is_err());
+
+    let path = std::env::temp_dir().join(format!("typed-env-envrc-{}", std::process::id()));
+    std::fs::write(&path, "export TEST_ENVFILE_PORT=9090\n").unwrap();
+    static PORT: Envar<u16> = Envar::on_demand("TEST_ENVFILE_PORT", || EnvarDef::Default(80));
+    let depth = crate::layer_depth();
+    let layer = crate::load_env_file(&path).unwrap();
+    assert_eq!(PORT.value().unwrap(), 9090);
+    assert_eq!(crate::layer_depth(), depth + 1);
+    drop(layer);
+    assert_eq!(PORT.value().unwrap(), 80);
+
+    std::fs::remove_file(&path).unwrap();
+    clear_env_var("TEST_ENVFILE_HOME");
+}
+
+#[test]
+fn test_duration() {
+    use std::time::Duration;
+    let _lock = get_test_lock();
+
+    static TIMEOUT: Envar<Duration> = Envar::on_demand("TEST_DURATION_TIMEOUT", || {
+        EnvarDef::Default(Duration::from_secs(30))
+    });
+    assert_eq!(TIMEOUT.value().unwrap(), Duration::from_secs(30));
+
+    for (raw, expected) in [
+        ("30s", Duration::from_secs(30)),
+        ("5m", Duration::from_secs(300)),
+        ("2h30m", Duration::from_secs(9000)),
+        ("1h 15s", Duration::from_secs(3615)),
+        ("1s500ms", Duration::from_millis(1500)),
+        ("250", Duration::from_millis(250)),
+        ("1d", Duration::from_secs(86400)),
+        ("10us", Duration::from_micros(10)),
+        ("5000000000s", Duration::from_secs(5_000_000_000)),
+    ] {
+        set_env_var("TEST_DURATION_TIMEOUT", raw);
+        assert_eq!(
+            TIMEOUT.value().unwrap(),
+            expected,
+            "Failed for value: {}",
+            raw
+        );
+    }
+
+    for bad in ["30x", "s", "1.5s", "-1s"] {
+        set_env_var("TEST_DURATION_TIMEOUT", bad);
+        assert!(
+            matches!(
+                TIMEOUT.value(),
+                Err(EnvarError::ParseError {
+                    typename: "Duration",
+                    ..
+                })
+            ),
+            "Failed for value: {}",
+            bad
+        );
+    }
+
+    set_env_var("TEST_DURATION_TIMEOUT", "300000000000000d");
+    match TIMEOUT.value() {
+        Err(EnvarError::ParseError { reason, .. }) => {
+            assert_eq!(reason.as_str(), "duration overflows")
+        }
+        other => panic!("expected ParseError, got {:?}", other),
+    }
+
+    let render = <crate::EnvarParser<Duration> as crate::EnvarParse<Duration>>::render;
+    assert_eq!(render(&Duration::from_secs(9000)).unwrap(), "2h30m");
+    assert_eq!(render(&Duration::from_millis(1500)).unwrap(), "1s500ms");
+    assert_eq!(render(&Duration::ZERO).unwrap(), "0s");
+
+    clear_env_var("TEST_DURATION_TIMEOUT");
+}
+
+#[test]
+fn test_ip_addrs() {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+    let _lock = get_test_lock();
+
+    static BIND: Envar<IpAddr> = Envar::on_demand("TEST_IP_BIND", || {
+        EnvarDef::Default(IpAddr::V4(Ipv4Addr::UNSPECIFIED))
+    });
+    assert_eq!(BIND.value().unwrap(), IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+    set_env_var("TEST_IP_BIND", "::1");
+    assert_eq!(BIND.value().unwrap(), IpAddr::V6(Ipv6Addr::LOCALHOST));
+
+    static V4: Envar<Ipv4Addr> = Envar::on_demand("TEST_IP_V4", || EnvarDef::Unset);
+    set_env_var("TEST_IP_V4", "10.0.0.1");
+    assert_eq!(V4.value().unwrap(), Ipv4Addr::new(10, 0, 0, 1));
+    set_env_var("TEST_IP_V4", "::1");
+    match V4.value() {
+        Err(EnvarError::ParseError {
+            typename, reason, ..
+        }) => {
+            assert_eq!(typename, "Ipv4Addr");
+            assert_eq!(reason.as_str(), "invalid IPv4 address syntax");
+        }
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+
+    static V6: Envar<Ipv6Addr> = Envar::on_demand("TEST_IP_V6", || EnvarDef::Unset);
+    set_env_var("TEST_IP_V6", "fe80::1");
+    assert!(V6.value().unwrap().is_unicast_link_local());
+
+    clear_env_var("TEST_IP_BIND");
+    clear_env_var("TEST_IP_V4");
+    clear_env_var("TEST_IP_V6");
+}
+
+#[test]
+fn test_socket_addrs() {
+    use std::net::{SocketAddr, SocketAddrV4, SocketAddrV6};
+    let _lock = get_test_lock();
+
+    static LISTEN: Envar<SocketAddr> = Envar::on_demand("TEST_SOCKET_LISTEN", || EnvarDef::Unset);
+    let reason_for = |value: &str| {
+        set_env_var("TEST_SOCKET_LISTEN", value);
+        match LISTEN.value() {
+            Err(EnvarError::ParseError {
+                typename, reason, ..
+            }) => {
+                assert_eq!(typename, "SocketAddr");
+                reason.to_string()
+            }
+            other => panic!("expected a parse error for {:?}, got {:?}", value, other),
+        }
+    };
+
+    set_env_var("TEST_SOCKET_LISTEN", " 0.0.0.0:8080 ");
+    assert_eq!(LISTEN.value().unwrap(), "0.0.0.0:8080".parse().unwrap());
+    set_env_var("TEST_SOCKET_LISTEN", "[::1]:443");
+    assert_eq!(LISTEN.value().unwrap().port(), 443);
+
+    assert!(reason_for("0.0.0.0").starts_with("missing port"));
+    assert!(reason_for("[::1]").starts_with("missing port"));
+    assert_eq!(
+        reason_for("300.0.0.1:80"),
+        "invalid IP address \"300.0.0.1\""
+    );
+    assert_eq!(
+        reason_for("localhost:80"),
+        "invalid IP address \"localhost\""
+    );
+    assert_eq!(reason_for("127.0.0.1:http"), "invalid port \"http\"");
+
+    static V4: Envar<SocketAddrV4> = Envar::on_demand("TEST_SOCKET_V4", || EnvarDef::Unset);
+    set_env_var("TEST_SOCKET_V4", "127.0.0.1:80");
+    assert_eq!(V4.value().unwrap().port(), 80);
+    set_env_var("TEST_SOCKET_V4", "[::1]:80");
+    assert!(V4.value().is_err());
+
+    static V6: Envar<SocketAddrV6> = Envar::on_demand("TEST_SOCKET_V6", || EnvarDef::Unset);
+    set_env_var("TEST_SOCKET_V6", "[fe80::1]:9000");
+    assert_eq!(V6.value().unwrap().port(), 9000);
+
+    clear_env_var("TEST_SOCKET_LISTEN");
+    clear_env_var("TEST_SOCKET_V4");
+    clear_env_var("TEST_SOCKET_V6");
+}
+
+#[cfg(feature = "option")]
+#[derive(Debug, typed_env_derive::EnvConfig)]
+#[envar(prefix = "TEST_CONFIG_")]
+struct AppConfig {
+    #[envar(example = "postgres://localhost/app")]
+    db_url: String,
+    #[envar(default = 8080)]
+    port: u16,
+    #[envar(default = "info")]
+    log_level: String,
+    #[envar(default)]
+    replica_url: Option<String>,
+    #[envar(rename = "API_TOKEN", sensitive)]
+    token: String,
+}
+
+#[cfg(feature = "option")]
+#[test]
+fn test_derive_env_config() {
+    let _lock = get_test_lock();
+    clear_env_var("TEST_CONFIG_DB_URL");
+    clear_env_var("TEST_CONFIG_API_TOKEN");
+    set_env_var("TEST_CONFIG_PORT", "http");
+
+    let errors = AppConfig::load().unwrap_err().errors;
+    assert_eq!(errors.len(), 3);
+    assert!(matches!(&errors[0], EnvarError::NotSet(name) if name == "TEST_CONFIG_DB_URL"));
+    assert!(
+        matches!(&errors[1], EnvarError::ParseError { varname, .. } if varname == "TEST_CONFIG_PORT")
+    );
+    assert!(matches!(&errors[2], EnvarError::NotSet(name) if name == "TEST_CONFIG_API_TOKEN"));
+
+    set_env_var("TEST_CONFIG_DB_URL", "postgres://db");
+    clear_env_var("TEST_CONFIG_PORT");
+    set_env_var("TEST_CONFIG_API_TOKEN", "s3cr3t");
+    let config = AppConfig::load().unwrap();
+    assert_eq!(config.db_url, "postgres://db");
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.log_level, "info");
+    assert_eq!(config.replica_url, None);
+    assert_eq!(config.token, "s3cr3t");
+
+    clear_env_var("TEST_CONFIG_DB_URL");
+    clear_env_var("TEST_CONFIG_API_TOKEN");
+}
+
+crate::envar! {
+    /// documented
+    static MACRO_PORT: u16 = "TEST_MACRO_PORT", default 8080;
+    pub(crate) static MACRO_URL: String = "TEST_MACRO_URL";
+    static MACRO_WORKERS: usize = "TEST_MACRO_WORKERS", default 2 * 2, on_startup;
+    static MACRO_REQUIRED_AT_STARTUP: String = "TEST_MACRO_REQUIRED", on_startup;
+}
+
+#[test]
+fn test_envar_macro() {
+    let _lock = get_test_lock();
+
+    assert_eq!(MACRO_PORT.name(), "TEST_MACRO_PORT");
+    assert_eq!(MACRO_PORT.value().unwrap(), 8080);
+    set_env_var("TEST_MACRO_PORT", "9090");
+    assert_eq!(MACRO_PORT.value().unwrap(), 9090);
+
+    assert!(matches!(MACRO_URL.value(), Err(EnvarError::NotSet(_))));
+    assert_eq!(MACRO_WORKERS.value().unwrap(), 4);
+    assert!(matches!(
+        MACRO_REQUIRED_AT_STARTUP.value(),
+        Err(EnvarError::NotSet(_))
+    ));
+
+    clear_env_var("TEST_MACRO_PORT");
+}
+
+#[test]
+fn test_collect_results() {
+    let _lock = get_test_lock();
+
+    static PORT: Envar<u16> = Envar::on_demand("TEST_COLLECT_PORT", || EnvarDef::Unset);
+    static HOST: Envar<String> = Envar::on_demand("TEST_COLLECT_HOST", || EnvarDef::Unset);
+    set_env_var("TEST_COLLECT_PORT", "not-a-port");
+    clear_env_var("TEST_COLLECT_HOST");
+
+    let errors = crate::collect((PORT.value(), HOST.value())).unwrap_err();
+    assert_eq!(errors.errors.len(), 2);
+    let report = errors.to_string();
+    assert!(report.starts_with("2 environment variable(s) failed validation:"));
+    assert_eq!(report.lines().count(), 3);
+
+    set_env_var("TEST_COLLECT_PORT", "80");
+    set_env_var("TEST_COLLECT_HOST", "example.com");
+    let (port, host) = crate::collect((PORT.value(), HOST.value())).unwrap();
+    assert_eq!((port, host.as_str()), (80, "example.com"));
+
+    let ports = crate::collect([PORT.value(), Ok(443)]).unwrap();
+    assert_eq!(ports, vec![80, 443]);
+
+    let errors: crate::EnvarErrors = vec![EnvarError::NotSet("A".into())].into_iter().collect();
+    assert_eq!(errors.exit_code(), crate::EX_USAGE);
+
+    clear_env_var("TEST_COLLECT_PORT");
+    clear_env_var("TEST_COLLECT_HOST");
+}
+
+#[test]
+fn test_value_or() {
+    let _lock = get_test_lock();
+
+    static TIMEOUT: Envar<u32> = Envar::on_demand("TEST_VALUE_OR", || EnvarDef::Unset);
+    clear_env_var("TEST_VALUE_OR");
+    assert_eq!(TIMEOUT.value_or(30), 30);
+    assert_eq!(TIMEOUT.value_or_else(|| 60), 60);
+    assert_eq!(TIMEOUT.get(), None);
+
+    set_env_var("TEST_VALUE_OR", "soon");
+    assert_eq!(TIMEOUT.value_or(30), 30);
+    assert_eq!(TIMEOUT.get(), None);
+
+    set_env_var("TEST_VALUE_OR", "5");
+    assert_eq!(TIMEOUT.value_or(30), 5);
+    assert_eq!(TIMEOUT.value_or_else(|| unreachable!()), 5);
+    assert_eq!(TIMEOUT.get(), Some(5));
+
+    clear_env_var("TEST_VALUE_OR");
+}
+
+#[cfg(feature = "option")]
+#[test]
+fn test_required_failure_report() {
+    let _lock = get_test_lock();
+
+    static API_KEY: Envar<Option<String>> = Envar::required("TEST_REQUIRED_API_KEY")
+        .description("Key for the upstream API")
+        .example("sk-123");
+    clear_env_var("TEST_REQUIRED_API_KEY");
+    let error = API_KEY.value().unwrap_err();
+    assert!(matches!(error, EnvarError::NotSet(_)));
+    assert_eq!(
+        API_KEY.failure_report(&error),
+        format!(
+            "error: {}\n  expected: Option<String>\n  description: Key for the upstream API\n  example: TEST_REQUIRED_API_KEY=sk-123\n",
+            error
+        )
+    );
+
+    set_env_var("TEST_REQUIRED_API_KEY", "sk-456");
+    assert_eq!(API_KEY.expect_value(), Some("sk-456".to_string()));
+    assert_eq!(API_KEY.value_or_exit(1), Some("sk-456".to_string()));
+
+    clear_env_var("TEST_REQUIRED_API_KEY");
+}
+
+#[test]
+fn test_set_override() {
+    let _lock = get_test_lock();
+
+    static LIMIT: Envar<u32> = Envar::on_demand("TEST_OVERRIDE_LIMIT", || EnvarDef::Default(10));
+    static FROZEN: Envar<u32> = Envar::on_startup("TEST_OVERRIDE_FROZEN", || EnvarDef::Default(1));
+    set_env_var("TEST_OVERRIDE_LIMIT", "20");
+    assert_eq!(LIMIT.value().unwrap(), 20);
+    assert_eq!(FROZEN.value().unwrap(), 1);
+
+    LIMIT.set_override(30);
+    FROZEN.set_override(2);
+    assert_eq!(LIMIT.value().unwrap(), 30);
+    assert_eq!(FROZEN.value().unwrap(), 2);
+    assert_eq!(std::env::var("TEST_OVERRIDE_LIMIT").unwrap(), "20");
+    let explanation = LIMIT.explain();
+    assert!(explanation.overridden);
+    assert_eq!(explanation.outcome, Ok("30".to_string()));
+
+    assert_eq!(LIMIT.clear_override(), Some(30));
+    assert_eq!(FROZEN.clear_override(), Some(2));
+    assert_eq!(LIMIT.clear_override(), None);
+    assert_eq!(LIMIT.value().unwrap(), 20);
+    assert_eq!(FROZEN.value().unwrap(), 1);
+
+    clear_env_var("TEST_OVERRIDE_LIMIT");
+}
+
+#[test]
+fn test_scoped_overrides() {
+    // no test lock: scoped values are per-thread
+    static SCOPED_LIMIT: Envar<u32> =
+        Envar::on_demand("TEST_SCOPED_LIMIT", || EnvarDef::Default(10));
+
+    {
+        let _limit = crate::test::set_scoped("TEST_SCOPED_LIMIT", "20");
+        assert_eq!(SCOPED_LIMIT.value().unwrap(), 20);
+        assert_eq!(SCOPED_LIMIT.explain().lookups[0].source, "test scope");
+        {
+            let _unset = crate::test::unset_scoped("TEST_SCOPED_LIMIT");
+            assert_eq!(SCOPED_LIMIT.value().unwrap(), 10);
+        }
+        assert_eq!(SCOPED_LIMIT.value().unwrap(), 20);
+
+        // other threads do not see this thread's scope
+        std::thread::spawn(|| assert_eq!(SCOPED_LIMIT.value().unwrap(), 10))
+            .join()
+            .unwrap();
+
+        let _typed = SCOPED_LIMIT.override_scope(30);
+        assert_eq!(SCOPED_LIMIT.value().unwrap(), 30);
+        {
+            let _inner = SCOPED_LIMIT.override_scope(40);
+            assert_eq!(SCOPED_LIMIT.value().unwrap(), 40);
+        }
+        assert_eq!(SCOPED_LIMIT.value().unwrap(), 30);
+    }
+    assert_eq!(SCOPED_LIMIT.value().unwrap(), 10);
+
+    // reads inside a scope neither freeze `on_startup` values nor fill caches
+    static SCOPED_STARTUP: Envar<u32> =
+        Envar::on_startup("TEST_SCOPED_STARTUP", || EnvarDef::Default(1));
+    static SCOPED_TTL: Envar<u32> = Envar::on_demand_ttl(
+        "TEST_SCOPED_TTL",
+        std::time::Duration::from_secs(3600),
+        || EnvarDef::Default(1),
+    );
+    {
+        let _startup = crate::test::set_scoped("TEST_SCOPED_STARTUP", "2");
+        let _ttl = crate::test::set_scoped("TEST_SCOPED_TTL", "2");
+        assert_eq!(SCOPED_STARTUP.value().unwrap(), 2);
+        assert_eq!(SCOPED_TTL.value().unwrap(), 2);
+        assert!(matches!(
+            SCOPED_TTL.provenance(),
+            Some(crate::Provenance::TestScope { .. })
+        ));
+    }
+    assert_eq!(SCOPED_STARTUP.value().unwrap(), 1);
+    assert_eq!(SCOPED_TTL.value().unwrap(), 1);
+    assert_eq!(SCOPED_TTL.provenance(), Some(crate::Provenance::Default));
+}
+
+#[test]
+fn test_on_demand_ttl() {
+    use std::time::Duration;
+    let _lock = get_test_lock();
+
+    static LEVEL: Envar<u8> =
+        Envar::on_demand_ttl("TEST_TTL_LEVEL", Duration::from_millis(200), || {
+            EnvarDef::Default(1)
+        });
+    clear_env_var("TEST_TTL_LEVEL");
+    assert_eq!(LEVEL.value().unwrap(), 1);
+
+    // cached until the TTL expires
+    set_env_var("TEST_TTL_LEVEL", "2");
+    assert_eq!(LEVEL.value().unwrap(), 1);
+    assert!(LEVEL.stats().cache_hits >= 1);
+    std::thread::sleep(Duration::from_millis(250));
+    assert_eq!(LEVEL.value().unwrap(), 2);
+
+    // layers expire the cache early
+    let layer = crate::push_layer([("TEST_TTL_LEVEL", "3")]);
+    assert_eq!(LEVEL.value().unwrap(), 3);
+    drop(layer);
+    assert_eq!(LEVEL.value().unwrap(), 2);
+
+    // failures are not cached
+    std::thread::sleep(Duration::from_millis(250));
+    set_env_var("TEST_TTL_LEVEL", "high");
+    assert!(LEVEL.value().is_err());
+    set_env_var("TEST_TTL_LEVEL", "4");
+    assert_eq!(LEVEL.value().unwrap(), 4);
+
+    clear_env_var("TEST_TTL_LEVEL");
+}
+
+#[test]
+fn test_watch() {
+    use std::sync::mpsc;
+    use std::time::Duration;
+    let _lock = get_test_lock();
+
+    static WATCHED_LEVEL: Envar<String> =
+        Envar::on_demand("TEST_WATCH_LEVEL", || EnvarDef::Default("info".to_string()));
+    clear_env_var("TEST_WATCH_LEVEL");
+
+    let mut watch = WATCHED_LEVEL.watch();
+    assert_eq!(watch.current().map(String::as_str), Some("info"));
+    assert_eq!(watch.refresh(), None);
+    set_env_var("TEST_WATCH_LEVEL", "debug");
+    assert_eq!(watch.refresh(), Some("debug".to_string()));
+    assert_eq!(watch.refresh(), None);
+
+    let (sender, receiver) = mpsc::channel();
+    let handle = watch.spawn_polling(Duration::from_millis(10), move |level| {
+        let _ = sender.send(level);
+    });
+    set_env_var("TEST_WATCH_LEVEL", "warn");
+    assert_eq!(
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap(),
+        "warn"
+    );
+    handle.stop();
+
+    clear_env_var("TEST_WATCH_LEVEL");
+}
+
+#[test]
+fn test_aliases() {
+    let _lock = get_test_lock();
+
+    static ALIASED_PORT: Envar<u16> = Envar::on_demand_with_aliases(
+        "TEST_ALIAS_APP_PORT",
+        &["TEST_ALIAS_PORT", "TEST_ALIAS_HTTP_PORT"],
+        || EnvarDef::Unset,
+    );
+    clear_env_var("TEST_ALIAS_APP_PORT");
+    clear_env_var("TEST_ALIAS_PORT");
+    clear_env_var("TEST_ALIAS_HTTP_PORT");
+
+    let error = ALIASED_PORT.value().unwrap_err();
+    assert!(matches!(error, EnvarError::NotSetAliases { .. }));
+    assert_eq!(
+        error.to_string(),
+        "None of the environment variables TEST_ALIAS_APP_PORT, TEST_ALIAS_PORT, TEST_ALIAS_HTTP_PORT is set"
+    );
+    assert_eq!(error.exit_code(), crate::EX_USAGE);
+
+    set_env_var("TEST_ALIAS_HTTP_PORT", "3");
+    assert_eq!(ALIASED_PORT.value().unwrap(), 3);
+    set_env_var("TEST_ALIAS_PORT", "2");
+    assert_eq!(ALIASED_PORT.value().unwrap(), 2);
+    set_env_var("TEST_ALIAS_APP_PORT", "1");
+    assert_eq!(ALIASED_PORT.value().unwrap(), 1);
+
+    clear_env_var("TEST_ALIAS_APP_PORT");
+    let explanation = ALIASED_PORT.explain();
+    let names: Vec<&str> = explanation
+        .lookups
+        .iter()
+        .map(|lookup| lookup.name.as_str())
+        .collect();
+    assert_eq!(names, ["TEST_ALIAS_APP_PORT", "TEST_ALIAS_PORT"]);
+    assert_eq!(explanation.outcome, Ok("2".to_string()));
+
+    clear_env_var("TEST_ALIAS_PORT");
+    clear_env_var("TEST_ALIAS_HTTP_PORT");
+}
+
+#[test]
+fn test_deprecated_aliases() {
+    use std::sync::{Arc, Mutex};
+    let _lock = get_test_lock();
+
+    static RENAMED: Envar<u32> = Envar::on_demand("TEST_DEPRECATED_NEW", || EnvarDef::Unset)
+        .aliases(&["TEST_DEPRECATED_ALIAS"])
+        .deprecated_aliases(&["TEST_DEPRECATED_OLD"]);
+    clear_env_var("TEST_DEPRECATED_NEW");
+    clear_env_var("TEST_DEPRECATED_ALIAS");
+    set_env_var("TEST_DEPRECATED_OLD", "7");
+
+    let warnings = Arc::new(Mutex::new(Vec::<String>::new()));
+    let sink = warnings.clone();
+    crate::set_warning_handler(move |message| sink.lock().unwrap().push(message.to_string()));
+
+    assert_eq!(RENAMED.value().unwrap(), 7);
+    set_env_var("TEST_DEPRECATED_OLD", "8");
+    assert_eq!(RENAMED.value().unwrap(), 8);
+    crate::reset_warning_handler();
+
+    let warnings = warnings.lock().unwrap();
+    let deprecations: Vec<&String> = warnings
+        .iter()
+        .filter(|message| message.contains("TEST_DEPRECATED_OLD"))
+        .collect();
+    assert_eq!(
+        deprecations,
+        ["environment variable TEST_DEPRECATED_OLD is deprecated, use TEST_DEPRECATED_NEW instead"]
+    );
+
+    clear_env_var("TEST_DEPRECATED_OLD");
+    match RENAMED.value() {
+        Err(EnvarError::NotSetAliases { aliases, .. }) => {
+            assert_eq!(aliases, ["TEST_DEPRECATED_ALIAS", "TEST_DEPRECATED_OLD"])
+        }
+        other => panic!("expected NotSetAliases, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_env_namespace() {
+    let _lock = get_test_lock();
+
+    static NAMESPACE: crate::EnvNamespace = crate::EnvNamespace::new("TEST_NS_");
+    let port = NAMESPACE.var("PORT", EnvarDef::Default(8080u16));
+    let host = NAMESPACE.var::<String>("HOST", EnvarDef::Unset);
+    assert_eq!(port.name(), "TEST_NS_PORT");
+    assert_eq!(port.value().unwrap(), 8080);
+    assert!(matches!(host.value(), Err(EnvarError::NotSet(_))));
+
+    set_env_var("TEST_NS_PORT", "9000");
+    set_env_var("TEST_NS_PROT", "9001");
+    assert_eq!(port.value().unwrap(), 9000);
+    assert_eq!(NAMESPACE.unknown(), ["TEST_NS_PROT"]);
+
+    // the derive reads its fields through a namespace too
+    #[cfg(feature = "option")]
+    {
+        static CONFIG_NAMESPACE: crate::EnvNamespace = crate::EnvNamespace::new("TEST_NS_CONFIG_");
+        set_env_var("TEST_NS_CONFIG_DB_URL", "postgres://ns");
+        set_env_var("TEST_NS_CONFIG_API_TOKEN", "t");
+        set_env_var("TEST_NS_CONFIG_LOG_LVL", "debug");
+        let config = AppConfig::load_from(&CONFIG_NAMESPACE).unwrap();
+        assert_eq!(config.db_url, "postgres://ns");
+        assert_eq!(CONFIG_NAMESPACE.unknown(), ["TEST_NS_CONFIG_LOG_LVL"]);
+    }
+
+    clear_env_var("TEST_NS_PORT");
+    clear_env_var("TEST_NS_PROT");
+    clear_env_var("TEST_NS_CONFIG_DB_URL");
+    clear_env_var("TEST_NS_CONFIG_API_TOKEN");
+    clear_env_var("TEST_NS_CONFIG_LOG_LVL");
+}
+
+#[test]
+fn test_metadata_unit_and_example() {
+    let _lock = get_test_lock();
+
+    static IDLE_TIMEOUT: Envar<u64> = Envar::required("TEST_META_IDLE_TIMEOUT")
+        .description("Idle connection timeout")
+        .example("30")
+        .unit("seconds");
+    let meta = IDLE_TIMEOUT.describe();
+    assert_eq!(meta.example, Some("30"));
+    assert_eq!(meta.unit, Some("seconds"));
+
+    clear_env_var("TEST_META_IDLE_TIMEOUT");
+    let error = IDLE_TIMEOUT.value().unwrap_err();
+    assert!(IDLE_TIMEOUT
+        .failure_report(&error)
+        .ends_with("  unit: seconds\n  example: TEST_META_IDLE_TIMEOUT=30\n"));
+
+    #[cfg(feature = "docgen")]
+    {
+        let schema: serde_json::Value =
+            serde_json::from_str(&crate::docgen::json_schema_of(&[&IDLE_TIMEOUT])).unwrap();
+        assert_eq!(
+            schema["properties"]["TEST_META_IDLE_TIMEOUT"]["examples"],
+            serde_json::json!(["30"])
+        );
+    }
+}
+
+#[cfg(all(feature = "docgen", feature = "option"))]
+#[test]
+fn test_env_template() {
+    static TEMPLATE_URL: Envar<String> = Envar::required("TEST_TEMPLATE_DB_URL")
+        .description("Primary database")
+        .example("postgres://localhost/app")
+        .category("Database");
+    static TEMPLATE_POOL: Envar<u32> =
+        Envar::on_demand("TEST_TEMPLATE_POOL", || EnvarDef::Default(10)).category("Database");
+    static TEMPLATE_TIMEOUT: Envar<Option<u64>> =
+        Envar::on_demand("TEST_TEMPLATE_TIMEOUT", || EnvarDef::Default(None)).unit("seconds");
+    static TEMPLATE_TOKEN: Envar<String> =
+        Envar::on_demand("TEST_TEMPLATE_TOKEN", || EnvarDef::Unset).sensitive();
+
+    let template = crate::docgen::env_template_of(&[
+        &TEMPLATE_URL,
+        &TEMPLATE_TIMEOUT,
+        &TEMPLATE_POOL,
+        &TEMPLATE_TOKEN,
+    ]);
+    assert_eq!(
+        template,
+        "# TEST_TEMPLATE_TIMEOUT (Option<u64>, in seconds)\n\
+         # TEST_TEMPLATE_TIMEOUT=\n\
+         # TEST_TEMPLATE_TOKEN (String, required, sensitive)\n\
+         TEST_TEMPLATE_TOKEN=\n\
+         \n\
+         # --- Database ---\n\
+         # TEST_TEMPLATE_DB_URL (String, required)\n\
+         # Primary database\n\
+         TEST_TEMPLATE_DB_URL=postgres://localhost/app\n\
+         # TEST_TEMPLATE_POOL (u32)\n\
+         # TEST_TEMPLATE_POOL=10\n"
+    );
+}
+
+#[test]
+fn test_secret() {
+    use crate::Secret;
+    let _lock = get_test_lock();
+
+    static TOKEN: Envar<Secret<String>> = Envar::on_demand("TEST_SECRET_TOKEN", || {
+        EnvarDef::Default(Secret::new("fallback-token".to_string()))
+    });
+    static PIN: Envar<Secret<u32>> = Envar::on_demand("TEST_SECRET_PIN", || EnvarDef::Unset);
+
+    set_env_var("TEST_SECRET_TOKEN", "s3cr3t");
+    let token = TOKEN.value().unwrap();
+    assert_eq!(token.expose(), "s3cr3t");
+    assert_eq!(format!("{:?} {}", token, token), "*** ***");
+    assert!(TOKEN.is_sensitive());
+    assert!(!TOKEN.explain().to_string().contains("s3cr3t"));
+    assert_eq!(TOKEN.explain().raw.as_deref(), Some("***"));
+
+    static PASSWORD: Envar<String> =
+        Envar::on_demand("TEST_SECRET_PASSWORD", || EnvarDef::Unset).sensitive();
+    set_env_var("TEST_SECRET_PASSWORD", "hunter2");
+    let explanation = PASSWORD.explain();
+    assert_eq!(explanation.lookups[0].raw.as_deref(), Some("***"));
+    assert_eq!(explanation.outcome, Ok("***".to_string()));
+    assert!(!explanation.to_string().contains("hunter2"));
+    clear_env_var("TEST_SECRET_PASSWORD");
+
+    set_env_var("TEST_SECRET_PIN", "12a4");
+    let error = PIN.value().unwrap_err();
+    assert!(matches!(&error, EnvarError::ParseError { value, .. } if value == "***"));
+    assert!(!error.to_string().contains("12a4"));
+
+    static LIMIT: Envar<u64> =
+        Envar::on_demand("TEST_SECRET_LIMIT", || EnvarDef::Unset).sensitive();
+    set_env_var("TEST_SECRET_LIMIT", "secret123");
+    let error = LIMIT.value().unwrap_err();
+    assert!(matches!(&error, EnvarError::ParseError { value, .. } if value == "***"));
+    assert!(!error.to_string().contains("secret123"));
+    clear_env_var("TEST_SECRET_LIMIT");
+
+    #[cfg(feature = "docgen")]
+    {
+        let template = crate::docgen::env_template_of(&[&TOKEN]);
+        assert!(!template.contains("fallback-token"));
+        let schema = crate::docgen::json_schema_of(&[&TOKEN]);
+        assert!(!schema.contains("fallback-token"));
+        assert!(schema.contains("\"writeOnly\": true"));
+    }
+
+    clear_env_var("TEST_SECRET_TOKEN");
+    clear_env_var("TEST_SECRET_PIN");
+}
+
+#[test]
+fn test_clear_cache() {
+    let _lock = get_test_lock();
+
+    static CACHED_TOKEN: Envar<crate::Secret<String>> =
+        Envar::on_demand("TEST_CLEAR_CACHE_TOKEN", || EnvarDef::Unset);
+    set_env_var("TEST_CLEAR_CACHE_TOKEN", "t0k3n");
+    CACHED_TOKEN.value().unwrap();
+    CACHED_TOKEN.value().unwrap();
+    let hits = CACHED_TOKEN.stats().cache_hits;
+    assert!(hits >= 1);
+
+    CACHED_TOKEN.clear_cache();
+    assert_eq!(CACHED_TOKEN.value().unwrap().expose(), "t0k3n");
+    assert_eq!(CACHED_TOKEN.stats().cache_hits, hits);
+
+    // the recorded raw value goes with the cache
+    assert_eq!(CACHED_TOKEN.raw().unwrap(), "t0k3n");
+    CACHED_TOKEN.clear_cache();
+    assert_eq!(CACHED_TOKEN.provenance(), None);
+    clear_env_var("TEST_CLEAR_CACHE_TOKEN");
+    assert!(matches!(CACHED_TOKEN.raw(), Err(EnvarError::NotSet(_))));
+}
+
+#[cfg(feature = "zeroize")]
+#[test]
+fn test_secret_zeroize() {
+    use crate::SecretValue;
+
+    let mut value = "s3cr3t".to_string();
+    value.wipe();
+    assert!(value.is_empty());
+    let mut bytes = vec![1u8, 2, 3];
+    bytes.wipe();
+    assert!(bytes.is_empty());
+    let mut raw = std::ffi::OsString::from("s3cr3t");
+    raw.wipe();
+    assert!(raw.is_empty());
+}
+
+#[test]
+fn test_env_source() {
+    use std::collections::HashMap;
+    let _lock = get_test_lock();
+
+    static FIXED: [(&str, &str); 1] = [("TEST_SOURCE_FIXED", "7")];
+    static FIXED_VAR: Envar<u32> =
+        Envar::on_demand("TEST_SOURCE_FIXED", || EnvarDef::Unset).source(&FIXED);
+    static GLOBAL_VAR: Envar<u32> = Envar::on_demand("TEST_SOURCE_GLOBAL", || EnvarDef::Default(1));
+
+    set_env_var("TEST_SOURCE_FIXED", "1");
+    assert_eq!(FIXED_VAR.value().unwrap(), 7);
+    assert_eq!(FIXED_VAR.explain().lookups[0].source, "static pairs");
+
+    set_env_var("TEST_SOURCE_GLOBAL", "2");
+    assert_eq!(GLOBAL_VAR.value().unwrap(), 2);
+    crate::set_source(HashMap::from([(
+        "TEST_SOURCE_GLOBAL".to_string(),
+        "3".to_string(),
+    )]));
+    assert_eq!(GLOBAL_VAR.value().unwrap(), 3);
+    assert_eq!(GLOBAL_VAR.explain().lookups[0].source, "in-memory map");
+    let map = crate::collect_prefix::<u32>("TEST_SOURCE_").unwrap();
+    assert_eq!(map.get("GLOBAL"), Some(&3));
+    assert_eq!(map.len(), 1);
+    // override layers still shadow the source
+    let layer = crate::push_layer([("TEST_SOURCE_GLOBAL", "4")]);
+    assert_eq!(GLOBAL_VAR.value().unwrap(), 4);
+    drop(layer);
+    crate::reset_source();
+
+    assert_eq!(GLOBAL_VAR.value().unwrap(), 2);
+    clear_env_var("TEST_SOURCE_FIXED");
+    clear_env_var("TEST_SOURCE_GLOBAL");
+}
+
+#[test]
+fn test_layered_source() {
+    use crate::{EnvSource, LayeredSource};
+    use std::collections::HashMap;
+    let _lock = get_test_lock();
+
+    let path = std::env::temp_dir().join(format!("typed-env-layered-{}.env", std::process::id()));
+    std::fs::write(
+        &path,
+        "TEST_LAYERED_PORT=9000\nTEST_LAYERED_HOST=dotenv.local\n",
+    )
+    .unwrap();
+
+    static DEFAULTS: [(&str, &str); 2] =
+        [("TEST_LAYERED_PORT", "8080"), ("TEST_LAYERED_WORKERS", "4")];
+    let source = LayeredSource::new()
+        .layer(
+            "env",
+            HashMap::from([("TEST_LAYERED_HOST".to_string(), "env.local".to_string())]),
+        )
+        .layer(".env", crate::read_env_file(&path).unwrap())
+        .layer("defaults", DEFAULTS);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(source.layer_of("TEST_LAYERED_HOST"), Some("env"));
+    assert_eq!(source.layer_of("TEST_LAYERED_PORT"), Some(".env"));
+    assert_eq!(source.layer_of("TEST_LAYERED_WORKERS"), Some("defaults"));
+    assert_eq!(source.layer_of("TEST_LAYERED_MISSING"), None);
+    assert_eq!(source.vars().len(), 3);
+
+    static LAYERED_PORT: Envar<u16> = Envar::on_demand("TEST_LAYERED_PORT", || EnvarDef::Unset);
+    crate::set_source(source);
+    assert_eq!(LAYERED_PORT.value().unwrap(), 9000);
+    assert_eq!(
+        LAYERED_PORT.explain().lookups[0].source,
+        "layered source (.env)"
+    );
+    crate::reset_source();
+}
+
+#[test]
+fn test_file_fallback() {
+    let _lock = get_test_lock();
+    let path = std::env::temp_dir().join(format!("typed-env-secret-{}", std::process::id()));
+    std::fs::write(&path, "hunter2\n").unwrap();
+
+    static DB_PASSWORD: Envar<String> =
+        Envar::on_demand("TEST_FILE_DB_PASSWORD", || EnvarDef::Unset).with_file_fallback();
+    clear_env_var("TEST_FILE_DB_PASSWORD");
+    set_env_var("TEST_FILE_DB_PASSWORD_FILE", path.to_str().unwrap());
+    assert_eq!(DB_PASSWORD.value().unwrap(), "hunter2");
+    let explanation = DB_PASSWORD.explain();
+    assert_eq!(explanation.lookups[1].name, "TEST_FILE_DB_PASSWORD_FILE");
+    assert_eq!(explanation.raw.as_deref(), Some("hunter2"));
+
+    // the variable itself takes precedence over the file
+    set_env_var("TEST_FILE_DB_PASSWORD", "direct");
+    assert_eq!(DB_PASSWORD.value().unwrap(), "direct");
+
+    // sensitive variables consult `_FILE` without asking
+    static API_TOKEN: Envar<String> =
+        Envar::on_demand("TEST_FILE_API_TOKEN", || EnvarDef::Unset).sensitive();
+    static PLAIN: Envar<String> = Envar::on_demand("TEST_FILE_PLAIN", || EnvarDef::Unset);
+    clear_env_var("TEST_FILE_API_TOKEN");
+    clear_env_var("TEST_FILE_PLAIN");
+    set_env_var("TEST_FILE_API_TOKEN_FILE", path.to_str().unwrap());
+    set_env_var("TEST_FILE_PLAIN_FILE", path.to_str().unwrap());
+    assert_eq!(API_TOKEN.value().unwrap(), "hunter2");
+    assert!(matches!(PLAIN.value(), Err(EnvarError::NotSet(_))));
+
+    std::fs::remove_file(&path).unwrap();
+    match API_TOKEN.value() {
+        Err(e @ EnvarError::FileError { .. }) => {
+            let message = e.to_string();
+            assert!(message.contains("TEST_FILE_API_TOKEN_FILE"), "{}", message);
+            assert!(message.contains(path.to_str().unwrap()), "{}", message);
+        }
+        other => panic!("expected a file error, got {:?}", other),
+    }
+
+    clear_env_var("TEST_FILE_DB_PASSWORD");
+    clear_env_var("TEST_FILE_DB_PASSWORD_FILE");
+    clear_env_var("TEST_FILE_API_TOKEN_FILE");
+    clear_env_var("TEST_FILE_PLAIN_FILE");
+}
+
+#[test]
+fn test_provenance() {
+    use crate::Provenance;
+    let _lock = get_test_lock();
+
+    static WORKERS: Envar<u32> = Envar::on_demand_with_aliases(
+        "TEST_PROVENANCE_WORKERS",
+        &["TEST_PROVENANCE_THREADS"],
+        || EnvarDef::Default(4),
+    )
+    .with_file_fallback()
+    .on_parse_error(crate::OnParseError::UseDefault);
+    clear_env_var("TEST_PROVENANCE_WORKERS");
+    clear_env_var("TEST_PROVENANCE_THREADS");
+    assert_eq!(WORKERS.provenance(), None);
+    assert_eq!(WORKERS.value().unwrap(), 4);
+    assert_eq!(WORKERS.provenance(), Some(Provenance::Default));
+
+    set_env_var("TEST_PROVENANCE_THREADS", "8");
+    assert_eq!(WORKERS.value().unwrap(), 8);
+    assert_eq!(
+        WORKERS.provenance(),
+        Some(Provenance::Environment {
+            name: "TEST_PROVENANCE_THREADS".to_string(),
+            origin: "process environment".to_string(),
+        })
+    );
+    assert_eq!(
+        WORKERS.provenance().unwrap().to_string(),
+        "TEST_PROVENANCE_THREADS in process environment"
+    );
+
+    // an invalid value falls back to the default
+    set_env_var("TEST_PROVENANCE_WORKERS", "many");
+    assert_eq!(WORKERS.value().unwrap(), 4);
+    assert_eq!(WORKERS.provenance(), Some(Provenance::Default));
+    clear_env_var("TEST_PROVENANCE_WORKERS");
+    assert_eq!(WORKERS.value().unwrap(), 8);
+    assert!(matches!(
+        WORKERS.provenance(),
+        Some(Provenance::Environment { .. })
+    ));
+
+    let layer = crate::push_layer([("TEST_PROVENANCE_WORKERS", "16")]);
+    assert_eq!(WORKERS.value().unwrap(), 16);
+    assert!(matches!(
+        WORKERS.provenance(),
+        Some(Provenance::Layer { name, .. }) if name == "TEST_PROVENANCE_WORKERS"
+    ));
+    drop(layer);
+
+    let path = std::env::temp_dir().join(format!("typed-env-provenance-{}", std::process::id()));
+    std::fs::write(&path, "32\n").unwrap();
+    clear_env_var("TEST_PROVENANCE_THREADS");
+    set_env_var("TEST_PROVENANCE_WORKERS_FILE", path.to_str().unwrap());
+    assert_eq!(WORKERS.value().unwrap(), 32);
+    assert_eq!(
+        WORKERS.provenance(),
+        Some(Provenance::File {
+            name: "TEST_PROVENANCE_WORKERS".to_string(),
+            path: path.to_str().unwrap().to_string(),
+        })
+    );
+    clear_env_var("TEST_PROVENANCE_WORKERS_FILE");
+    std::fs::remove_file(&path).unwrap();
+
+    {
+        let _scoped = crate::test::set_scoped("TEST_PROVENANCE_WORKERS", "64");
+        assert_eq!(WORKERS.value().unwrap(), 64);
+        assert!(matches!(
+            WORKERS.provenance(),
+            Some(Provenance::TestScope { .. })
+        ));
+    }
+
+    WORKERS.set_override(128);
+    assert_eq!(WORKERS.provenance(), Some(Provenance::Override));
+    WORKERS.clear_override();
+    assert_eq!(WORKERS.value().unwrap(), 4);
+    assert_eq!(WORKERS.provenance(), Some(Provenance::Default));
+
+    // `on_startup` values keep the provenance they were frozen with
+    static REGION: Envar<String> = Envar::on_startup("TEST_PROVENANCE_REGION", || {
+        EnvarDef::Default("local".to_string())
+    });
+    set_env_var("TEST_PROVENANCE_REGION", "eu-west-1");
+    assert_eq!(REGION.value().unwrap(), "eu-west-1");
+    clear_env_var("TEST_PROVENANCE_REGION");
+    assert_eq!(REGION.value().unwrap(), "eu-west-1");
+    assert!(matches!(
+        REGION.provenance(),
+        Some(Provenance::Environment { .. })
+    ));
+}
+
+#[test]
+fn test_raw() {
+    let _lock = get_test_lock();
+
+    static PORT: Envar<u16> = Envar::on_demand("TEST_RAW_PORT", || EnvarDef::Default(8080));
+    clear_env_var("TEST_RAW_PORT");
+    assert!(matches!(PORT.raw(), Err(EnvarError::NotSet(_))));
+
+    set_env_var("TEST_RAW_PORT", "9090");
+    assert_eq!(PORT.value().unwrap(), 9090);
+    assert_eq!(PORT.raw().unwrap(), "9090");
+    assert_eq!(PORT.raw_os().unwrap(), "9090");
+    assert_eq!(PORT.raw_redacted().unwrap(), "9090");
+
+    static TOKEN: Envar<String> =
+        Envar::on_demand("TEST_RAW_TOKEN", || EnvarDef::Unset).sensitive();
+    set_env_var("TEST_RAW_TOKEN", "hunter2");
+    assert_eq!(TOKEN.raw().unwrap(), "hunter2");
+    assert_eq!(TOKEN.raw_redacted().unwrap(), "***");
+    clear_env_var("TEST_RAW_TOKEN");
+
+    PORT.set_override(1);
+    assert!(matches!(PORT.raw(), Err(EnvarError::NotSet(_))));
+    PORT.clear_override();
+    assert_eq!(PORT.raw().unwrap(), "9090");
+
+    set_env_var("TEST_RAW_PORT", "http");
+    assert!(matches!(PORT.raw(), Err(EnvarError::ParseError { .. })));
+
+    // the raw value of a frozen `on_startup` variable is the one it was parsed from
+    static REGION: Envar<String> = Envar::on_startup("TEST_RAW_REGION", || EnvarDef::Unset);
+    set_env_var("TEST_RAW_REGION", "eu-west-1");
+    assert_eq!(REGION.value().unwrap(), "eu-west-1");
+    set_env_var("TEST_RAW_REGION", "us-east-1");
+    assert_eq!(REGION.raw().unwrap(), "eu-west-1");
+
+    clear_env_var("TEST_RAW_PORT");
+    clear_env_var("TEST_RAW_REGION");
+}
+
+#[test]
+fn test_path_validators() {
+    use std::path::PathBuf;
+    let _lock = get_test_lock();
+    let dir = std::env::temp_dir();
+    let file = dir.join(format!("typed-env-path-{}", std::process::id()));
+    std::fs::write(&file, "").unwrap();
+
+    static CONFIG_PATH: Envar<PathBuf> = Envar::on_demand("TEST_PATH_CONFIG", || EnvarDef::Unset)
+        .must_be_file()
+        .must_be_readable();
+    static DATA_DIR: Envar<PathBuf> =
+        Envar::on_demand("TEST_PATH_DATA_DIR", || EnvarDef::Unset).must_be_dir();
+
+    set_env_var("TEST_PATH_CONFIG", file.to_str().unwrap());
+    set_env_var("TEST_PATH_DATA_DIR", dir.to_str().unwrap());
+    assert_eq!(CONFIG_PATH.value().unwrap(), file);
+    assert_eq!(DATA_DIR.value().unwrap(), dir);
+
+    // a file is not a directory
+    set_env_var("TEST_PATH_DATA_DIR", file.to_str().unwrap());
+    match DATA_DIR.value() {
+        Err(EnvarError::ParseError {
+            varname,
+            value,
+            reason,
+            ..
+        }) => {
+            assert_eq!(varname, "TEST_PATH_DATA_DIR");
+            assert_eq!(value, file.to_str().unwrap());
+            assert!(reason.as_str().contains("is not a directory"), "{}", reason);
+        }
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+
+    std::fs::remove_file(&file).unwrap();
+    CONFIG_PATH.clear_cache();
+    match CONFIG_PATH.value() {
+        Err(EnvarError::ParseError { reason, .. }) => {
+            assert!(reason.as_str().contains("does not exist"), "{}", reason);
+            assert!(
+                reason.as_str().contains(file.to_str().unwrap()),
+                "{}",
+                reason
+            );
+        }
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+
+    clear_env_var("TEST_PATH_CONFIG");
+    clear_env_var("TEST_PATH_DATA_DIR");
+}
+
+#[test]
+fn test_with_validator() {
+    let _lock = get_test_lock();
+
+    fn unprivileged(port: &u16) -> Result<(), String> {
+        if *port < 1024 {
+            return Err(format!("port {} is privileged", port));
+        }
+        Ok(())
+    }
+
+    static PORT: Envar<u16> = Envar::on_demand("TEST_VALIDATOR_PORT", || EnvarDef::Default(8080))
+        .with_validator(unprivileged)
+        .with_validator(|port| {
+            if *port == 9999 {
+                return Err("port 9999 is reserved".to_string());
+            }
+            Ok(())
+        });
+    static LENIENT_PORT: Envar<u16> =
+        Envar::on_demand("TEST_VALIDATOR_PORT", || EnvarDef::Default(8080))
+            .with_validator(unprivileged)
+            .on_parse_error(OnParseError::UseDefault);
+
+    set_env_var("TEST_VALIDATOR_PORT", "3000");
+    assert_eq!(PORT.value().unwrap(), 3000);
+
+    set_env_var("TEST_VALIDATOR_PORT", "80");
+    match PORT.value() {
+        Err(e @ EnvarError::ParseError { .. }) => {
+            assert!(e.to_string().contains("TEST_VALIDATOR_PORT"), "{}", e);
+            let EnvarError::ParseError { reason, .. } = e else {
+                unreachable!()
+            };
+            assert_eq!(reason.as_str(), "port 80 is privileged");
+        }
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+    assert_eq!(LENIENT_PORT.value().unwrap(), 8080);
+
+    set_env_var("TEST_VALIDATOR_PORT", "9999");
+    assert!(matches!(
+        PORT.value(),
+        Err(EnvarError::ParseError { reason, .. }) if reason.as_str() == "port 9999 is reserved"
+    ));
+
+    // the default is not validated
+    clear_env_var("TEST_VALIDATOR_PORT");
+    assert_eq!(PORT.value().unwrap(), 8080);
+}
+
+#[test]
+fn test_in_range() {
+    let _lock = get_test_lock();
+    static PORT: Envar<u16> =
+        Envar::on_demand("TEST_RANGE_PORT", || EnvarDef::Unset).in_range(1..=65535);
+    static RATIO: Envar<f64> =
+        Envar::on_demand("TEST_RANGE_RATIO", || EnvarDef::Default(0.5)).in_range(0.0..=1.0);
+
+    set_env_var("TEST_RANGE_PORT", "8080");
+    assert_eq!(PORT.value().unwrap(), 8080);
+    set_env_var("TEST_RANGE_PORT", "0");
+    match PORT.value() {
+        Err(e @ EnvarError::OutOfRange { .. }) => {
+            assert_eq!(e.to_string(), "TEST_RANGE_PORT=0 is out of range 1..=65535");
+            assert_eq!(e.exit_code(), crate::EX_CONFIG);
+        }
+        other => panic!("expected an out-of-range error, got {:?}", other),
+    }
+
+    set_env_var("TEST_RANGE_RATIO", "1");
+    assert_eq!(RATIO.value().unwrap(), 1.0);
+    set_env_var("TEST_RANGE_RATIO", "1.5");
+    assert_eq!(
+        RATIO.value().unwrap_err().to_string(),
+        "TEST_RANGE_RATIO=1.5 is out of range 0..=1"
+    );
+    set_env_var("TEST_RANGE_RATIO", "NaN");
+    assert!(matches!(RATIO.value(), Err(EnvarError::OutOfRange { .. })));
+
+    clear_env_var("TEST_RANGE_PORT");
+    clear_env_var("TEST_RANGE_RATIO");
+    assert_eq!(RATIO.value().unwrap(), 0.5);
+}
+
+#[test]
+fn test_one_of() {
+    let _lock = get_test_lock();
+    static LOG_LEVEL: Envar<String> = Envar::on_demand("TEST_ONE_OF_LEVEL", || {
+        EnvarDef::Default("info".to_string())
+    })
+    .one_of(&["debug", "info", "warn", "error"]);
+
+    set_env_var("TEST_ONE_OF_LEVEL", "warn");
+    assert_eq!(LOG_LEVEL.value().unwrap(), "warn");
+
+    set_env_var("TEST_ONE_OF_LEVEL", "wran");
+    assert_eq!(
+        LOG_LEVEL.value().unwrap_err().to_string(),
+        "TEST_ONE_OF_LEVEL=wran is not one of debug, info, warn, error (did you mean warn?)"
+    );
+    set_env_var("TEST_ONE_OF_LEVEL", "INFO");
+    assert!(matches!(
+        LOG_LEVEL.value(),
+        Err(EnvarError::NotAllowed {
+            suggestion: Some("info"),
+            ..
+        })
+    ));
+    set_env_var("TEST_ONE_OF_LEVEL", "verbose");
+    assert_eq!(
+        LOG_LEVEL.value().unwrap_err().to_string(),
+        "TEST_ONE_OF_LEVEL=verbose is not one of debug, info, warn, error"
+    );
+
+    clear_env_var("TEST_ONE_OF_LEVEL");
+    assert_eq!(LOG_LEVEL.value().unwrap(), "info");
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, typed_env_derive::EnvarEnum)]
+enum CacheMode {
+    Memory,
+    #[envar(rename = "redis", alias = "remote", alias = "kv")]
+    Redis,
+    Disabled,
+}
+
+#[test]
+fn test_derive_envar_enum() {
+    let _lock = get_test_lock();
+    static CACHE_MODE: Envar<CacheMode> = Envar::on_demand("TEST_ENUM_CACHE_MODE", || {
+        EnvarDef::Default(CacheMode::Memory)
+    });
+
+    set_env_var("TEST_ENUM_CACHE_MODE", " DISABLED ");
+    assert_eq!(CACHE_MODE.value().unwrap(), CacheMode::Disabled);
+    set_env_var("TEST_ENUM_CACHE_MODE", "Redis");
+    assert_eq!(CACHE_MODE.value().unwrap(), CacheMode::Redis);
+    set_env_var("TEST_ENUM_CACHE_MODE", "kv");
+    assert_eq!(CACHE_MODE.value().unwrap(), CacheMode::Redis);
+
+    set_env_var("TEST_ENUM_CACHE_MODE", "disk");
+    match CACHE_MODE.value() {
+        Err(EnvarError::ParseError {
+            typename, reason, ..
+        }) => {
+            assert_eq!(typename, "CacheMode");
+            assert_eq!(reason.as_str(), "expected one of Memory, redis, Disabled");
+        }
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+
+    clear_env_var("TEST_ENUM_CACHE_MODE");
+    assert_eq!(CACHE_MODE.value().unwrap(), CacheMode::Memory);
+    assert_eq!(
+        <crate::EnvarParser<CacheMode> as crate::EnvarParse<CacheMode>>::render(&CacheMode::Redis),
+        Some("redis".to_string())
+    );
+}
+
+#[test]
+fn test_from_str_envar() {
+    use crate::FromStrEnvar;
+    let _lock = get_test_lock();
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Version(u32, u32);
+
+    impl std::str::FromStr for Version {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let (major, minor) = s.split_once('.').ok_or("expected major.minor")?;
+            Ok(Version(
+                major.parse().map_err(|_| "invalid major version")?,
+                minor.parse().map_err(|_| "invalid minor version")?,
+            ))
+        }
+    }
+
+    static API_VERSION: Envar<FromStrEnvar<Version>> =
+        Envar::on_demand("TEST_FROM_STR_VERSION", || EnvarDef::Unset);
+
+    set_env_var("TEST_FROM_STR_VERSION", "2.1");
+    assert_eq!(*API_VERSION.value().unwrap(), Version(2, 1));
+
+    set_env_var("TEST_FROM_STR_VERSION", "2");
+    match API_VERSION.value() {
+        Err(EnvarError::ParseError { value, reason, .. }) => {
+            assert_eq!(value, "2");
+            assert_eq!(reason.as_str(), "expected major.minor");
+        }
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+    clear_env_var("TEST_FROM_STR_VERSION");
+}
+
+#[cfg(feature = "list")]
+#[test]
+fn test_map_envar() {
+    use crate::{ColonPairMap, CommaMap, DuplicateKeys, MapEnvar, MapEnvarConfig};
+    let _lock = get_test_lock();
+
+    static LABELS: Envar<MapEnvar<String, String, CommaMap>> =
+        Envar::on_demand("TEST_MAP_LABELS", || EnvarDef::Unset);
+    set_env_var("TEST_MAP_LABELS", "env=prod, team = core,,region=eu=west");
+    let labels = LABELS.value().unwrap();
+    assert_eq!(labels.len(), 3);
+    assert_eq!(labels["team"], "core");
+    assert_eq!(labels["region"], "eu=west");
+    assert_eq!(
+        <crate::EnvarParser<MapEnvar<String, String, CommaMap>> as crate::EnvarParse<_>>::render(
+            &labels
+        )
+        .unwrap(),
+        "env=prod,region=eu=west,team=core"
+    );
+
+    set_env_var("TEST_MAP_LABELS", "env=prod,env=dev");
+    assert!(matches!(
+        LABELS.value(),
+        Err(EnvarError::ParseError { reason, .. }) if reason.as_str() == "duplicate key \"env\""
+    ));
+    set_env_var("TEST_MAP_LABELS", "env");
+    assert!(matches!(
+        LABELS.value(),
+        Err(EnvarError::ParseError { reason, .. }) if reason.as_str() == "expected key=value, found \"env\""
+    ));
+
+    struct LastWins;
+    impl MapEnvarConfig for LastWins {
+        const PAIR_SEP: &'static str = ";";
+        const KV_SEP: &'static str = "=";
+        const DUPLICATES: DuplicateKeys = DuplicateKeys::Last;
+    }
+    static LIMITS: Envar<MapEnvar<String, u32, LastWins>> =
+        Envar::on_demand("TEST_MAP_LIMITS", || EnvarDef::Unset);
+    set_env_var("TEST_MAP_LIMITS", "read=10;write=5;read=20");
+    assert_eq!(LIMITS.value().unwrap()["read"], 20);
+    set_env_var("TEST_MAP_LIMITS", "read=many");
+    assert!(matches!(
+        LIMITS.value(),
+        Err(EnvarError::ParseError {
+            typename: "u32",
+            ..
+        })
+    ));
+
+    static HEADERS: Envar<MapEnvar<String, String, ColonPairMap>> =
+        Envar::on_demand("TEST_MAP_HEADERS", || EnvarDef::Unset);
+    set_env_var("TEST_MAP_HEADERS", "X-Team: core, Accept: */*");
+    assert_eq!(HEADERS.value().unwrap()["Accept"], "*/*");
+
+    clear_env_var("TEST_MAP_LABELS");
+    clear_env_var("TEST_MAP_LIMITS");
+    clear_env_var("TEST_MAP_HEADERS");
+}
+
+#[cfg(feature = "list")]
+#[test]
+fn test_set_envar() {
+    use crate::{CommaList, SetEnvar};
+    use std::collections::{BTreeSet, HashSet};
+    let _lock = get_test_lock();
+
+    static ORIGINS: Envar<SetEnvar<HashSet<String>, CommaList>> =
+        Envar::on_demand("TEST_SET_ORIGINS", || EnvarDef::Unset);
+    set_env_var("TEST_SET_ORIGINS", "a.com, b.com,a.com");
+    let origins = ORIGINS.value().unwrap();
+    assert_eq!(origins.len(), 2);
+    assert!(origins.contains("b.com"));
+
+    static PORTS: Envar<SetEnvar<BTreeSet<u16>, CommaList>> =
+        Envar::on_demand("TEST_SET_PORTS", || EnvarDef::Unset);
+    set_env_var("TEST_SET_PORTS", "443,80,8080,80");
+    let ports = PORTS.value().unwrap();
+    assert_eq!(
+        ports.iter().copied().collect::<Vec<_>>(),
+        vec![80, 443, 8080]
+    );
+    assert_eq!(
+        <crate::EnvarParser<SetEnvar<BTreeSet<u16>, CommaList>> as crate::EnvarParse<_>>::render(
+            &ports
+        )
+        .unwrap(),
+        "80,443,8080"
+    );
+
+    static FLAGS: Envar<SetEnvar<BTreeSet<String>, CommaList, true>> =
+        Envar::on_demand("TEST_SET_FLAGS", || EnvarDef::Unset);
+    set_env_var("TEST_SET_FLAGS", "beta,dark-mode");
+    assert_eq!(FLAGS.value().unwrap().len(), 2);
+    set_env_var("TEST_SET_FLAGS", "beta,dark-mode,beta");
+    assert!(matches!(
+        FLAGS.value(),
+        Err(EnvarError::ParseError { reason, .. }) if reason.as_str() == "duplicate item \"beta\""
+    ));
+
+    clear_env_var("TEST_SET_ORIGINS");
+    clear_env_var("TEST_SET_PORTS");
+    clear_env_var("TEST_SET_FLAGS");
+}
+
+#[cfg(feature = "list")]
+#[test]
+fn test_dyn_list_envar() {
+    use crate::{DynListConfig, DynListEnvar};
+    let _lock = get_test_lock();
+
+    static SHARDS: DynListEnvar<u32> = DynListEnvar::new(
+        Envar::on_demand("TEST_DYN_LIST_SHARDS", || EnvarDef::Unset),
+        DynListConfig::new("|"),
+    );
+    set_env_var("TEST_DYN_LIST_SHARDS", "1| 2||3");
+    assert_eq!(SHARDS.value().unwrap(), vec![1, 2, 3]);
+    set_env_var("TEST_DYN_LIST_SHARDS", "1|two");
+    assert!(matches!(
+        SHARDS.value(),
+        Err(EnvarError::ParseError { varname, .. }) if varname == "TEST_DYN_LIST_SHARDS"
+    ));
+    clear_env_var("TEST_DYN_LIST_SHARDS");
+    assert!(matches!(SHARDS.value(), Err(EnvarError::NotSet(_))));
+
+    // the separator comes from another variable
+    set_env_var("TEST_DYN_LIST_SEP", "::");
+    set_env_var("TEST_DYN_LIST_HOSTS", "a::::b");
+    let sep = std::env::var("TEST_DYN_LIST_SEP").unwrap();
+    let hosts: DynListEnvar<String> = DynListEnvar::new(
+        Envar::on_demand("TEST_DYN_LIST_HOSTS", || EnvarDef::Unset),
+        DynListConfig::with_sep(sep).keep_empty().keep_whitespace(),
+    );
+    assert_eq!(hosts.config().sep(), "::");
+    assert_eq!(hosts.value().unwrap(), vec!["a", "", "b"]);
+
+    // an empty separator, e.g. from an unset variable, is rejected up front
+    let empty = std::panic::catch_unwind(|| DynListConfig::with_sep(String::new()));
+    let message = empty.unwrap_err();
+    assert_eq!(
+        message.downcast_ref::<&str>(),
+        Some(&"DynListConfig: the separator must not be empty")
+    );
+
+    clear_env_var("TEST_DYN_LIST_SEP");
+    clear_env_var("TEST_DYN_LIST_HOSTS");
+}
+
+#[cfg(feature = "list")]
+#[test]
+fn test_path_list() {
+    use crate::PathList;
+    use std::path::PathBuf;
+    let _lock = get_test_lock();
+
+    static SEARCH_PATH: Envar<ListEnvar<PathBuf, PathList>> =
+        Envar::on_demand("TEST_PATH_LIST", || EnvarDef::Unset);
+    let paths = [
+        PathBuf::from("/usr/local/bin"),
+        PathBuf::from("/opt/my tools"),
+    ];
+    let joined = std::env::join_paths(&paths).unwrap();
+    set_env_var("TEST_PATH_LIST", joined.to_str().unwrap());
+    assert_eq!(SEARCH_PATH.value().unwrap().to_vec(), paths.to_vec());
+    assert_eq!(
+        <crate::EnvarParser<ListEnvar<PathBuf, PathList>> as crate::EnvarParse<_>>::render(
+            &SEARCH_PATH.value().unwrap()
+        )
+        .unwrap(),
+        joined.to_str().unwrap()
+    );
+    clear_env_var("TEST_PATH_LIST");
+}
+
+#[cfg(feature = "list")]
+#[test]
+fn test_list_escaped_separators() {
+    let _lock = get_test_lock();
+
+    struct EscapedList;
+    impl ListEnvarConfig for EscapedList {
+        const SEP: &'static str = ",";
+        const FILTER_EMPTY_STR: bool = true;
+        const FILTER_WHITESPACE: bool = true;
+        const ESCAPE: Option<char> = Some('\\');
+    }
+    struct QuotedList;
+    impl ListEnvarConfig for QuotedList {
+        const SEP: &'static str = ",";
+        const FILTER_EMPTY_STR: bool = true;
+        const FILTER_WHITESPACE: bool = true;
+        const QUOTE: Option<char> = Some('"');
+    }
+    type Render<C> = crate::EnvarParser<ListEnvar<String, C>>;
+
+    static ESCAPED: Envar<ListEnvar<String, EscapedList>> =
+        Envar::on_demand("TEST_LIST_ESCAPED", || EnvarDef::Unset);
+    set_env_var("TEST_LIST_ESCAPED", r"a\,b, c\\,d\");
+    let escaped = ESCAPED.value().unwrap();
+    assert_eq!(escaped.to_vec(), vec!["a,b", r"c\", r"d\"]);
+    assert_eq!(
+        <Render<EscapedList> as crate::EnvarParse<_>>::render(&escaped).unwrap(),
+        r"a\,b,c\\,d\\"
+    );
+
+    static QUOTED: Envar<ListEnvar<String, QuotedList>> =
+        Envar::on_demand("TEST_LIST_QUOTED", || EnvarDef::Unset);
+    set_env_var(
+        "TEST_LIST_QUOTED",
+        r#""https://a.com/?x=1,y=2", https://b.com,"""#,
+    );
+    let quoted = QUOTED.value().unwrap();
+    assert_eq!(
+        quoted.to_vec(),
+        vec!["https://a.com/?x=1,y=2", "https://b.com"]
+    );
+    assert_eq!(
+        <Render<QuotedList> as crate::EnvarParse<_>>::render(&quoted).unwrap(),
+        r#""https://a.com/?x=1,y=2",https://b.com"#
+    );
+
+    // without ESCAPE or QUOTE, backslashes and quotes are ordinary characters
+    static PLAIN: Envar<ListEnvar<String, crate::CommaList>> =
+        Envar::on_demand("TEST_LIST_ESCAPED", || EnvarDef::Unset);
+    assert_eq!(
+        PLAIN.value().unwrap().to_vec(),
+        vec![r"a\", "b", r"c\\", r"d\"]
+    );
+
+    clear_env_var("TEST_LIST_ESCAPED");
+    clear_env_var("TEST_LIST_QUOTED");
+}
+
+#[cfg(feature = "list")]
+#[test]
+fn test_list_length_limits() {
+    let _lock = get_test_lock();
+
+    struct Peers;
+    impl ListEnvarConfig for Peers {
+        const SEP: &'static str = ",";
+        const FILTER_EMPTY_STR: bool = true;
+        const FILTER_WHITESPACE: bool = true;
+        const MIN_LEN: usize = 1;
+        const MAX_LEN: usize = 3;
+    }
+
+    static PEERS: Envar<ListEnvar<String, Peers>> =
+        Envar::on_demand("TEST_LIST_LEN_PEERS", || EnvarDef::Unset);
+    set_env_var("TEST_LIST_LEN_PEERS", "a,b,c");
+    assert_eq!(PEERS.value().unwrap().len(), 3);
+
+    set_env_var("TEST_LIST_LEN_PEERS", " , ");
+    let error = PEERS.value().unwrap_err();
+    assert!(matches!(error, EnvarError::ListLength { len: 0, .. }));
+    assert_eq!(
+        error.to_string(),
+        "Environment variable TEST_LIST_LEN_PEERS has 0 item(s), expected between 1 and 3"
+    );
+
+    set_env_var("TEST_LIST_LEN_PEERS", "a,b,c,d");
+    assert!(matches!(
+        PEERS.value(),
+        Err(EnvarError::ListLength { len: 4, .. })
+    ));
+
+    // lazy lists check the limits too, without parsing the items
+    static LAZY_PEERS: Envar<crate::LazyList<u16, Peers>> =
+        Envar::on_demand("TEST_LIST_LEN_PEERS", || EnvarDef::Unset);
+    assert!(matches!(
+        LAZY_PEERS.value(),
+        Err(EnvarError::ListLength { len: 4, .. })
+    ));
+    set_env_var("TEST_LIST_LEN_PEERS", "1,x");
+    let peers = LAZY_PEERS.value().unwrap();
+    assert_eq!(peers.len(), 2);
+    assert!(peers.iter_parse().nth(1).unwrap().is_err());
+    set_env_var("TEST_LIST_LEN_PEERS", "");
+    assert!(matches!(
+        LAZY_PEERS.value(),
+        Err(EnvarError::ListLength { len: 0, .. })
+    ));
+    clear_env_var("TEST_LIST_LEN_PEERS");
+}
+
+#[cfg(feature = "list")]
+#[test]
+fn test_list_into_iter() {
+    let _lock = get_test_lock();
+    static HOSTS: Envar<ListEnvar<String, crate::CommaList>> =
+        Envar::on_demand("TEST_LIST_INTO_ITER", || EnvarDef::Unset);
+    set_env_var("TEST_LIST_INTO_ITER", "a,b");
+
+    let hosts = HOSTS.value().unwrap();
+    let mut seen = vec![];
+    for host in &hosts {
+        seen.push(host.as_str());
+    }
+    assert_eq!(seen, vec!["a", "b"]);
+    assert_eq!(hosts.to_vec(), vec!["a", "b"]);
+
+    let owned: Vec<String> = hosts.clone().into_iter().collect();
+    assert_eq!(owned, vec!["a", "b"]);
+    assert_eq!(hosts.into_vec(), vec!["a", "b"]);
+    clear_env_var("TEST_LIST_INTO_ITER");
+}
+
+#[cfg(feature = "list")]
+#[test]
+fn test_list_default() {
+    use crate::CommaList;
+    let _lock = get_test_lock();
+
+    static PEERS: Envar<ListEnvar<String, CommaList>> =
+        Envar::on_demand("TEST_LIST_DEFAULT", || {
+            EnvarDef::Default(["a", "b"].into_iter().map(String::from).collect())
+        });
+    static PORTS: Envar<ListEnvar<u16, CommaList>> = Envar::on_demand("TEST_LIST_DEFAULT", || {
+        EnvarDef::Default(vec![80, 443].into())
+    });
+    static SHARDS: Envar<ListEnvar<u32, CommaList>> = Envar::on_demand("TEST_LIST_DEFAULT", || {
+        EnvarDef::Default(ListEnvar::new(vec![]))
+    });
+
+    clear_env_var("TEST_LIST_DEFAULT");
+    assert_eq!(PEERS.value().unwrap().to_vec(), vec!["a", "b"]);
+    assert_eq!(PORTS.value().unwrap().to_vec(), vec![80, 443]);
+    assert!(SHARDS.value().unwrap().is_empty());
+}
+
+#[cfg(feature = "list")]
+#[test]
+fn test_list_eq() {
+    use crate::{CharSep, CommaList};
+    let _lock = get_test_lock();
+    static PORTS: Envar<ListEnvar<u16, CommaList>> =
+        Envar::on_demand("TEST_LIST_EQ", || EnvarDef::Unset);
+    set_env_var("TEST_LIST_EQ", "80, 443");
+
+    let ports = PORTS.value().unwrap();
+    assert_eq!(ports, ListEnvar::<u16, CommaList>::new(vec![80, 443]));
+    assert_eq!(ports, vec![80, 443]);
+    assert_ne!(ports, ListEnvar::<u16, CommaList>::new(vec![443, 80]));
+    // the separator is not part of the value
+    assert_eq!(
+        ListEnvar::<u16, CharSep<'|'>>::new(vec![80, 443]),
+        vec![80, 443]
+    );
+
+    #[cfg(feature = "serde")]
+    assert_eq!(serde_json::to_string(&ports).unwrap(), "[80,443]");
+    clear_env_var("TEST_LIST_EQ");
+}
+
+#[cfg(feature = "list")]
+#[test]
+fn test_nested_list() {
+    use crate::{CharSep, SemicolonList};
+    let _lock = get_test_lock();
+
+    static ROUTES: Envar<ListEnvar<ListEnvar<String, CharSep<'|'>>, SemicolonList>> =
+        Envar::on_demand("TEST_NESTED_ROUTES", || EnvarDef::Unset);
+    set_env_var("TEST_NESTED_ROUTES", "a|b|c; d|e;");
+    let routes = ROUTES.value().unwrap();
+    assert_eq!(routes.len(), 2);
+    assert_eq!(routes[0].to_vec(), vec!["a", "b", "c"]);
+    assert_eq!(routes[1].to_vec(), vec!["d", "e"]);
+    assert_eq!(
+        <crate::EnvarParser<ListEnvar<ListEnvar<String, CharSep<'|'>>, SemicolonList>> as crate::EnvarParse<_>>::render(&routes).unwrap(),
+        "a|b|c;d|e"
+    );
+
+    static SHARDS: Envar<ListEnvar<ListEnvar<u32, crate::CommaList>, SemicolonList>> =
+        Envar::on_demand("TEST_NESTED_SHARDS", || EnvarDef::Unset);
+    set_env_var("TEST_NESTED_SHARDS", "1,2;x");
+    assert!(matches!(
+        SHARDS.value(),
+        Err(EnvarError::ParseError {
+            typename: "u32",
+            ..
+        })
+    ));
+
+    clear_env_var("TEST_NESTED_ROUTES");
+    clear_env_var("TEST_NESTED_SHARDS");
+}
+
+#[cfg(feature = "list")]
+#[test]
+fn test_list_item_positions() {
+    use crate::{CommaList, LazyList, Secret};
+    let _lock = get_test_lock();
+
+    static PORTS: Envar<ListEnvar<u16, CommaList>> =
+        Envar::on_demand("TEST_LIST_POSITION", || EnvarDef::Unset);
+    set_env_var("TEST_LIST_POSITION", "80, ,443,http,8080");
+    match PORTS.value() {
+        Err(EnvarError::ParseError { value, reason, .. }) => {
+            assert_eq!(value, "http");
+            assert!(
+                reason
+                    .as_str()
+                    .starts_with("item 3 of TEST_LIST_POSITION: \"http\": "),
+                "{}",
+                reason
+            );
+        }
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+
+    static LAZY_PORTS: Envar<LazyList<u16, CommaList>> =
+        Envar::on_demand("TEST_LIST_POSITION", || EnvarDef::Unset);
+    let error = LAZY_PORTS
+        .value()
+        .unwrap()
+        .iter_parse()
+        .find_map(Result::err)
+        .unwrap();
+    assert!(matches!(
+        error,
+        EnvarError::ParseError { reason, .. } if reason.as_str().starts_with("item 3 of")
+    ));
+
+    // masked values stay masked
+    static TOKENS: Envar<ListEnvar<Secret<u32>, CommaList>> =
+        Envar::on_demand("TEST_LIST_POSITION", || EnvarDef::Unset);
+    match TOKENS.value() {
+        Err(EnvarError::ParseError { reason, .. }) => {
+            assert!(!reason.as_str().contains("http"), "{}", reason);
+            assert!(reason
+                .as_str()
+                .starts_with("item 3 of TEST_LIST_POSITION: \"***\""));
+        }
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+    clear_env_var("TEST_LIST_POSITION");
+}
+
+#[test]
+fn test_byte_size() {
+    use crate::ByteSize;
+    let _lock = get_test_lock();
+
+    static UPLOAD_LIMIT: Envar<ByteSize> = Envar::on_demand("TEST_BYTE_SIZE_UPLOAD_LIMIT", || {
+        EnvarDef::Default(ByteSize(1024))
+    });
+    clear_env_var("TEST_BYTE_SIZE_UPLOAD_LIMIT");
+    assert_eq!(UPLOAD_LIMIT.value().unwrap().bytes(), 1024);
+
+    for (raw, expected) in [
+        ("512", 512),
+        ("64K", 64_000),
+        ("64kb", 64_000),
+        ("10MiB", 10 * 1024 * 1024),
+        ("10 mi", 10 * 1024 * 1024),
+        ("2GB", 2_000_000_000),
+        ("1TiB", 1 << 40),
+        ("0B", 0),
+    ] {
+        set_env_var("TEST_BYTE_SIZE_UPLOAD_LIMIT", raw);
+        assert_eq!(
+            UPLOAD_LIMIT.value().unwrap(),
+            ByteSize(expected),
+            "Failed for value: {}",
+            raw
+        );
+    }
+
+    for bad in ["10XB", "MiB", "1.5GB", "-1K", "20000000PB"] {
+        set_env_var("TEST_BYTE_SIZE_UPLOAD_LIMIT", bad);
+        assert!(
+            matches!(
+                UPLOAD_LIMIT.value(),
+                Err(EnvarError::ParseError {
+                    typename: "ByteSize",
+                    ..
+                })
+            ),
+            "Failed for value: {}",
+            bad
+        );
+    }
+
+    let render = <crate::EnvarParser<ByteSize> as crate::EnvarParse<ByteSize>>::render;
+    assert_eq!(render(&ByteSize(10 * 1024 * 1024)).unwrap(), "10MiB");
+    assert_eq!(render(&ByteSize(2_000_000_000)).unwrap(), "2GB");
+    assert_eq!(render(&ByteSize(1500)).unwrap(), "1500");
+    assert_eq!(render(&ByteSize(0)).unwrap(), "0");
+
+    clear_env_var("TEST_BYTE_SIZE_UPLOAD_LIMIT");
+}
+
+#[test]
+fn test_ratio() {
+    use crate::{FractionRatio, Percent, Ratio};
+    let _lock = get_test_lock();
+
+    static SAMPLE_RATE: Envar<Ratio> = Envar::on_demand("TEST_RATIO_SAMPLE_RATE", || {
+        EnvarDef::Default(Ratio::new(0.1))
+    });
+    clear_env_var("TEST_RATIO_SAMPLE_RATE");
+    assert_eq!(SAMPLE_RATE.value().unwrap().get(), 0.1);
+
+    for (raw, expected) in [
+        ("75%", 0.75),
+        ("0.75", 0.75),
+        ("75", 0.75),
+        ("1", 1.0),
+        ("0%", 0.0),
+    ] {
+        set_env_var("TEST_RATIO_SAMPLE_RATE", raw);
+        assert_eq!(
+            SAMPLE_RATE.value().unwrap().get(),
+            expected,
+            "Failed for value: {}",
+            raw
+        );
+    }
+    for bad in ["101%", "-5%", "250", "half", "nan"] {
+        set_env_var("TEST_RATIO_SAMPLE_RATE", bad);
+        assert!(
+            matches!(
+                SAMPLE_RATE.value(),
+                Err(EnvarError::ParseError {
+                    typename: "Ratio",
+                    ..
+                })
+            ),
+            "Failed for value: {}",
+            bad
+        );
+    }
+
+    static CPU_FRACTION: Envar<Ratio<FractionRatio>> =
+        Envar::on_demand("TEST_RATIO_CPU_FRACTION", || EnvarDef::Unset);
+    set_env_var("TEST_RATIO_CPU_FRACTION", "0.5");
+    assert_eq!(CPU_FRACTION.value().unwrap().as_percent(), 50.0);
+    set_env_var("TEST_RATIO_CPU_FRACTION", "50%");
+    assert!(CPU_FRACTION.value().is_err());
+
+    static ROLLOUT: Envar<Percent> = Envar::on_demand("TEST_RATIO_ROLLOUT", || EnvarDef::Unset);
+    set_env_var("TEST_RATIO_ROLLOUT", "1");
+    assert_eq!(ROLLOUT.value().unwrap().get(), 0.01);
+
+    let render = <crate::EnvarParser<Percent> as crate::EnvarParse<Percent>>::render;
+    assert_eq!(render(&Ratio::new(0.25)).unwrap(), "25%");
+    let render = <crate::EnvarParser<Ratio> as crate::EnvarParse<Ratio>>::render;
+    assert_eq!(render(&Ratio::new(0.25)).unwrap(), "0.25");
+
+    clear_env_var("TEST_RATIO_SAMPLE_RATE");
+    clear_env_var("TEST_RATIO_CPU_FRACTION");
+    clear_env_var("TEST_RATIO_ROLLOUT");
+}
+
+#[test]
+fn test_char() {
+    let _lock = get_test_lock();
+
+    static DELIMITER: Envar<char> =
+        Envar::on_demand("TEST_CHAR_DELIMITER", || EnvarDef::Default(','));
+    clear_env_var("TEST_CHAR_DELIMITER");
+    assert_eq!(DELIMITER.value().unwrap(), ',');
+
+    for (raw, expected) in [
+        ("|", '|'),
+        (" ; ", ';'),
+        (" ", ' '),
+        ("\t", '\t'),
+        ("é", 'é'),
+    ] {
+        set_env_var("TEST_CHAR_DELIMITER", raw);
+        assert_eq!(
+            DELIMITER.value().unwrap(),
+            expected,
+            "Failed for value: {:?}",
+            raw
+        );
+    }
+
+    set_env_var("TEST_CHAR_DELIMITER", "  ");
+    assert_eq!(DELIMITER.value().unwrap(), ',');
+
+    set_env_var("TEST_CHAR_DELIMITER", "ab");
+    match DELIMITER.value() {
+        Err(EnvarError::ParseError {
+            typename, reason, ..
+        }) => {
+            assert_eq!(typename, "char");
+            assert_eq!(reason.as_str(), "expected exactly one character, found 2");
+        }
+        other => panic!("expected ParseError, got {:?}", other),
+    }
+
+    clear_env_var("TEST_CHAR_DELIMITER");
+}
+
+#[test]
+fn test_non_empty_and_trimmed_strings() {
+    use crate::{NonEmptyString, TrimmedString};
+    let _lock = get_test_lock();
+
+    static SERVICE_NAME: Envar<NonEmptyString> =
+        Envar::on_demand("TEST_STRINGS_SERVICE_NAME", || {
+            EnvarDef::Default(NonEmptyString::new("api").unwrap())
+        });
+    clear_env_var("TEST_STRINGS_SERVICE_NAME");
+    assert_eq!(SERVICE_NAME.value().unwrap().as_str(), "api");
+
+    set_env_var("TEST_STRINGS_SERVICE_NAME", " billing ");
+    assert_eq!(&*SERVICE_NAME.value().unwrap(), " billing ");
+
+    for blank in ["", "   ", "\n"] {
+        set_env_var("TEST_STRINGS_SERVICE_NAME", blank);
+        match SERVICE_NAME.value() {
+            Err(EnvarError::ParseError {
+                typename, reason, ..
+            }) => {
+                assert_eq!(typename, "NonEmptyString");
+                assert_eq!(reason.as_str(), "must not be empty or whitespace-only");
+            }
+            other => panic!("expected ParseError for {:?}, got {:?}", blank, other),
+        }
+    }
+
+    static REGION: Envar<TrimmedString> =
+        Envar::on_demand("TEST_STRINGS_REGION", || EnvarDef::Unset);
+    set_env_var("TEST_STRINGS_REGION", "  eu-west-1\n");
+    assert_eq!(REGION.value().unwrap().into_inner(), "eu-west-1");
+
+    clear_env_var("TEST_STRINGS_SERVICE_NAME");
+    clear_env_var("TEST_STRINGS_REGION");
+}
+
+#[test]
+fn test_hex_and_base64_bytes() {
+    use crate::{Base64Bytes, HexBytes, Secret};
+    let _lock = get_test_lock();
+
+    static SALT: Envar<HexBytes> = Envar::on_demand("TEST_BYTES_SALT", || EnvarDef::Unset);
+    set_env_var("TEST_BYTES_SALT", "0xDEadBEef");
+    assert_eq!(SALT.value().unwrap().as_slice(), &[0xde, 0xad, 0xbe, 0xef]);
+    for bad in ["abc", "zz", "0x12g4"] {
+        set_env_var("TEST_BYTES_SALT", bad);
+        assert!(
+            matches!(
+                SALT.value(),
+                Err(EnvarError::ParseError {
+                    typename: "HexBytes",
+                    ..
+                })
+            ),
+            "Failed for value: {}",
+            bad
+        );
+    }
+
+    static TOKEN: Envar<Base64Bytes> = Envar::on_demand("TEST_BYTES_TOKEN", || EnvarDef::Unset);
+    for (raw, expected) in [
+        ("aGVsbG8=", &b"hello"[..]),
+        ("aGVsbG8", b"hello"),
+        ("aGk=", b"hi"),
+        ("+/8=", &[0xfb, 0xff]),
+        ("-_8", &[0xfb, 0xff]),
+    ] {
+        set_env_var("TEST_BYTES_TOKEN", raw);
+        assert_eq!(
+            TOKEN.value().unwrap().as_slice(),
+            expected,
+            "Failed for value: {}",
+            raw
+        );
+    }
+    set_env_var("TEST_BYTES_TOKEN", "aGVsb*8=");
+    match TOKEN.value() {
+        Err(EnvarError::ParseError { reason, .. }) => {
+            assert_eq!(reason.as_str(), "invalid base64 character at position 6");
+        }
+        other => panic!("expected ParseError, got {:?}", other),
+    }
+
+    static KEY: Envar<Secret<HexBytes<[u8; 4]>>> =
+        Envar::on_demand("TEST_BYTES_KEY", || EnvarDef::Unset);
+    set_env_var("TEST_BYTES_KEY", "01020304");
+    assert_eq!(**KEY.value().unwrap().expose(), [1, 2, 3, 4]);
+    set_env_var("TEST_BYTES_KEY", "010203");
+    match KEY.value() {
+        Err(EnvarError::ParseError { value, reason, .. }) => {
+            assert_eq!(value, "***");
+            assert_eq!(reason.as_str(), "details hidden for a sensitive value");
+        }
+        other => panic!("expected ParseError, got {:?}", other),
+    }
+
+    let render = <crate::EnvarParser<Base64Bytes> as crate::EnvarParse<_>>::render;
+    for raw in ["", "a", "ab", "abc", "hello"] {
+        let encoded = render(&Base64Bytes(raw.as_bytes().to_vec())).unwrap();
+        set_env_var("TEST_BYTES_TOKEN", &encoded);
+        if !raw.is_empty() {
+            assert_eq!(TOKEN.value().unwrap().as_slice(), raw.as_bytes());
+        }
+    }
+    assert_eq!(render(&Base64Bytes(b"hello".to_vec())).unwrap(), "aGVsbG8=");
+    let render = <crate::EnvarParser<HexBytes<[u8; 2]>> as crate::EnvarParse<_>>::render;
+    assert_eq!(render(&HexBytes([0xab, 0x01])).unwrap(), "ab01");
+
+    clear_env_var("TEST_BYTES_SALT");
+    clear_env_var("TEST_BYTES_TOKEN");
+    clear_env_var("TEST_BYTES_KEY");
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn test_uuid() {
+    use ::uuid::Uuid;
+    let _lock = get_test_lock();
+
+    static TENANT_ID: Envar<Uuid> = Envar::on_demand("TEST_UUID_TENANT_ID", || EnvarDef::Unset);
+    let expected = Uuid::from_u128(0x67e55044_10b1_426f_9247_bb680e5fe0c8);
+    for raw in [
+        "67e55044-10b1-426f-9247-bb680e5fe0c8",
+        "67E5504410B1426F9247BB680E5FE0C8",
+        " {67e55044-10b1-426f-9247-bb680e5fe0c8} ",
+        "urn:uuid:67e55044-10b1-426f-9247-bb680e5fe0c8",
+    ] {
+        set_env_var("TEST_UUID_TENANT_ID", raw);
+        assert_eq!(
+            TENANT_ID.value().unwrap(),
+            expected,
+            "Failed for value: {}",
+            raw
+        );
+    }
+
+    set_env_var("TEST_UUID_TENANT_ID", "67e55044-10b1-426f-9247");
+    assert!(matches!(
+        TENANT_ID.value(),
+        Err(EnvarError::ParseError {
+            typename: "Uuid",
+            ..
+        })
+    ));
+
+    let render = <crate::EnvarParser<Uuid> as crate::EnvarParse<Uuid>>::render;
+    assert_eq!(
+        render(&expected).unwrap(),
+        "67e55044-10b1-426f-9247-bb680e5fe0c8"
+    );
+    assert!(crate::docgen::json_schema_of(&[&TENANT_ID]).contains("\"format\": \"uuid\""));
+    clear_env_var("TEST_UUID_TENANT_ID");
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn test_chrono() {
+    use ::chrono::{DateTime, FixedOffset, NaiveDate, Utc};
+    let _lock = get_test_lock();
+
+    static WINDOW_START: Envar<DateTime<Utc>> =
+        Envar::on_demand("TEST_CHRONO_WINDOW_START", || EnvarDef::Unset);
+    let expected = DateTime::from_timestamp(1714546800, 0).unwrap();
+    for raw in [
+        "2024-05-01T09:00:00+02:00",
+        "2024-05-01T07:00:00Z",
+        "1714546800",
+    ] {
+        set_env_var("TEST_CHRONO_WINDOW_START", raw);
+        assert_eq!(
+            WINDOW_START.value().unwrap(),
+            expected,
+            "Failed for value: {}",
+            raw
+        );
+    }
+    set_env_var("TEST_CHRONO_WINDOW_START", "2024-05-01 09:00");
+    assert!(matches!(
+        WINDOW_START.value(),
+        Err(EnvarError::ParseError {
+            typename: "DateTime<Utc>",
+            ..
+        })
+    ));
+
+    static LOCAL_START: Envar<DateTime<FixedOffset>> =
+        Envar::on_demand("TEST_CHRONO_LOCAL_START", || EnvarDef::Unset);
+    set_env_var("TEST_CHRONO_LOCAL_START", "2024-05-01T09:00:00+02:00");
+    assert_eq!(
+        LOCAL_START.value().unwrap().offset().local_minus_utc(),
+        7200
+    );
+
+    static LAUNCH_DATE: Envar<NaiveDate> =
+        Envar::on_demand("TEST_CHRONO_LAUNCH_DATE", || EnvarDef::Unset);
+    set_env_var("TEST_CHRONO_LAUNCH_DATE", "2024-05-01");
+    assert_eq!(
+        LAUNCH_DATE.value().unwrap(),
+        NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()
+    );
+    set_env_var("TEST_CHRONO_LAUNCH_DATE", "2024-02-30");
+    assert!(LAUNCH_DATE.value().is_err());
+
+    let render = <crate::EnvarParser<DateTime<Utc>> as crate::EnvarParse<_>>::render;
+    assert_eq!(render(&expected).unwrap(), "2024-05-01T07:00:00Z");
+    let schema = crate::docgen::json_schema_of(&[&WINDOW_START, &LAUNCH_DATE]);
+    assert!(schema.contains("\"format\": \"date-time\""), "{}", schema);
+    assert!(schema.contains("\"format\": \"date\""), "{}", schema);
+
+    clear_env_var("TEST_CHRONO_WINDOW_START");
+    clear_env_var("TEST_CHRONO_LOCAL_START");
+    clear_env_var("TEST_CHRONO_LAUNCH_DATE");
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn test_time() {
+    use ::time::{Date, Month, OffsetDateTime, UtcOffset};
+    let _lock = get_test_lock();
+
+    static CERT_CUTOFF: Envar<OffsetDateTime> =
+        Envar::on_demand("TEST_TIME_CERT_CUTOFF", || EnvarDef::Unset);
+    set_env_var("TEST_TIME_CERT_CUTOFF", "2024-05-01T09:00:00+02:00");
+    let cutoff = CERT_CUTOFF.value().unwrap();
+    assert_eq!(cutoff.unix_timestamp(), 1714546800);
+    assert_eq!(cutoff.offset(), UtcOffset::from_hms(2, 0, 0).unwrap());
+    set_env_var("TEST_TIME_CERT_CUTOFF", "1714546800");
+    assert_eq!(CERT_CUTOFF.value().unwrap().offset(), UtcOffset::UTC);
+    set_env_var("TEST_TIME_CERT_CUTOFF", "tomorrow");
+    assert!(matches!(
+        CERT_CUTOFF.value(),
+        Err(EnvarError::ParseError {
+            typename: "OffsetDateTime",
+            ..
+        })
+    ));
+
+    static LAUNCH_DATE: Envar<Date> = Envar::on_demand("TEST_TIME_LAUNCH_DATE", || EnvarDef::Unset);
+    set_env_var("TEST_TIME_LAUNCH_DATE", "2024-05-01");
+    let date = LAUNCH_DATE.value().unwrap();
+    assert_eq!(date, Date::from_calendar_date(2024, Month::May, 1).unwrap());
+
+    let render = <crate::EnvarParser<Date> as crate::EnvarParse<_>>::render;
+    assert_eq!(render(&date).unwrap(), "2024-05-01");
+
+    clear_env_var("TEST_TIME_CERT_CUTOFF");
+    clear_env_var("TEST_TIME_LAUNCH_DATE");
+}
+
+#[cfg(feature = "semver")]
+#[test]
+fn test_semver() {
+    use ::semver::{Version, VersionReq};
+    let _lock = get_test_lock();
+
+    static MIN_CLIENT_VERSION: Envar<VersionReq> =
+        Envar::on_demand("TEST_SEMVER_MIN_CLIENT_VERSION", || EnvarDef::Unset);
+    set_env_var("TEST_SEMVER_MIN_CLIENT_VERSION", ">=1.4.0, <3");
+    let req = MIN_CLIENT_VERSION.value().unwrap();
+    assert!(req.matches(&Version::new(2, 0, 0)));
+    assert!(!req.matches(&Version::new(1, 3, 9)));
+    set_env_var("TEST_SEMVER_MIN_CLIENT_VERSION", ">=1.4.x.y");
+    assert!(matches!(
+        MIN_CLIENT_VERSION.value(),
+        Err(EnvarError::ParseError {
+            typename: "VersionReq",
+            ..
+        })
+    ));
+
+    static APP_VERSION: Envar<Version> =
+        Envar::on_demand("TEST_SEMVER_APP_VERSION", || EnvarDef::Unset);
+    for raw in ["2.0.0-rc.1", "v2.0.0-rc.1"] {
+        set_env_var("TEST_SEMVER_APP_VERSION", raw);
+        assert_eq!(
+            APP_VERSION.value().unwrap(),
+            Version::parse("2.0.0-rc.1").unwrap(),
+            "Failed for value: {}",
+            raw
+        );
+    }
+    set_env_var("TEST_SEMVER_APP_VERSION", "2.0");
+    match APP_VERSION.value() {
+        Err(EnvarError::ParseError {
+            typename, value, ..
+        }) => {
+            assert_eq!(typename, "Version");
+            assert_eq!(value, "2.0");
+        }
+        other => panic!("expected ParseError, got {:?}", other),
+    }
+
+    clear_env_var("TEST_SEMVER_MIN_CLIENT_VERSION");
+    clear_env_var("TEST_SEMVER_APP_VERSION");
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_regex() {
+    use ::regex::Regex;
+    let _lock = get_test_lock();
+
+    static REDACT_PATTERN: Envar<Regex> = Envar::on_demand("TEST_REGEX_REDACT_PATTERN", || {
+        EnvarDef::Default(Regex::new("token=\\w+").unwrap())
+    });
+    clear_env_var("TEST_REGEX_REDACT_PATTERN");
+    assert!(REDACT_PATTERN.value().unwrap().is_match("token=abc"));
+
+    set_env_var("TEST_REGEX_REDACT_PATTERN", r"\d{4}-\d{4}");
+    assert!(REDACT_PATTERN.value().unwrap().is_match("card 1234-5678"));
+
+    set_env_var("TEST_REGEX_REDACT_PATTERN", "ab(c");
+    match REDACT_PATTERN.value() {
+        Err(EnvarError::ParseError {
+            typename, reason, ..
+        }) => {
+            assert_eq!(typename, "Regex");
+            assert_eq!(reason.as_str(), "unclosed group at column 3");
+        }
+        other => panic!("expected ParseError, got {:?}", other),
+    }
+
+    set_env_var("TEST_REGEX_REDACT_PATTERN", r"a\p{Bogus}");
+    match REDACT_PATTERN.value() {
+        Err(EnvarError::ParseError { reason, .. }) => {
+            assert!(
+                reason.as_str().ends_with("at column 2"),
+                "{}",
+                reason.as_str()
+            );
+        }
+        other => panic!("expected ParseError, got {:?}", other),
+    }
+
+    clear_env_var("TEST_REGEX_REDACT_PATTERN");
+}
+
+#[cfg(feature = "log")]
+#[test]
+fn test_log_level() {
+    use ::log::{Level, LevelFilter};
+    let _lock = get_test_lock();
+
+    static HTTP_LOG_LEVEL: Envar<LevelFilter> = Envar::on_demand("TEST_LOG_HTTP_LOG_LEVEL", || {
+        EnvarDef::Default(LevelFilter::Warn)
+    });
+    clear_env_var("TEST_LOG_HTTP_LOG_LEVEL");
+    assert_eq!(HTTP_LOG_LEVEL.value().unwrap(), LevelFilter::Warn);
+    for (raw, expected) in [("OFF", LevelFilter::Off), (" Debug ", LevelFilter::Debug)] {
+        set_env_var("TEST_LOG_HTTP_LOG_LEVEL", raw);
+        assert_eq!(
+            HTTP_LOG_LEVEL.value().unwrap(),
+            expected,
+            "Failed for value: {}",
+            raw
+        );
+    }
+    set_env_var("TEST_LOG_HTTP_LOG_LEVEL", "degub");
+    match HTTP_LOG_LEVEL.value() {
+        Err(EnvarError::ParseError {
+            typename, reason, ..
+        }) => {
+            assert_eq!(typename, "LevelFilter");
+            assert_eq!(
+                reason.as_str(),
+                "expected one of off, error, warn, info, debug, trace (did you mean debug?)"
+            );
+        }
+        other => panic!("expected ParseError, got {:?}", other),
+    }
+
+    static AUDIT_LEVEL: Envar<Level> = Envar::on_demand("TEST_LOG_AUDIT_LEVEL", || EnvarDef::Unset);
+    set_env_var("TEST_LOG_AUDIT_LEVEL", "TRACE");
+    assert_eq!(AUDIT_LEVEL.value().unwrap(), Level::Trace);
+    set_env_var("TEST_LOG_AUDIT_LEVEL", "off");
+    assert!(AUDIT_LEVEL.value().is_err());
+
+    let render = <crate::EnvarParser<LevelFilter> as crate::EnvarParse<_>>::render;
+    assert_eq!(render(&LevelFilter::Info).unwrap(), "info");
+
+    clear_env_var("TEST_LOG_HTTP_LOG_LEVEL");
+    clear_env_var("TEST_LOG_AUDIT_LEVEL");
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_tracing_level() {
+    use ::tracing::level_filters::LevelFilter;
+    use ::tracing::Level;
+    let _lock = get_test_lock();
+
+    static SPAN_LEVEL: Envar<Level> =
+        Envar::on_demand("TEST_TRACING_SPAN_LEVEL", || EnvarDef::Unset);
+    set_env_var("TEST_TRACING_SPAN_LEVEL", "Info");
+    assert_eq!(SPAN_LEVEL.value().unwrap(), Level::INFO);
+    set_env_var("TEST_TRACING_SPAN_LEVEL", "wran");
+    match SPAN_LEVEL.value() {
+        Err(EnvarError::ParseError { reason, .. }) => {
+            assert_eq!(
+                reason.as_str(),
+                "expected one of error, warn, info, debug, trace (did you mean warn?)"
+            );
+        }
+        other => panic!("expected ParseError, got {:?}", other),
+    }
+
+    static MAX_LEVEL: Envar<LevelFilter> =
+        Envar::on_demand("TEST_TRACING_MAX_LEVEL", || EnvarDef::Unset);
+    set_env_var("TEST_TRACING_MAX_LEVEL", "off");
+    assert_eq!(MAX_LEVEL.value().unwrap(), LevelFilter::OFF);
+
+    let render = <crate::EnvarParser<Level> as crate::EnvarParse<_>>::render;
+    assert_eq!(render(&Level::WARN).unwrap(), "warn");
+    let render = <crate::EnvarParser<LevelFilter> as crate::EnvarParse<_>>::render;
+    assert_eq!(render(&LevelFilter::ERROR).unwrap(), "error");
+
+    clear_env_var("TEST_TRACING_SPAN_LEVEL");
+    clear_env_var("TEST_TRACING_MAX_LEVEL");
+}
+
+#[cfg(feature = "ipnet")]
+#[test]
+fn test_ipnet() {
+    use crate::CommaList;
+    use ::ipnet::{IpNet, Ipv4Net};
+    let _lock = get_test_lock();
+
+    static TRUSTED_CIDRS: Envar<ListEnvar<IpNet, CommaList>> =
+        Envar::on_demand("TEST_IPNET_TRUSTED_CIDRS", || EnvarDef::Unset);
+    set_env_var(
+        "TEST_IPNET_TRUSTED_CIDRS",
+        "10.0.0.0/8, 192.168.0.0/16, 203.0.113.7, fd00::/8",
+    );
+    let cidrs = TRUSTED_CIDRS.value().unwrap();
+    let trusted = |ip: &str| {
+        let ip: std::net::IpAddr = ip.parse().unwrap();
+        cidrs.iter().any(|net| net.contains(&ip))
+    };
+    assert!(trusted("10.1.2.3"));
+    assert!(trusted("192.168.40.1"));
+    assert!(trusted("203.0.113.7"));
+    assert!(!trusted("203.0.113.8"));
+    assert!(trusted("fd12::1"));
+    assert_eq!(cidrs[2].prefix_len(), 32);
+
+    set_env_var("TEST_IPNET_TRUSTED_CIDRS", "10.0.0.0/8,10.0.0.0/33");
+    match TRUSTED_CIDRS.value() {
+        Err(EnvarError::ParseError {
+            typename, reason, ..
+        }) => {
+            assert_eq!(typename, "IpNet");
+            assert!(reason
+                .as_str()
+                .starts_with("item 2 of TEST_IPNET_TRUSTED_CIDRS"));
+        }
+        other => panic!("expected ParseError, got {:?}", other),
+    }
+
+    static ADMIN_NET: Envar<Ipv4Net> = Envar::on_demand("TEST_IPNET_ADMIN_NET", || EnvarDef::Unset);
+    set_env_var("TEST_IPNET_ADMIN_NET", "fd00::/8");
+    assert!(ADMIN_NET.value().is_err());
+    set_env_var("TEST_IPNET_ADMIN_NET", "172.16.0.0/12");
+    assert_eq!(ADMIN_NET.value().unwrap().to_string(), "172.16.0.0/12");
+
+    clear_env_var("TEST_IPNET_TRUSTED_CIDRS");
+    clear_env_var("TEST_IPNET_ADMIN_NET");
+}
+
+#[test]
+fn test_pair() {
+    use crate::{Pair, XPair};
+    use std::time::Duration;
+    let _lock = get_test_lock();
+
+    static RATE_LIMIT: Envar<Pair<u32, Duration>> =
+        Envar::on_demand("TEST_PAIR_RATE_LIMIT", || {
+            EnvarDef::Default(Pair::new(10, Duration::from_secs(1)))
+        });
+    clear_env_var("TEST_PAIR_RATE_LIMIT");
+    assert_eq!(RATE_LIMIT.value().unwrap().first, 10);
+
+    set_env_var("TEST_PAIR_RATE_LIMIT", "100 : 1m");
+    let (requests, window) = RATE_LIMIT.value().unwrap().into_tuple();
+    assert_eq!(requests, 100);
+    assert_eq!(window, Duration::from_secs(60));
+
+    set_env_var("TEST_PAIR_RATE_LIMIT", "100");
+    match RATE_LIMIT.value() {
+        Err(EnvarError::ParseError {
+            typename, reason, ..
+        }) => {
+            assert_eq!(typename, "Pair");
+            assert_eq!(reason.as_str(), "expected two parts separated by \":\"");
+        }
+        other => panic!("expected ParseError, got {:?}", other),
+    }
+
+    set_env_var("TEST_PAIR_RATE_LIMIT", "lots:1m");
+    match RATE_LIMIT.value() {
+        Err(EnvarError::ParseError {
+            typename, reason, ..
+        }) => {
+            assert_eq!(typename, "u32");
+            assert_eq!(
+                reason.as_str(),
+                "first part of TEST_PAIR_RATE_LIMIT: \"lots\": invalid digit found in string"
+            );
+        }
+        other => panic!("expected ParseError, got {:?}", other),
+    }
+
+    static RESOLUTION: Envar<Pair<u16, u16, XPair>> =
+        Envar::on_demand("TEST_PAIR_RESOLUTION", || EnvarDef::Unset);
+    set_env_var("TEST_PAIR_RESOLUTION", "1920x1080");
+    assert_eq!(RESOLUTION.value().unwrap(), Pair::new(1920, 1080));
+
+    let render = <crate::EnvarParser<Pair<u32, Duration>> as crate::EnvarParse<_>>::render;
+    assert_eq!(
+        render(&Pair::new(100, Duration::from_secs(60))).unwrap(),
+        "100:1m"
+    );
+
+    clear_env_var("TEST_PAIR_RATE_LIMIT");
+    clear_env_var("TEST_PAIR_RESOLUTION");
+}
+
+#[test]
+fn test_range_inclusive() {
+    use std::ops::RangeInclusive;
+    let _lock = get_test_lock();
+
+    static PORT_RANGE: Envar<RangeInclusive<u16>> =
+        Envar::on_demand("TEST_RANGE_PORT_RANGE", || EnvarDef::Default(8000..=8999));
+    clear_env_var("TEST_RANGE_PORT_RANGE");
+    assert_eq!(PORT_RANGE.value().unwrap(), 8000..=8999);
+
+    for raw in ["9000-9100", "9000..=9100", " 9000 - 9100 "] {
+        set_env_var("TEST_RANGE_PORT_RANGE", raw);
+        assert_eq!(
+            PORT_RANGE.value().unwrap(),
+            9000..=9100,
+            "Failed for value: {}",
+            raw
+        );
+    }
+
+    set_env_var("TEST_RANGE_PORT_RANGE", "9100-9000");
+    match PORT_RANGE.value() {
+        Err(EnvarError::ParseError {
+            typename, reason, ..
+        }) => {
+            assert_eq!(typename, "RangeInclusive");
+            assert_eq!(reason.as_str(), "start 9100 is greater than end 9000");
+        }
+        other => panic!("expected ParseError, got {:?}", other),
+    }
+    for bad in ["9000", "9000-", "é-5", "9000-70000"] {
+        set_env_var("TEST_RANGE_PORT_RANGE", bad);
+        assert!(
+            matches!(PORT_RANGE.value(), Err(EnvarError::ParseError { .. })),
+            "Failed for value: {}",
+            bad
+        );
+    }
+
+    static SHARDS: Envar<RangeInclusive<i32>> =
+        Envar::on_demand("TEST_RANGE_SHARDS", || EnvarDef::Unset);
+    set_env_var("TEST_RANGE_SHARDS", "-10--5");
+    assert_eq!(SHARDS.value().unwrap(), -10..=-5);
+
+    let render = <crate::EnvarParser<RangeInclusive<i32>> as crate::EnvarParse<_>>::render;
+    assert_eq!(render(&(-10..=-5)).unwrap(), "-10..=-5");
+
+    clear_env_var("TEST_RANGE_PORT_RANGE");
+    clear_env_var("TEST_RANGE_SHARDS");
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn test_json() {
+    use crate::Json;
+    use std::collections::HashMap;
+    let _lock = get_test_lock();
+
+    #[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+    struct Features {
+        a: bool,
+        b: u32,
+    }
+
+    static FEATURES: Envar<Json<Features>> =
+        Envar::on_demand("TEST_JSON_FEATURES", || EnvarDef::Unset);
+    set_env_var("TEST_JSON_FEATURES", r#"{"a": true, "b": 3}"#);
+    assert_eq!(
+        FEATURES.value().unwrap().into_inner(),
+        Features { a: true, b: 3 }
+    );
+
+    set_env_var("TEST_JSON_FEATURES", r#"{"a": true, "b": "3"}"#);
+    match FEATURES.value() {
+        Err(EnvarError::ParseError {
+            typename, reason, ..
+        }) => {
+            assert!(typename.ends_with("Features"), "{}", typename);
+            assert!(
+                reason.as_str().contains("line 1 column"),
+                "{}",
+                reason.as_str()
+            );
+        }
+        other => panic!("expected ParseError, got {:?}", other),
+    }
+
+    set_env_var("TEST_JSON_FEATURES", "  ");
+    assert!(matches!(FEATURES.value(), Err(EnvarError::NotSet(_))));
+
+    static LABELS: Envar<Json<HashMap<String, String>>> =
+        Envar::on_demand("TEST_JSON_LABELS", || {
+            EnvarDef::Default(Json(HashMap::new()))
+        });
+    set_env_var("TEST_JSON_LABELS", r#"{"team": "core"}"#);
+    assert_eq!(LABELS.value().unwrap()["team"], "core");
+
+    clear_env_var("TEST_JSON_FEATURES");
+    clear_env_var("TEST_JSON_LABELS");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_de_from_env() {
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Mode {
+        Fast,
+        Safe,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Db {
+        host: String,
+        port: u16,
+        #[serde(default)]
+        pool: Option<u32>,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct AppConfig {
+        name: String,
+        debug: bool,
+        ports: Vec<u16>,
+        mode: Mode,
+        db: Db,
+        labels: HashMap<String, String>,
+        timeout: Option<u64>,
+        #[serde(default)]
+        retries: u8,
+    }
+
+    // scoped values are thread-local, so this test needs no lock
+    let _name = crate::test::set_scoped("TEST_DE_NAME", "api");
+    let _debug = crate::test::set_scoped("TEST_DE_DEBUG", "on");
+    let _ports = crate::test::set_scoped("TEST_DE_PORTS", "80, 443,");
+    let _mode = crate::test::set_scoped("TEST_DE_MODE", "safe");
+    let _host = crate::test::set_scoped("TEST_DE_DB__HOST", "db.internal");
+    let _port = crate::test::set_scoped("TEST_DE_DB__PORT", "5432");
+    let _team = crate::test::set_scoped("TEST_DE_LABELS__TEAM", "core");
+    let _tier = crate::test::set_scoped("TEST_DE_LABELS__TIER", "1");
+
+    let config: AppConfig = crate::de::from_env("TEST_DE").unwrap();
+    assert_eq!(
+        config,
+        AppConfig {
+            name: "api".to_string(),
+            debug: true,
+            ports: vec![80, 443],
+            mode: Mode::Safe,
+            db: Db {
+                host: "db.internal".to_string(),
+                port: 5432,
+                pool: None,
+            },
+            labels: HashMap::from([
+                ("team".to_string(), "core".to_string()),
+                ("tier".to_string(), "1".to_string()),
+            ]),
+            timeout: None,
+            retries: 0,
+        }
+    );
+
+    {
+        let _port = crate::test::set_scoped("TEST_DE_DB__PORT", "postgres");
+        match crate::de::from_env::<AppConfig>("TEST_DE_") {
+            Err(EnvarError::ParseError {
+                varname, typename, ..
+            }) => {
+                assert_eq!(varname, "TEST_DE_DB__PORT");
+                assert_eq!(typename, "u16");
+            }
+            other => panic!("expected ParseError, got {:?}", other),
+        }
+    }
+    {
+        let _ports = crate::test::set_scoped("TEST_DE_PORTS", "80,http");
+        match crate::de::from_env::<AppConfig>("TEST_DE") {
+            Err(EnvarError::ParseError { reason, .. }) => {
+                assert_eq!(
+                    reason.as_str(),
+                    "item 2 of TEST_DE_PORTS: \"http\": invalid digit found in string"
+                );
+            }
+            other => panic!("expected ParseError, got {:?}", other),
+        }
+    }
+    {
+        let _mode = crate::test::set_scoped("TEST_DE_MODE", "turbo");
+        match crate::de::from_env::<AppConfig>("TEST_DE") {
+            Err(EnvarError::ParseError {
+                varname,
+                typename,
+                value,
+                reason,
+            }) => {
+                assert_eq!(varname, "TEST_DE_MODE");
+                assert_eq!(typename, "Mode");
+                assert_eq!(value, "turbo");
+                assert!(reason.as_str().contains("unknown variant `turbo`"));
+            }
+            other => panic!("expected ParseError, got {:?}", other),
+        }
+    }
+    {
+        let _host = crate::test::unset_scoped("TEST_DE_DB__HOST");
+        assert!(matches!(
+            crate::de::from_env::<AppConfig>("TEST_DE"),
+            Err(EnvarError::NotSet(varname)) if varname == "TEST_DE_DB__HOST"
+        ));
+    }
+}
@@ -0,0 +1,67 @@
+use crate::core::{EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::ErrorReason;
+use ::time::format_description::well_known::Rfc3339;
+use ::time::macros::format_description;
+use ::time::{Date, OffsetDateTime};
+use std::borrow::Cow;
+
+fn parse_error(
+    varname: Cow<'static, str>,
+    typename: &'static str,
+    value: &str,
+    reason: String,
+) -> EnvarError {
+    EnvarError::ParseError {
+        varname,
+        typename,
+        value: value.to_string(),
+        reason: ErrorReason::new(move || reason),
+    }
+}
+
+/// Accepts RFC 3339 (`2024-05-01T09:00:00+02:00`, keeping the offset) or unix
+/// timestamps in seconds (at `+00:00`); renders RFC 3339.
+impl EnvarParse<OffsetDateTime> for EnvarParser<OffsetDateTime> {
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<OffsetDateTime, EnvarError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(EnvarError::TryDefault(varname));
+        }
+        let parsed = match value.parse::<i64>() {
+            Ok(seconds) => OffsetDateTime::from_unix_timestamp(seconds)
+                .map_err(|_| "unix timestamp out of range".to_string()),
+            Err(_) => OffsetDateTime::parse(value, &Rfc3339)
+                .map_err(|e| format!("expected an RFC 3339 date-time or a unix timestamp: {}", e)),
+        };
+        parsed.map_err(|reason| parse_error(varname, "OffsetDateTime", value, reason))
+    }
+
+    fn render(value: &OffsetDateTime) -> Option<String> {
+        value.format(&Rfc3339).ok()
+    }
+}
+
+/// Accepts RFC 3339 full dates, `2024-05-01`.
+impl EnvarParse<Date> for EnvarParser<Date> {
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<Date, EnvarError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(EnvarError::TryDefault(varname));
+        }
+        Date::parse(value, format_description!("[year]-[month]-[day]")).map_err(|e| {
+            parse_error(
+                varname,
+                "Date",
+                value,
+                format!("expected a date such as 2024-05-01: {}", e),
+            )
+        })
+    }
+
+    fn render(value: &Date) -> Option<String> {
+        value
+            .format(format_description!("[year]-[month]-[day]"))
+            .ok()
+    }
+}
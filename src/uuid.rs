@@ -0,0 +1,28 @@
+use crate::core::{EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::ErrorReason;
+use ::uuid::Uuid;
+use std::borrow::Cow;
+
+/// Accepts the hyphenated (`67e55044-10b1-426f-9247-bb680e5fe0c8`), simple
+/// (`67e5504410b1426f9247bb680e5fe0c8`), braced (`{67e55044-...}`) and URN forms;
+/// renders the hyphenated one.
+impl EnvarParse<Uuid> for EnvarParser<Uuid> {
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<Uuid, EnvarError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(EnvarError::TryDefault(varname));
+        }
+
+        Uuid::try_parse(value).map_err(|e| EnvarError::ParseError {
+            varname,
+            typename: "Uuid",
+            value: value.to_string(),
+            reason: ErrorReason::new(move || e.to_string()),
+        })
+    }
+
+    fn render(value: &Uuid) -> Option<String> {
+        Some(value.hyphenated().to_string())
+    }
+}
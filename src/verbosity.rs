@@ -0,0 +1,68 @@
+use crate::core::{EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::ErrorReason;
+use std::borrow::Cow;
+
+/// A verbosity level, parsed from `v`-counts (`vvv`), integers (`3`)
+/// or level names (`off`, `error`, `warn`, `info`, `debug`, `trace`).
+///
+/// Empty values are treated as `0`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Verbosity(pub usize);
+
+impl Verbosity {
+    pub fn level(&self) -> usize {
+        self.0
+    }
+
+    pub fn is_quiet(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn is_at_least(&self, level: usize) -> bool {
+        self.0 >= level
+    }
+}
+
+impl std::fmt::Display for Verbosity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl EnvarParse<Verbosity> for EnvarParser<Verbosity> {
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<Verbosity, EnvarError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Ok(Verbosity(0));
+        }
+
+        if value.chars().all(|c| c == 'v' || c == 'V') {
+            return Ok(Verbosity(value.len()));
+        }
+
+        if let Ok(level) = value.parse::<usize>() {
+            return Ok(Verbosity(level));
+        }
+
+        for (name, level) in crate::special_constants::VERBOSITY_NAMES {
+            if name.eq_ignore_ascii_case(value) {
+                return Ok(Verbosity(*level));
+            }
+        }
+
+        Err(EnvarError::ParseError {
+            varname,
+            typename: "Verbosity",
+            value: value.to_string(),
+            reason: ErrorReason::new(|| {
+                "expected a run of 'v', an integer, or one of off, error, warn, info, debug, trace"
+                    .to_string()
+            }),
+        })
+    }
+
+    fn render(value: &Verbosity) -> Option<String> {
+        Some(value.to_string())
+    }
+}
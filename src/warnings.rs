@@ -0,0 +1,28 @@
+use std::sync::{PoisonError, RwLock};
+
+type WarningHandler = Box<dyn Fn(&str) + Send + Sync>;
+
+static HANDLER: RwLock<Option<WarningHandler>> = RwLock::new(None);
+
+/// Routes warnings (lenient parsing fallbacks, deprecated names, stale values, ...)
+/// to `handler` instead of stderr, e.g. into an application's logger.
+pub fn set_warning_handler(handler: impl Fn(&str) + Send + Sync + 'static) {
+    *HANDLER.write().unwrap_or_else(PoisonError::into_inner) = Some(Box::new(handler));
+}
+
+/// Restores printing warnings to stderr.
+pub fn reset_warning_handler() {
+    *HANDLER.write().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// Reports a recoverable configuration problem through the installed handler, or on stderr.
+pub(crate) fn emit(message: &str) {
+    match HANDLER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_deref()
+    {
+        Some(handler) => handler(message),
+        None => eprintln!("typed-env: warning: {}", message),
+    }
+}
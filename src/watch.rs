@@ -0,0 +1,105 @@
+use crate::core::{Envar, EnvarDef, EnvarParse, EnvarParser};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Tracks the effective value of an `Envar`, reporting when it changes.
+///
+/// Created by `Envar::watch`. Changes are detected when `refresh` is called,
+/// or periodically on a background thread after `spawn_polling`.
+/// Values that fail to resolve are not reported; the last good value is kept.
+pub struct Watch<T: 'static, F: 'static> {
+    envar: &'static Envar<T, F>,
+    last: Option<T>,
+}
+
+impl<T, F> Envar<T, F>
+where
+    T: Clone + PartialEq + 'static,
+    EnvarParser<T>: EnvarParse<T>,
+    F: Fn() -> EnvarDef<T>,
+{
+    /// Starts watching this variable from its current value.
+    pub fn watch(&'static self) -> Watch<T, F> {
+        Watch {
+            envar: self,
+            last: self.value().ok(),
+        }
+    }
+}
+
+impl<T, F> Watch<T, F>
+where
+    T: Clone + PartialEq + 'static,
+    EnvarParser<T>: EnvarParse<T>,
+    F: Fn() -> EnvarDef<T>,
+{
+    /// The last value seen, if any resolved successfully.
+    pub fn current(&self) -> Option<&T> {
+        self.last.as_ref()
+    }
+
+    /// Re-reads the variable, returning the new value if it differs from the last one seen.
+    pub fn refresh(&mut self) -> Option<T> {
+        let value = self.envar.value().ok()?;
+        if self.last.as_ref() == Some(&value) {
+            return None;
+        }
+        self.last = Some(value.clone());
+        Some(value)
+    }
+
+    /// Calls `refresh` every `interval` on a background thread, passing each change
+    /// to `on_change`, until the returned handle is stopped or dropped.
+    pub fn spawn_polling(
+        mut self,
+        interval: Duration,
+        mut on_change: impl FnMut(T) + Send + 'static,
+    ) -> WatchHandle
+    where
+        Envar<T, F>: Sync,
+        T: Send,
+    {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stopped = stopped.clone();
+            std::thread::spawn(move || loop {
+                std::thread::sleep(interval);
+                if stopped.load(Ordering::SeqCst) {
+                    return;
+                }
+                if let Some(value) = self.refresh() {
+                    on_change(value);
+                }
+            })
+        };
+        WatchHandle {
+            stopped,
+            thread: Some(thread),
+        }
+    }
+}
+
+/// Stops a polling `Watch` when dropped or stopped.
+pub struct WatchHandle {
+    stopped: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl WatchHandle {
+    /// Stops polling and waits for the background thread to finish.
+    pub fn stop(mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        // the thread exits at its next wake-up; dropping does not wait for it
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
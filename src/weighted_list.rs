@@ -0,0 +1,126 @@
+use crate::core::{parse_item, EnvarParse, EnvarParser};
+use crate::error::EnvarError;
+use crate::ErrorReason;
+use std::borrow::Cow;
+use std::sync::Arc;
+
+/// Items with relative weights, parsed from `a:3,b:1`, e.g. for traffic splitting.
+///
+/// Every item needs a weight after its last `:`, so items may contain colons
+/// themselves (`host:8080:3`). Weights are non-negative and need not sum to 1.
+pub struct WeightedList<T> {
+    _items: Arc<Vec<(T, f64)>>,
+    _total: f64,
+}
+
+impl<T> Clone for WeightedList<T> {
+    fn clone(&self) -> Self {
+        Self {
+            _items: self._items.clone(),
+            _total: self._total,
+        }
+    }
+}
+
+impl<T> WeightedList<T> {
+    /// Items with their weights as given
+    pub fn items(&self) -> &[(T, f64)] {
+        &self._items
+    }
+
+    /// Items with their weights normalized to sum to 1
+    pub fn normalized(&self) -> impl Iterator<Item = (&T, f64)> + '_ {
+        self._items
+            .iter()
+            .map(move |(item, weight)| (item, weight / self._total))
+    }
+
+    /// Picks an item in proportion to its weight, given `sample` uniformly
+    /// distributed in `[0, 1)`, e.g. `rand::random::<f64>()`.
+    pub fn pick(&self, sample: f64) -> &T {
+        let mut threshold = sample.clamp(0.0, 1.0) * self._total;
+        for (item, weight) in self._items.iter() {
+            if threshold < *weight {
+                return item;
+            }
+            threshold -= weight;
+        }
+        // rounding, or a sample of exactly 1: the last item with any weight
+        let (item, _) = self
+            ._items
+            .iter()
+            .rev()
+            .find(|(_, weight)| *weight > 0.0)
+            .expect("a parsed WeightedList has a positive total weight");
+        item
+    }
+
+    /// The normalized weight of `item`, summed over its occurrences
+    pub fn weight_of(&self, item: &T) -> f64
+    where
+        T: PartialEq,
+    {
+        self._items
+            .iter()
+            .filter(|(candidate, _)| candidate == item)
+            .map(|(_, weight)| weight / self._total)
+            .sum()
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for WeightedList<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "WeightedList {{ _items: {:?} }}", self._items)
+    }
+}
+
+impl<T> EnvarParse<WeightedList<T>> for EnvarParser<WeightedList<T>>
+where
+    EnvarParser<T>: EnvarParse<T>,
+{
+    fn parse(varname: Cow<'static, str>, value: &str) -> Result<WeightedList<T>, EnvarError> {
+        let invalid = |reason: String| EnvarError::ParseError {
+            varname: varname.clone(),
+            typename: "WeightedList",
+            value: value.to_string(),
+            reason: ErrorReason::new(move || reason),
+        };
+
+        let mut items = vec![];
+        let entries = value.split(',').map(str::trim);
+        for (index, entry) in entries.filter(|entry| !entry.is_empty()).enumerate() {
+            let Some((item, weight)) = entry.rsplit_once(':') else {
+                return Err(invalid(format!(
+                    "missing weight in {:?}, expected item:weight",
+                    entry
+                )));
+            };
+            let weight = match weight.trim().parse::<f64>() {
+                Ok(weight) if weight.is_finite() && weight >= 0.0 => weight,
+                _ => return Err(invalid(format!("invalid weight in {:?}", entry))),
+            };
+            items.push((
+                parse_item::<T>(varname.clone(), index, item.trim())?,
+                weight,
+            ));
+        }
+
+        let total: f64 = items.iter().map(|(_, weight)| weight).sum();
+        if total <= 0.0 {
+            return Err(invalid("expected at least one positive weight".to_string()));
+        }
+        Ok(WeightedList {
+            _items: Arc::new(items),
+            _total: total,
+        })
+    }
+
+    fn render(value: &WeightedList<T>) -> Option<String> {
+        let items: Option<Vec<String>> = value
+            .items()
+            .iter()
+            .map(|(item, weight)| Some(format!("{}:{}", EnvarParser::<T>::render(item)?, weight)))
+            .collect();
+        Some(items?.join(","))
+    }
+}
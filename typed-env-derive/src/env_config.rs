@@ -0,0 +1,198 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Expr, Fields, LitStr};
+
+struct FieldAttrs {
+    rename: Option<LitStr>,
+    /// `Some(None)` for a bare `default`, `Some(Some(expr))` for `default = expr`
+    default: Option<Option<Expr>>,
+    description: Option<LitStr>,
+    example: Option<LitStr>,
+    unit: Option<LitStr>,
+    sensitive: bool,
+}
+
+fn parse_prefix(input: &DeriveInput) -> syn::Result<String> {
+    let mut prefix = String::new();
+    for attr in &input.attrs {
+        if !attr.path().is_ident("envar") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("prefix") {
+                prefix = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else {
+                Err(meta.error("unsupported envar attribute, expected `prefix = \"...\"`"))
+            }
+        })?;
+    }
+    Ok(prefix)
+}
+
+fn parse_field_attrs(field: &syn::Field) -> syn::Result<FieldAttrs> {
+    let mut attrs = FieldAttrs {
+        rename: None,
+        default: None,
+        description: None,
+        example: None,
+        unit: None,
+        sensitive: false,
+    };
+    for attr in &field.attrs {
+        if !attr.path().is_ident("envar") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                attrs.rename = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("default") {
+                attrs.default = Some(if meta.input.peek(syn::Token![=]) {
+                    Some(meta.value()?.parse()?)
+                } else {
+                    None
+                });
+                Ok(())
+            } else if meta.path.is_ident("description") {
+                attrs.description = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("example") {
+                attrs.example = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("unit") {
+                attrs.unit = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("sensitive") {
+                attrs.sensitive = true;
+                Ok(())
+            } else {
+                Err(meta.error(
+                    "unsupported envar field attribute, expected `rename`, `default`, `description`, `example`, `unit` or `sensitive`",
+                ))
+            }
+        })?;
+    }
+    Ok(attrs)
+}
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields,
+            _ => return Err(unsupported(&input)),
+        },
+        _ => return Err(unsupported(&input)),
+    };
+    let prefix = parse_prefix(&input)?;
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut reads = vec![];
+    let mut inits = vec![];
+    let mut schemas = vec![];
+    let mut slots = vec![];
+    for field in &fields.named {
+        let name = field.ident.as_ref().expect("named fields have identifiers");
+        let ty = &field.ty;
+        let attrs = parse_field_attrs(field)?;
+        let suffix = match &attrs.rename {
+            Some(rename) => rename.value(),
+            None => name.to_string().trim_start_matches("r#").to_uppercase(),
+        };
+        let default = match &attrs.default {
+            None => quote! { ::typed_env::EnvarDef::Unset },
+            Some(None) => {
+                quote! { ::typed_env::EnvarDef::Default(::std::default::Default::default()) }
+            }
+            // string literals convert into `String`, `Cow` and friends
+            Some(Some(
+                expr @ Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(_),
+                    ..
+                }),
+            )) => {
+                quote! { ::typed_env::EnvarDef::Default(::std::convert::Into::into(#expr)) }
+            }
+            Some(Some(expr)) => quote! { ::typed_env::EnvarDef::Default(#expr) },
+        };
+        let description = attrs
+            .description
+            .as_ref()
+            .map(|description| quote! { .description(#description) });
+        let example = attrs
+            .example
+            .as_ref()
+            .map(|example| quote! { .example(#example) });
+        let unit = attrs.unit.as_ref().map(|unit| quote! { .unit(#unit) });
+        let sensitive = attrs.sensitive.then(|| quote! { .sensitive() });
+        let slot = format_ident!("__field_{}", name);
+        let envar = quote! {
+            ::typed_env::Envar::<#ty, _>::on_demand_named(namespace.name(#suffix), || #default)
+                #description
+                #example
+                #unit
+                #sensitive
+        };
+        schemas.push(quote! {
+            let #slot = #envar;
+        });
+        slots.push(slot.clone());
+        reads.push(quote! {
+            let #slot = match #envar.value() {
+                ::std::result::Result::Ok(value) => ::std::option::Option::Some(value),
+                ::std::result::Result::Err(e) => {
+                    __errors.push(e);
+                    ::std::option::Option::None
+                }
+            };
+        });
+        inits.push(quote! {
+            #name: #slot.expect("every failed field is reported above"),
+        });
+    }
+
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Reads every field from its environment variable, reporting all failures at once.
+            pub fn load() -> ::std::result::Result<Self, ::typed_env::EnvarErrors> {
+                Self::load_from(&::typed_env::EnvNamespace::new(#prefix))
+            }
+
+            /// Like `load`, but with the names prefixed by `namespace` instead.
+            pub fn load_from(
+                namespace: &::typed_env::EnvNamespace,
+            ) -> ::std::result::Result<Self, ::typed_env::EnvarErrors> {
+                let mut __errors: ::std::vec::Vec<::typed_env::EnvarError> = ::std::vec::Vec::new();
+                #(#reads)*
+                if !__errors.is_empty() {
+                    return ::std::result::Result::Err(::typed_env::EnvarErrors { errors: __errors });
+                }
+                ::std::result::Result::Ok(Self { #(#inits)* })
+            }
+        }
+
+        ::typed_env::__with_docgen! {
+            impl #impl_generics #ident #ty_generics #where_clause {
+                /// A JSON Schema of the fields' variables: their types, defaults, and
+                /// which of them are required (see `typed_env::docgen::json_schema_of`).
+                pub fn json_schema() -> ::std::string::String {
+                    Self::json_schema_from(&::typed_env::EnvNamespace::new(#prefix))
+                }
+
+                /// Like `json_schema`, but with the names prefixed by `namespace` instead.
+                pub fn json_schema_from(namespace: &::typed_env::EnvNamespace) -> ::std::string::String {
+                    #(#schemas)*
+                    ::typed_env::docgen::json_schema_of(&[#(&#slots),*])
+                }
+            }
+        }
+    })
+}
+
+fn unsupported(input: &DeriveInput) -> syn::Error {
+    syn::Error::new_spanned(
+        &input.ident,
+        "EnvConfig can only be derived for structs with named fields",
+    )
+}
@@ -0,0 +1,108 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, LitStr};
+
+struct VariantAttrs {
+    rename: Option<LitStr>,
+    aliases: Vec<LitStr>,
+}
+
+fn parse_variant_attrs(variant: &syn::Variant) -> syn::Result<VariantAttrs> {
+    let mut attrs = VariantAttrs {
+        rename: None,
+        aliases: vec![],
+    };
+    for attr in &variant.attrs {
+        if !attr.path().is_ident("envar") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                attrs.rename = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("alias") {
+                attrs.aliases.push(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error(
+                    "unsupported envar variant attribute, expected `rename = \"...\"` or `alias = \"...\"`",
+                ))
+            }
+        })?;
+    }
+    Ok(attrs)
+}
+
+fn unsupported(input: &DeriveInput) -> syn::Error {
+    syn::Error::new_spanned(
+        &input.ident,
+        "EnvarEnum can only be derived for enums whose variants have no fields",
+    )
+}
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let Data::Enum(data) = &input.data else {
+        return Err(unsupported(&input));
+    };
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let typename = ident.to_string();
+
+    let mut names = vec![];
+    let mut parse_arms = vec![];
+    let mut render_arms = vec![];
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(unsupported(&input));
+        }
+        let variant_ident = &variant.ident;
+        let attrs = parse_variant_attrs(variant)?;
+        let name = match &attrs.rename {
+            Some(rename) => rename.value(),
+            None => variant_ident.to_string(),
+        };
+        let spellings = std::iter::once(name.clone())
+            .chain(attrs.aliases.iter().map(LitStr::value))
+            .collect::<Vec<_>>();
+        parse_arms.push(quote! {
+            if [#(#spellings),*].iter().any(|spelling| spelling.eq_ignore_ascii_case(value)) {
+                return ::std::result::Result::Ok(#ident::#variant_ident);
+            }
+        });
+        render_arms.push(quote! {
+            #ident::#variant_ident => #name,
+        });
+        names.push(name);
+    }
+    let reason = format!("expected one of {}", names.join(", "));
+
+    Ok(quote! {
+        impl #impl_generics ::typed_env::EnvarParse<#ident #ty_generics>
+            for ::typed_env::EnvarParser<#ident #ty_generics>
+        #where_clause
+        {
+            fn parse(
+                varname: ::std::borrow::Cow<'static, str>,
+                value: &str,
+            ) -> ::std::result::Result<#ident #ty_generics, ::typed_env::EnvarError> {
+                let value = value.trim();
+                if value.is_empty() {
+                    return ::std::result::Result::Err(::typed_env::EnvarError::TryDefault(varname));
+                }
+                #(#parse_arms)*
+                ::std::result::Result::Err(::typed_env::EnvarError::ParseError {
+                    varname,
+                    typename: #typename,
+                    value: value.to_string(),
+                    reason: ::typed_env::ErrorReason::new(|| #reason.to_string()),
+                })
+            }
+
+            fn render(value: &#ident #ty_generics) -> ::std::option::Option<::std::string::String> {
+                ::std::option::Option::Some(::std::string::ToString::to_string(match value {
+                    #(#render_arms)*
+                }))
+            }
+        }
+    })
+}
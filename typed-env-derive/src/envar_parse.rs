@@ -0,0 +1,350 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Fields, FieldsNamed, LitChar, Path, Type};
+
+struct ContainerAttrs {
+    validate: Option<Path>,
+    separator: Option<LitChar>,
+}
+
+fn parse_attrs(input: &DeriveInput) -> syn::Result<ContainerAttrs> {
+    let mut attrs = ContainerAttrs {
+        validate: None,
+        separator: None,
+    };
+    for attr in &input.attrs {
+        if !attr.path().is_ident("envar") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("validate") {
+                attrs.validate = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("separator") {
+                attrs.separator = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error(
+                    "unsupported envar attribute, expected `validate = path` or `separator = 'c'`",
+                ))
+            }
+        })?;
+    }
+    Ok(attrs)
+}
+
+/// Whether a field is marked `#[envar(default)]`
+fn has_default_attr(field: &syn::Field) -> syn::Result<bool> {
+    let mut default = false;
+    for attr in &field.attrs {
+        if !attr.path().is_ident("envar") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("default") {
+                default = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported envar field attribute, expected `default`"))
+            }
+        })?;
+    }
+    Ok(default)
+}
+
+fn unsupported(input: &DeriveInput) -> syn::Error {
+    syn::Error::new_spanned(
+        &input.ident,
+        "EnvarParse can only be derived for single-field tuple structs or structs with named fields",
+    )
+}
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let attrs = parse_attrs(&input)?;
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                if attrs.separator.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        &input.ident,
+                        "`separator` only applies to structs with named fields",
+                    ));
+                }
+                let inner_ty = fields.unnamed[0].ty.clone();
+                Ok(expand_newtype(&input, attrs, inner_ty))
+            }
+            Fields::Named(fields) => expand_key_values(&input, attrs, fields),
+            _ => Err(unsupported(&input)),
+        },
+        _ => Err(unsupported(&input)),
+    }
+}
+
+fn where_clause_with(input: &DeriveInput, field_tys: &[&Type]) -> syn::WhereClause {
+    let mut where_clause = input
+        .generics
+        .where_clause
+        .clone()
+        .unwrap_or_else(|| syn::parse_quote!(where));
+    for ty in field_tys {
+        where_clause.predicates.push(syn::parse_quote!(
+            ::typed_env::EnvarParser<#ty>: ::typed_env::EnvarParse<#ty>
+        ));
+    }
+    where_clause
+}
+
+/// Reports a failed `validate` as a parse error of the whole value, rendered by `raw`
+/// (or masked, for sensitive types)
+fn validation(
+    attrs: &ContainerAttrs,
+    input: &DeriveInput,
+    target: TokenStream,
+    raw: TokenStream,
+) -> TokenStream {
+    let ident = &input.ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    match &attrs.validate {
+        None => quote! {},
+        Some(validate) => quote! {
+            if let ::std::result::Result::Err(reason) = #validate(&#target) {
+                let reason = reason.to_string();
+                return ::std::result::Result::Err(::typed_env::EnvarError::ParseError {
+                    varname,
+                    typename: ::std::any::type_name::<#ident #ty_generics>(),
+                    value: if <Self as ::typed_env::EnvarParse<#ident #ty_generics>>::SENSITIVE {
+                        ::std::string::String::from("***")
+                    } else {
+                        #raw
+                    },
+                    reason: ::typed_env::ErrorReason::new(move || reason),
+                });
+            }
+        },
+    }
+}
+
+fn expand_newtype(input: &DeriveInput, attrs: ContainerAttrs, inner_ty: Type) -> TokenStream {
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let where_clause = where_clause_with(input, &[&inner_ty]);
+    let validate_str = validation(&attrs, input, quote!(inner), quote!(value.to_string()));
+    let validate_os = validation(
+        &attrs,
+        input,
+        quote!(inner),
+        quote!(value.to_string_lossy().into_owned()),
+    );
+
+    quote! {
+        impl #impl_generics ::typed_env::EnvarParse<#ident #ty_generics>
+            for ::typed_env::EnvarParser<#ident #ty_generics>
+        #where_clause
+        {
+            const SENSITIVE: bool =
+                <::typed_env::EnvarParser<#inner_ty> as ::typed_env::EnvarParse<#inner_ty>>::SENSITIVE;
+            const LIST_SEP: ::std::option::Option<&'static str> =
+                <::typed_env::EnvarParser<#inner_ty> as ::typed_env::EnvarParse<#inner_ty>>::LIST_SEP;
+            const ACCEPTS_NON_UNICODE: bool =
+                <::typed_env::EnvarParser<#inner_ty> as ::typed_env::EnvarParse<#inner_ty>>::ACCEPTS_NON_UNICODE;
+            const DETERMINISTIC: bool =
+                <::typed_env::EnvarParser<#inner_ty> as ::typed_env::EnvarParse<#inner_ty>>::DETERMINISTIC;
+
+            fn parse(
+                varname: ::std::borrow::Cow<'static, str>,
+                value: &str,
+            ) -> ::std::result::Result<#ident #ty_generics, ::typed_env::EnvarError> {
+                let inner = <::typed_env::EnvarParser<#inner_ty> as ::typed_env::EnvarParse<#inner_ty>>::parse(
+                    varname.clone(),
+                    value,
+                )?;
+                #validate_str
+                ::std::result::Result::Ok(#ident(inner))
+            }
+
+            fn parse_os(
+                varname: ::std::borrow::Cow<'static, str>,
+                value: &::std::ffi::OsStr,
+            ) -> ::std::result::Result<#ident #ty_generics, ::typed_env::EnvarError> {
+                let inner = <::typed_env::EnvarParser<#inner_ty> as ::typed_env::EnvarParse<#inner_ty>>::parse_os(
+                    varname.clone(),
+                    value,
+                )?;
+                #validate_os
+                ::std::result::Result::Ok(#ident(inner))
+            }
+
+            fn render(value: &#ident #ty_generics) -> ::std::option::Option<::std::string::String> {
+                <::typed_env::EnvarParser<#inner_ty> as ::typed_env::EnvarParse<#inner_ty>>::render(&value.0)
+            }
+        }
+    }
+}
+
+/// Parses `key=value` pairs separated by `separator` (`;` by default) into the named fields
+fn expand_key_values(
+    input: &DeriveInput,
+    attrs: ContainerAttrs,
+    fields: &FieldsNamed,
+) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let field_tys: Vec<&Type> = fields.named.iter().map(|field| &field.ty).collect();
+    let where_clause = where_clause_with(input, &field_tys);
+    let separator = attrs
+        .separator
+        .as_ref()
+        .map(|separator| separator.value())
+        .unwrap_or(';');
+    let separator_str = separator.to_string();
+
+    let mut slots = vec![];
+    let mut arms = vec![];
+    let mut inits = vec![];
+    let mut renders = vec![];
+    for field in &fields.named {
+        let name = field.ident.as_ref().expect("named fields have identifiers");
+        let key = name.to_string();
+        let ty = &field.ty;
+        let slot = format_ident!("__field_{}", name);
+        let seen = format_ident!("__seen_{}", name);
+        let duplicate = format!("duplicate key {:?}", key);
+        let masked = format!("{}=***", key);
+        slots.push(quote! {
+            let mut #slot: ::std::option::Option<#ty> = ::std::option::Option::None;
+            let mut #seen = false;
+        });
+        arms.push(quote! {
+            #key => {
+                if #seen {
+                    let __shown = if <::typed_env::EnvarParser<#ty> as ::typed_env::EnvarParse<#ty>>::SENSITIVE {
+                        ::std::string::String::from(#masked)
+                    } else {
+                        __pair.to_string()
+                    };
+                    return ::std::result::Result::Err(__invalid(__shown, #duplicate.to_string()));
+                }
+                #seen = true;
+                match <::typed_env::EnvarParser<#ty> as ::typed_env::EnvarParse<#ty>>::parse(
+                    varname.clone(),
+                    __raw,
+                ) {
+                    ::std::result::Result::Ok(parsed) => #slot = ::std::option::Option::Some(parsed),
+                    ::std::result::Result::Err(::typed_env::EnvarError::TryDefault(_)) => #slot = ::std::option::Option::None,
+                    // the field's own value and type are kept, so sensitive fields stay masked
+                    ::std::result::Result::Err(::typed_env::EnvarError::ParseError {
+                        varname,
+                        typename,
+                        value,
+                        reason,
+                    }) => {
+                        return ::std::result::Result::Err(::typed_env::EnvarError::ParseError {
+                            varname,
+                            typename,
+                            value,
+                            reason: ::typed_env::ErrorReason::new(move || {
+                                ::std::format!("key {:?}: {}", #key, reason)
+                            }),
+                        })
+                    }
+                    ::std::result::Result::Err(e) => return ::std::result::Result::Err(e),
+                }
+            }
+        });
+        let missing = if has_default_attr(field)? {
+            quote! { ::std::default::Default::default() }
+        } else {
+            let reason = format!("missing key {:?}", key);
+            quote! {
+                return ::std::result::Result::Err(__invalid(
+                    ::std::string::String::new(),
+                    #reason.to_string(),
+                ))
+            }
+        };
+        inits.push(quote! {
+            #name: match #slot {
+                ::std::option::Option::Some(parsed) => parsed,
+                ::std::option::Option::None => #missing,
+            },
+        });
+        renders.push(quote! {
+            __pairs.push(::std::format!(
+                "{}={}",
+                #key,
+                <::typed_env::EnvarParser<#ty> as ::typed_env::EnvarParse<#ty>>::render(&value.#name)?
+            ));
+        });
+    }
+    let validation = validation(&attrs, input, quote!(parsed), quote!(value.to_string()));
+
+    Ok(quote! {
+        impl #impl_generics ::typed_env::EnvarParse<#ident #ty_generics>
+            for ::typed_env::EnvarParser<#ident #ty_generics>
+        #where_clause
+        {
+            const SENSITIVE: bool = false
+                #(|| <::typed_env::EnvarParser<#field_tys> as ::typed_env::EnvarParse<#field_tys>>::SENSITIVE)*;
+
+            fn parse(
+                varname: ::std::borrow::Cow<'static, str>,
+                value: &str,
+            ) -> ::std::result::Result<#ident #ty_generics, ::typed_env::EnvarError> {
+                let __sensitive = <Self as ::typed_env::EnvarParse<#ident #ty_generics>>::SENSITIVE;
+                // errors carry only the offending pair, never the other fields
+                let __invalid = |pair: ::std::string::String, reason: ::std::string::String| {
+                    ::typed_env::EnvarError::ParseError {
+                        varname: varname.clone(),
+                        typename: ::std::any::type_name::<#ident #ty_generics>(),
+                        value: pair,
+                        reason: ::typed_env::ErrorReason::new(move || reason),
+                    }
+                };
+                #(#slots)*
+                for __pair in value.split(#separator) {
+                    let __pair = __pair.trim();
+                    if __pair.is_empty() {
+                        continue;
+                    }
+                    let ::std::option::Option::Some((__key, __raw)) = __pair.split_once('=') else {
+                        if __sensitive {
+                            return ::std::result::Result::Err(__invalid(
+                                ::std::string::String::from("***"),
+                                ::std::string::String::from("expected key=value"),
+                            ));
+                        }
+                        return ::std::result::Result::Err(__invalid(
+                            __pair.to_string(),
+                            ::std::format!("expected key=value, found {:?}", __pair),
+                        ));
+                    };
+                    let __raw = __raw.trim();
+                    match __key.trim() {
+                        #(#arms)*
+                        __other => {
+                            // an unknown key may be a misspelt sensitive one
+                            let __shown = if __sensitive {
+                                ::std::format!("{}=***", __other)
+                            } else {
+                                __pair.to_string()
+                            };
+                            return ::std::result::Result::Err(__invalid(
+                                __shown,
+                                ::std::format!("unknown key {:?}", __other),
+                            ));
+                        }
+                    }
+                }
+                let parsed = #ident { #(#inits)* };
+                #validation
+                ::std::result::Result::Ok(parsed)
+            }
+
+            fn render(value: &#ident #ty_generics) -> ::std::option::Option<::std::string::String> {
+                let mut __pairs: ::std::vec::Vec<::std::string::String> = ::std::vec::Vec::new();
+                #(#renders)*
+                ::std::option::Option::Some(__pairs.join(#separator_str))
+            }
+        }
+    })
+}
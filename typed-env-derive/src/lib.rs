@@ -0,0 +1,72 @@
+mod env_config;
+mod envar_enum;
+mod envar_parse;
+mod main_attr;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput, ItemFn};
+
+/// Implements `EnvarParse` for a single-field tuple struct by delegating to the inner type,
+/// or for a struct with named fields from one `key=value;...` value.
+///
+/// `#[envar(validate = path)]` runs `path(&inner) -> Result<(), impl Display>` (or
+/// `path(&self)` for named fields) after parsing, reporting failures as `EnvarError::ParseError`.
+///
+/// For named fields, `#[envar(separator = ',')]` replaces the `;` between pairs, and
+/// fields marked `#[envar(default)]` fall back to `Default::default()` when their key is
+/// missing. Unknown, duplicate and missing keys are reported as parse errors naming only
+/// the offending pair, and a field's own parse error is prefixed with its key. The struct
+/// is sensitive if any field type is.
+#[proc_macro_derive(EnvarParse, attributes(envar))]
+pub fn derive_envar_parse(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    envar_parse::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `EnvarParse` for an enum whose variants have no fields, matching
+/// the variant names case-insensitively (surrounding whitespace is ignored).
+///
+/// `#[envar(rename = "name")]` on a variant replaces the name it is parsed from and
+/// rendered as, and each `#[envar(alias = "name")]` adds another accepted spelling.
+/// Unknown values are reported as `EnvarError::ParseError`, listing the valid names.
+#[proc_macro_derive(EnvarEnum, attributes(envar))]
+pub fn derive_envar_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    envar_enum::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Generates `load() -> Result<Self, EnvarErrors>` for a struct with named fields,
+/// reading each field from its own environment variable.
+///
+/// A field `db_url` is read from `DB_URL`, prefixed by `#[envar(prefix = "MYAPP_")]`
+/// on the struct. Fields accept `#[envar(rename = "NAME")]` to replace the derived name,
+/// `#[envar(default)]` or `#[envar(default = expr)]` for a default, and
+/// `#[envar(description = "...")]`, `#[envar(example = "...")]`, `#[envar(unit = "...")]`
+/// and `#[envar(sensitive)]` as metadata.
+///
+/// `load_from(&EnvNamespace)` reads the fields with another prefix, and records
+/// their names in the namespace for `EnvNamespace::unknown`.
+#[proc_macro_derive(EnvConfig, attributes(envar))]
+pub fn derive_env_config(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    env_config::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Validates every registered variable before running the function body,
+/// printing an aggregated report and exiting non-zero on failure.
+///
+/// `#[typed_env::main(register(PORT, HOST))]` registers the listed statics first.
+#[proc_macro_attribute]
+pub fn main(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as main_attr::MainArgs);
+    let item = parse_macro_input!(item as ItemFn);
+    main_attr::expand(args, item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,50 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{ItemFn, Path, Token};
+
+pub struct MainArgs {
+    register: Vec<Path>,
+}
+
+impl Parse for MainArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut register = Vec::new();
+        while !input.is_empty() {
+            let ident: syn::Ident = input.parse()?;
+            if ident != "register" {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    "unsupported argument, expected `register(STATIC, ...)`",
+                ));
+            }
+            let content;
+            syn::parenthesized!(content in input);
+            let paths = Punctuated::<Path, Token![,]>::parse_terminated(&content)?;
+            register.extend(paths);
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(MainArgs { register })
+    }
+}
+
+pub fn expand(args: MainArgs, item: ItemFn) -> syn::Result<TokenStream> {
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = item;
+    let register = args.register;
+    Ok(quote! {
+        #(#attrs)*
+        #vis #sig {
+            #( ::typed_env::registry::register(&#register); )*
+            ::typed_env::registry::validate_all_or_exit();
+            #block
+        }
+    })
+}